@@ -2,11 +2,15 @@
 //! @Date: Create in 2024-07-17 13:41:12
 //! @Description:
 
-use std::{path::PathBuf, sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
-}};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+};
 
+use lazy_static::lazy_static;
 use log::{error, info};
 use open::that;
 use rust_common::{file_util, log_util};
@@ -15,6 +19,18 @@ use tokio::task;
 
 use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
 
+//默认工作区，数据直接放在根数据目录下(兼容之前的数据)
+pub const DEFAULT_WORKSPACE: &str = "default";
+//其他工作区所在文件夹(根数据目录下)
+const WORKSPACES_DIR: &str = "workspaces";
+//记录当前工作区的文件(根数据目录下)
+const CURRENT_WORKSPACE_FILE: &str = "workspace.txt";
+
+lazy_static! {
+    //当前工作区
+    static ref CURRENT_WORKSPACE: RwLock<Option<String>> = RwLock::new(None);
+}
+
 ///全局状态
 pub struct AppState {
     //异步阻塞任务数量
@@ -29,8 +45,6 @@ pub async fn init_log() -> GlobalResult<()> {
     to_global_result(r)
 }
 
-
-
 /// @Author: DengLibin
 /// @Date: Create in 2023-11-06 15:10:09
 /// @Description: 初始化
@@ -89,40 +103,173 @@ where
     });
 }
 
- /// @Author: DengLibin
- /// @Date: Create in 2024-12-16 11:42:28
- /// @Description: 获取用户主目录
-pub fn get_user_home()->GlobalResult<String> {
+/// @Author: DengLibin
+/// @Date: Create in 2024-12-16 11:42:28
+/// @Description: 获取用户主目录
+pub fn get_user_home() -> GlobalResult<String> {
     let home = dirs_next::home_dir();
-    if let Some(path) = home{
+    if let Some(path) = home {
         return Ok(path.as_os_str().to_str().unwrap().into());
     }
     return Err(GlobalError::new("获取用户主目录失败".into()));
 }
- /// @Author: DengLibin
- /// @Date: Create in 2024-12-16 11:52:17
- /// @Description: 获取数据目录
-pub fn get_data_dir()->String {
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 09:30:12
+/// @Description: 获取根数据目录(所有工作区的上级目录)
+pub fn get_root_data_dir() -> String {
     let home_dir = get_user_home().unwrap_or("".into());
     let data_dir = format!("{}/.my_search", home_dir);
     let bo = file_util::exist(&data_dir);
     if !bo {
-        let r  = file_util::create_dr(&data_dir);
+        let r = file_util::create_dr(&data_dir);
         if let Err(e) = r {
             error!("创建数据目录异常:{}", e);
-            return  "".into();
+            return "".into();
         }
     }
     return data_dir;
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2024-12-16 11:52:17
+/// @Description: 获取数据目录(当前工作区的数据目录)
+pub fn get_data_dir() -> GlobalResult<String> {
+    get_workspace_dir(&current_workspace())
+}
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 09:41:36
+/// @Description: 获取工作区的数据目录, 不存在则创建
+pub fn get_workspace_dir(workspace: &str) -> GlobalResult<String> {
+    let root_dir = get_root_data_dir();
+    if workspace == DEFAULT_WORKSPACE {
+        return Ok(root_dir);
+    }
+    let data_dir = format!("{}/{}/{}", root_dir, WORKSPACES_DIR, workspace);
+    if !file_util::exist(&data_dir) {
+        to_global_result(file_util::create_dir_all(&data_dir))?;
+    }
+    Ok(data_dir)
 }
- /// @Author: DengLibin
- /// @Date: Create in 2024-12-17 10:10:21
- /// @Description: 打开文件所在文件夹
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 09:50:03
+/// @Description: 当前工作区, 未设置时读取上次使用的工作区
+pub fn current_workspace() -> String {
+    if let Some(workspace) = CURRENT_WORKSPACE.read().unwrap().as_ref() {
+        return workspace.clone();
+    }
+    let file = format!("{}/{}", get_root_data_dir(), CURRENT_WORKSPACE_FILE);
+    let saved = std::fs::read_to_string(file).unwrap_or_default();
+    let saved = saved.trim();
+    let workspace = if check_workspace_name(saved).is_ok() {
+        saved.to_string()
+    } else {
+        DEFAULT_WORKSPACE.to_string()
+    };
+    *CURRENT_WORKSPACE.write().unwrap() = Some(workspace.clone());
+    workspace
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 10:02:45
+/// @Description: 切换工作区(不存在则创建)，并记录为下次启动使用的工作区
+/// 切换后需要重新打开数据库和索引
+pub fn set_current_workspace(workspace: &str) -> GlobalResult<()> {
+    check_workspace_name(workspace)?;
+    get_workspace_dir(workspace)?;
+    let file = format!("{}/{}", get_root_data_dir(), CURRENT_WORKSPACE_FILE);
+    to_global_result(std::fs::write(file, workspace))?;
+    *CURRENT_WORKSPACE.write().unwrap() = Some(workspace.to_string());
+    info!("切换工作区:{}", workspace);
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-14 09:20:41
+/// @Description: 本次运行使用的工作区(不存在则创建)，不改变下次启动使用的工作区
+pub fn use_workspace(workspace: &str) -> GlobalResult<()> {
+    check_workspace_name(workspace)?;
+    get_workspace_dir(workspace)?;
+    *CURRENT_WORKSPACE.write().unwrap() = Some(workspace.to_string());
+    info!("使用工作区:{}", workspace);
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 10:15:20
+/// @Description: 所有工作区(默认工作区在最前)
+pub fn list_workspaces() -> Vec<String> {
+    let mut workspaces = vec![];
+    let dir = format!("{}/{}", get_root_data_dir(), WORKSPACES_DIR);
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if check_workspace_name(name).is_ok() {
+                    workspaces.push(name.to_string());
+                }
+            }
+        }
+    }
+    workspaces.sort();
+    workspaces.insert(0, DEFAULT_WORKSPACE.to_string());
+    workspaces
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 10:21:58
+/// @Description: 检查工作区名称(作为文件夹名)
+fn check_workspace_name(workspace: &str) -> GlobalResult<()> {
+    let invalid = workspace.is_empty()
+        || workspace.len() > 64
+        || workspace.starts_with('.')
+        || workspace.chars().any(|c| {
+            matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control()
+        });
+    if invalid {
+        return Err(GlobalError::new(format!("无效的工作区名称:{}", workspace)));
+    }
+    Ok(())
+}
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-13 10:32:47
+/// @Description: 文件或文件夹占用的磁盘大小(字节)
+pub fn disk_size(path: &str) -> u64 {
+    let meta = std::fs::symlink_metadata(path);
+    let meta = match meta {
+        Ok(m) => m,
+        Err(_) => return 0,
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    let mut size = 0;
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if let Some(p) = entry.path().to_str() {
+                size += disk_size(p);
+            }
+        }
+    }
+    size
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2024-12-17 10:10:21
+/// @Description: 打开文件所在文件夹
 pub fn open_file_folder(file_path: &str) {
     let binding = PathBuf::from(file_path);
     let file_dir = binding.parent();
     if let Some(dir) = file_dir {
         let _ = that(dir);
     }
-}
\ No newline at end of file
+}
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 11:12:30
+/// @Description: 用默认程序打开文件
+pub fn open_file(file_path: &str) {
+    let _ = that(file_path);
+}
@@ -10,6 +10,9 @@ use super::cmd::check_output;
 
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+//7z 压缩解压工具路径
+pub const SEVEN_Z_PATH: &str = r#"./7z/7z.exe"#;
+
 /// @Author: DengLibin
 /// @Date: Create in 2024-07-25 18:00:16
 /// @Description: 解压文件
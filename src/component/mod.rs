@@ -1,7 +1,8 @@
- //! @Author: DengLibin
- //! @Date: Create in 2024-07-24 09:52:34
- //! @Description: 组件
+//! @Author: DengLibin
+//! @Date: Create in 2024-07-24 09:52:34
+//! @Description: 组件
 
- pub mod comp_7z;
- pub mod cmd;
- pub mod rust_c;
\ No newline at end of file
+pub mod cmd;
+pub mod comp_7z;
+pub mod rust_c;
+pub mod snapshot;
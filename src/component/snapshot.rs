@@ -0,0 +1,357 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-01-15 09:20:41
+//! @Description: 索引快照 导出 导入
+//!
+//! 快照是一个zip包，包含索引目录、数据库、用户词典和版本信息(snapshot.json)
+//!
+//! 导入时先解压到数据目录下的临时文件夹(不用关闭数据库和索引), 再把旧文件移到备份文件夹、
+//! 快照中的文件移进数据目录(同一目录下改名), 中途失败时还原旧文件。
+
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool};
+use tantivy::Index;
+use zip::{write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
+
+//快照格式版本，快照内容结构变更时加1
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+//快照包内的顶层文件夹
+const SNAPSHOT_DIR_NAME: &str = "my_search_snapshot";
+//版本信息文件
+const MANIFEST_FILE: &str = "snapshot.json";
+//数据目录下的临时文件夹
+const SNAPSHOT_TMP_DIR: &str = "snapshot_tmp";
+//数据目录下的备份文件夹, 导入时旧文件先移到这里
+const SNAPSHOT_BACKUP_DIR: &str = "snapshot_backup";
+//数据库的WAL文件, 导入时不能留在新数据库旁边
+const DB_WAL_ENTRIES: [&str; 2] = ["mysearch.db-wal", "mysearch.db-shm"];
+
+//数据目录下需要一起备份的词典文件(文件夹)
+const DICT_ENTRIES: [&str; 4] = ["dict", "stop_words.txt", "dict.sig", "synonyms.txt"];
+
+/// 快照版本信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,        //快照格式版本
+    pub app_version: String, //导出时的程序版本
+    pub create_time: i64,    //导出时间
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-15 09:41:07
+/// @Description: 导出快照
+/// @param data_dir: 数据目录
+/// @param tantivy_index: 索引, 复制索引文件期间持有读锁(写索引需要写锁)，保证是提交后的状态
+/// @param pool: sqlite连接池
+/// @param out_file: 输出的快照文件
+pub async fn export_snapshot(
+    data_dir: &str,
+    tantivy_index: Arc<RwLock<Index>>,
+    pool: &SqlitePool,
+    out_file: &str,
+) -> GlobalResult<()> {
+    let tmp_dir = format!("{}/{}", data_dir, SNAPSHOT_TMP_DIR);
+    let snapshot_dir = format!("{}/{}", tmp_dir, SNAPSHOT_DIR_NAME);
+    remove_if_exist(&tmp_dir)?;
+    to_global_result(fs::create_dir_all(&snapshot_dir))?;
+
+    let r = do_export(data_dir, tantivy_index, pool, &snapshot_dir, out_file).await;
+    remove_if_exist(&tmp_dir)?;
+    r
+}
+
+async fn do_export(
+    data_dir: &str,
+    tantivy_index: Arc<RwLock<Index>>,
+    pool: &SqlitePool,
+    snapshot_dir: &str,
+    out_file: &str,
+) -> GlobalResult<()> {
+    //索引和数据库在同一次持有索引读锁期间复制, 扫描写入索引需要写锁, 两者对应同一提交点
+    //耗时任务 交给阻塞线程池
+    let index_out_dir = format!("{}/indices", snapshot_dir);
+    let index_dir = format!("{}/indices", data_dir);
+    let db_out_file = format!("{}/mysearch.db", snapshot_dir);
+    let pool = pool.clone();
+    let handle = tokio::runtime::Handle::current();
+    let r = tokio::task::spawn_blocking(move || {
+        let index = tantivy_index.read().unwrap();
+        copy_index_files(&index, &index_dir, &index_out_dir)?;
+        //数据库 VACUUM INTO 生成一致的副本
+        let sql = r#"VACUUM INTO $1"#;
+        handle.block_on(async {
+            to_global_result(
+                sqlx::query::<Sqlite>(sql)
+                    .bind(db_out_file)
+                    .execute(&pool)
+                    .await,
+            )
+        })?;
+        Ok::<(), GlobalError>(())
+    })
+    .await;
+    to_global_result(r)??;
+
+    //词典
+    for entry in DICT_ENTRIES {
+        copy_all(
+            &format!("{}/{}", data_dir, entry),
+            &format!("{}/{}", snapshot_dir, entry),
+        )?;
+    }
+
+    //版本信息
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        create_time: rust_common::date::get_sys_timestamp_millis() as i64,
+    };
+    let manifest_json = to_global_result(serde_json::to_string_pretty(&manifest))?;
+    to_global_result(fs::write(
+        format!("{}/{}", snapshot_dir, MANIFEST_FILE),
+        manifest_json,
+    ))?;
+
+    //压缩
+    let snapshot_dir = snapshot_dir.to_string();
+    let out_file = out_file.to_string();
+    let r = tokio::task::spawn_blocking(move || zip_dir(&snapshot_dir, &out_file)).await;
+    to_global_result(r)??;
+    info!("导出快照完成");
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-15 10:12:53
+/// @Description: 复制最近一次提交的索引文件(meta.json 和其中记录的段文件)
+fn copy_index_files(index: &Index, index_dir: &str, out_dir: &str) -> GlobalResult<()> {
+    to_global_result(fs::create_dir_all(out_dir))?;
+    let segment_metas = to_global_result(index.searchable_segment_metas())?;
+    let mut files = vec!["meta.json".to_string()];
+    for segment_meta in segment_metas {
+        for file in segment_meta.list_files() {
+            if let Some(f) = file.to_str() {
+                files.push(f.to_string());
+            }
+        }
+    }
+    for file in files {
+        let src = format!("{}/{}", index_dir, file);
+        //没有删除文档的段不存在.del等文件
+        if Path::new(&src).is_file() {
+            to_global_result(fs::copy(&src, format!("{}/{}", out_dir, file)))?;
+        }
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-15 10:40:26
+/// @Description: 导入快照到数据目录, 会覆盖数据目录中的索引、数据库和词典
+/// 调用前需要关闭数据库连接和索引
+pub fn import_snapshot(snapshot_file: &str, data_dir: &str) -> GlobalResult<SnapshotManifest> {
+    let manifest = prepare_import(snapshot_file, data_dir)?;
+    apply_import(data_dir)?;
+    info!("导入快照完成:{}", snapshot_file);
+    Ok(manifest)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 10:05:22
+/// @Description: 解压快照到数据目录下的临时文件夹并检查版本(耗时, 不用关闭数据库和索引)
+pub fn prepare_import(snapshot_file: &str, data_dir: &str) -> GlobalResult<SnapshotManifest> {
+    let tmp_dir = format!("{}/{}", data_dir, SNAPSHOT_TMP_DIR);
+    remove_if_exist(&tmp_dir)?;
+    let r = unzip_and_check(snapshot_file, &tmp_dir);
+    if r.is_err() {
+        remove_if_exist(&tmp_dir)?;
+    }
+    r
+}
+
+fn unzip_and_check(snapshot_file: &str, tmp_dir: &str) -> GlobalResult<SnapshotManifest> {
+    unzip_file(snapshot_file, tmp_dir)?;
+    let snapshot_dir = format!("{}/{}", tmp_dir, SNAPSHOT_DIR_NAME);
+
+    //检查版本
+    let manifest_file = format!("{}/{}", snapshot_dir, MANIFEST_FILE);
+    if !Path::new(&manifest_file).is_file() {
+        return Err(GlobalError::new("不是有效的快照文件".into()));
+    }
+    let manifest_json = to_global_result(fs::read_to_string(manifest_file))?;
+    let manifest: SnapshotManifest = to_global_result(serde_json::from_str(&manifest_json))?;
+    if manifest.version > SNAPSHOT_VERSION {
+        return Err(GlobalError::new(format!(
+            "快照版本({})高于当前程序支持的版本({})，请升级程序",
+            manifest.version, SNAPSHOT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 10:12:48
+/// @Description: 用解压好的快照替换数据目录中的文件, 调用前需要关闭数据库连接和索引
+/// 旧文件(包括数据库的WAL文件)先移到备份文件夹, 再把快照中的文件移进来, 失败时还原
+pub fn apply_import(data_dir: &str) -> GlobalResult<()> {
+    let tmp_dir = format!("{}/{}", data_dir, SNAPSHOT_TMP_DIR);
+    let snapshot_dir = format!("{}/{}", tmp_dir, SNAPSHOT_DIR_NAME);
+    if !Path::new(&format!("{}/{}", snapshot_dir, MANIFEST_FILE)).is_file() {
+        return Err(GlobalError::new("没有解压好的快照".into()));
+    }
+    let backup_dir = format!("{}/{}", data_dir, SNAPSHOT_BACKUP_DIR);
+    remove_if_exist(&backup_dir)?;
+    to_global_result(fs::create_dir_all(&backup_dir))?;
+
+    let mut entries = vec!["indices", "mysearch.db"];
+    entries.extend(DICT_ENTRIES);
+    //已移到备份文件夹的, 已移进数据目录的
+    let mut moved_out: Vec<&str> = vec![];
+    let mut moved_in: Vec<&str> = vec![];
+    let r = (|| -> GlobalResult<()> {
+        for entry in entries.iter().chain(DB_WAL_ENTRIES.iter()) {
+            let target = format!("{}/{}", data_dir, entry);
+            if Path::new(&target).exists() {
+                to_global_result(fs::rename(&target, format!("{}/{}", backup_dir, entry)))?;
+                moved_out.push(*entry);
+            }
+        }
+        for entry in entries.iter() {
+            let src = format!("{}/{}", snapshot_dir, entry);
+            if Path::new(&src).exists() {
+                to_global_result(fs::rename(&src, format!("{}/{}", data_dir, entry)))?;
+                moved_in.push(*entry);
+            }
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = r {
+        error!("替换数据文件异常, 还原:{}", e);
+        for entry in moved_in {
+            if let Err(e) = remove_if_exist(&format!("{}/{}", data_dir, entry)) {
+                error!("还原时删除{}异常:{}", entry, e);
+            }
+        }
+        for entry in moved_out {
+            let backup = format!("{}/{}", backup_dir, entry);
+            if let Err(e) = fs::rename(&backup, format!("{}/{}", data_dir, entry)) {
+                error!("还原{}异常, 备份在{}:{}", entry, backup, e);
+            }
+        }
+        return Err(e);
+    }
+    remove_if_exist(&backup_dir)?;
+    remove_if_exist(&tmp_dir)?;
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 10:26:37
+/// @Description: 文件夹压缩为zip包, 包内顶层是这个文件夹
+fn zip_dir(dir: &str, out_file: &str) -> GlobalResult<()> {
+    let dir_path = Path::new(dir);
+    let prefix = dir_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(SNAPSHOT_DIR_NAME)
+        .to_string();
+    let mut zip = ZipWriter::new(to_global_result(File::create(out_file))?);
+    add_to_zip(&mut zip, dir, &prefix)?;
+    to_global_result(zip.finish())?;
+    Ok(())
+}
+
+fn add_to_zip(zip: &mut ZipWriter<File>, dir: &str, prefix: &str) -> GlobalResult<()> {
+    for entry in to_global_result(fs::read_dir(dir))? {
+        let entry = to_global_result(entry)?;
+        let Some(name) = entry.file_name().to_str().map(|name| name.to_string()) else {
+            continue;
+        };
+        let path = format!("{}/{}", dir, name);
+        let zip_name = format!("{}/{}", prefix, name);
+        let metadata = to_global_result(entry.metadata())?;
+        if metadata.is_dir() {
+            to_global_result(zip.add_directory(zip_name.as_str(), SimpleFileOptions::default()))?;
+            add_to_zip(zip, &path, &zip_name)?;
+        } else {
+            //索引段文件可能超过4G
+            let options =
+                SimpleFileOptions::default().large_file(metadata.len() >= u32::MAX as u64);
+            to_global_result(zip.start_file(zip_name.as_str(), options))?;
+            to_global_result(io::copy(&mut to_global_result(File::open(&path))?, zip))?;
+        }
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 10:34:15
+/// @Description: 解压zip包, 跳过路径在输出目录之外的条目
+fn unzip_file(file_path: &str, out_dir: &str) -> GlobalResult<()> {
+    let mut archive = to_global_result(ZipArchive::new(to_global_result(File::open(file_path))?))?;
+    for i in 0..archive.len() {
+        let mut file = to_global_result(archive.by_index(i))?;
+        let Some(name) = file.enclosed_name() else {
+            continue;
+        };
+        let out_path = Path::new(out_dir).join(name);
+        if file.is_dir() {
+            to_global_result(fs::create_dir_all(&out_path))?;
+            continue;
+        }
+        if let Some(parent) = out_path.parent() {
+            to_global_result(fs::create_dir_all(parent))?;
+        }
+        to_global_result(io::copy(
+            &mut file,
+            &mut to_global_result(File::create(&out_path))?,
+        ))?;
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-15 10:25:19
+/// @Description: 复制文件或文件夹, 源不存在时忽略
+fn copy_all(src: &str, dest: &str) -> GlobalResult<()> {
+    let src_path = Path::new(src);
+    if src_path.is_file() {
+        to_global_result(fs::copy(src, dest))?;
+        return Ok(());
+    }
+    if src_path.is_dir() {
+        to_global_result(fs::create_dir_all(dest))?;
+        for entry in to_global_result(fs::read_dir(src))? {
+            let entry = to_global_result(entry)?;
+            let name = entry.file_name();
+            if let Some(name) = name.to_str() {
+                copy_all(&format!("{}/{}", src, name), &format!("{}/{}", dest, name))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-15 10:28:02
+/// @Description: 删除文件或文件夹(存在时)
+fn remove_if_exist(path: &str) -> GlobalResult<()> {
+    let p = Path::new(path);
+    if p.is_dir() {
+        to_global_result(fs::remove_dir_all(p))?;
+    } else if p.is_file() {
+        to_global_result(fs::remove_file(p))?;
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use egui::ahash::HashSet;
 
 use log::error;
@@ -9,15 +11,26 @@ use sqlx::{FromRow, QueryBuilder, Row, Sqlite, SqlitePool};
 
 use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
 
+use super::sqlite_dao;
+
+//文件状态: 已建索引
+pub const STATUS_INDEXED: i32 = 0;
+//文件状态: 提取文本失败(超时、内存超出上限、提取进程崩溃等)
+pub const STATUS_FAILED: i32 = 1;
+
 //索引的文件夹
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct IndexFile {
     pub id: i64,
-    pub name: String,      //名称
-    pub path: String,      //路径
-    pub scan_time: i64,    //扫描时间
-    pub status: i32,       //状态
-    pub index_dir_id: i64, // 文件夹id
+    pub name: String,              //名称
+    pub path: String,              //路径
+    pub scan_time: i64,            //扫描时间
+    pub status: i32,               //状态
+    pub index_dir_id: i64,         // 文件夹id
+    pub fingerprint: Option<i64>,  //内容指纹(SimHash)
+    pub content_hash: Option<i64>, //内容哈希
+    pub encoding: Option<String>,  //文本文件的编码
+    pub error: Option<String>,     //提取失败的原因
 }
 impl IndexFile {
     /// @Author: DengLibin
@@ -38,8 +51,12 @@ impl IndexFile {
             name: file_name,
             path: file_path,
             scan_time: time as i64,
-            status: 0,
+            status: STATUS_INDEXED,
             index_dir_id,
+            fingerprint: None,
+            content_hash: None,
+            encoding: None,
+            error: None,
         }
     }
 }
@@ -54,7 +71,11 @@ pub async fn create_index_file_table(pool: &SqlitePool) -> GlobalResult<()> {
         "path" text,
         "scan_time" integer,
         "status" integer,
-        "index_dir_id" integer
+        "index_dir_id" integer,
+        "fingerprint" integer,
+        "content_hash" integer,
+        "encoding" text,
+        "error" text
         )"#;
 
     let query = sqlx::query::<Sqlite>(sql);
@@ -70,6 +91,11 @@ pub async fn create_index_file_table(pool: &SqlitePool) -> GlobalResult<()> {
         //提交
         to_global_result(tx.commit().await)?;
     }
+    //旧版本建的表没有这些列
+    sqlite_dao::add_column_if_missing(pool, "INDEX_FILE", "fingerprint", "integer").await?;
+    sqlite_dao::add_column_if_missing(pool, "INDEX_FILE", "content_hash", "integer").await?;
+    sqlite_dao::add_column_if_missing(pool, "INDEX_FILE", "encoding", "text").await?;
+    sqlite_dao::add_column_if_missing(pool, "INDEX_FILE", "error", "text").await?;
     Ok(())
 }
 
@@ -100,16 +126,17 @@ pub async fn select_by_index_dir_id(
 }
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-12 14:55:14
-/// @Description:
+/// @Description: 已建索引的文件路径(不含提取失败的, 重新扫描时再提取)
 pub async fn file_paths(
     pool: &SqlitePool,
     index_dir_id: i64, //所属索引文件夹id
 ) -> GlobalResult<HashSet<String>> {
-    let sql = r#"SELECT path FROM INDEX_FILE  where index_dir_id=$1"#;
+    let sql = r#"SELECT path FROM INDEX_FILE  where index_dir_id=$1 AND IFNULL(status, 0)<>$2"#;
 
     let rows = to_global_result(
         sqlx::query::<Sqlite>(sql)
             .bind(index_dir_id)
+            .bind(STATUS_FAILED)
             .fetch_all(pool)
             .await,
     )?;
@@ -120,12 +147,10 @@ pub async fn file_paths(
         .collect();
     Ok(paths)
 }
- /// @Author: DengLibin
- /// @Date: Create in 2024-12-16 12:26:44
- /// @Description: 总数
-pub async fn count(
-    pool: &SqlitePool,
-) -> i64{
+/// @Author: DengLibin
+/// @Date: Create in 2024-12-16 12:26:44
+/// @Description: 总数
+pub async fn count(pool: &SqlitePool) -> i64 {
     let sql = r#"SELECT count(1) FROM INDEX_FILE"#;
 
     let count = to_global_result(
@@ -136,7 +161,7 @@ pub async fn count(
     );
     if let Ok(c) = count {
         c
-    }else {
+    } else {
         error!("统计总数异常:{}", count.unwrap_err());
         0
     }
@@ -189,7 +214,7 @@ pub async fn add_index_file(
 /// @Description: 批量插入
 pub async fn insert_batch(pool: &SqlitePool, index_files: Vec<IndexFile>) -> GlobalResult<()> {
     let chunk_size = 1000; // 每批次插入 1000 条
-    let sql = r#"INSERT INTO INDEX_FILE("name", "path", "scan_time", "status", "index_dir_id")"#;
+    let sql = r#"INSERT INTO INDEX_FILE("name", "path", "scan_time", "status", "index_dir_id", "fingerprint", "content_hash", "encoding", "error")"#;
     for chunk in index_files.chunks(chunk_size) {
         let mut tx = to_global_result(pool.begin().await)?;
         let mut query_builder = QueryBuilder::new(sql);
@@ -202,6 +227,10 @@ pub async fn insert_batch(pool: &SqlitePool, index_files: Vec<IndexFile>) -> Glo
                 scan_time,
                 status,
                 index_dir_id,
+                fingerprint,
+                content_hash,
+                encoding,
+                error,
             } = index_file;
 
             builder
@@ -209,7 +238,11 @@ pub async fn insert_batch(pool: &SqlitePool, index_files: Vec<IndexFile>) -> Glo
                 .push_bind(path)
                 .push_bind(scan_time)
                 .push_bind(status)
-                .push_bind(index_dir_id);
+                .push_bind(index_dir_id)
+                .push_bind(fingerprint)
+                .push_bind(content_hash)
+                .push_bind(encoding)
+                .push_bind(error);
         });
 
         to_global_result(query_builder.build().execute(&mut *tx).await)?;
@@ -218,6 +251,165 @@ pub async fn insert_batch(pool: &SqlitePool, index_files: Vec<IndexFile>) -> Glo
     Ok(())
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 11:20:48
+/// @Description: 批量更新内容指纹
+/// @param fingerprints: (文件路径, 指纹, 内容哈希)
+pub async fn update_fingerprints(
+    pool: &SqlitePool,
+    index_dir_id: i64,
+    fingerprints: Vec<(String, i64, i64)>,
+) -> GlobalResult<()> {
+    let chunk_size = 1000; // 每批次 1000 条
+    let sql = r#"UPDATE INDEX_FILE SET fingerprint=$1, content_hash=$2 WHERE path=$3 AND index_dir_id=$4"#;
+    for chunk in fingerprints.chunks(chunk_size) {
+        let mut tx = to_global_result(pool.begin().await)?;
+        for (path, fingerprint, content_hash) in chunk {
+            let query = sqlx::query::<Sqlite>(sql)
+                .bind(fingerprint)
+                .bind(content_hash)
+                .bind(path)
+                .bind(index_dir_id);
+            let r = query.execute(&mut *tx).await;
+            if let Err(err) = r {
+                // 回滚事务
+                to_global_result(tx.rollback().await)?;
+                return Err(GlobalError {
+                    msg: err.to_string(),
+                });
+            }
+        }
+        to_global_result(tx.commit().await)?;
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-25 10:40:22
+/// @Description: 批量更新文本文件的编码
+/// @param encodings: (文件路径, 编码)
+pub async fn update_encodings(
+    pool: &SqlitePool,
+    index_dir_id: i64,
+    encodings: Vec<(String, String)>,
+) -> GlobalResult<()> {
+    let chunk_size = 1000; // 每批次 1000 条
+    let sql = r#"UPDATE INDEX_FILE SET encoding=$1 WHERE path=$2 AND index_dir_id=$3"#;
+    for chunk in encodings.chunks(chunk_size) {
+        let mut tx = to_global_result(pool.begin().await)?;
+        for (path, encoding) in chunk {
+            let query = sqlx::query::<Sqlite>(sql)
+                .bind(encoding)
+                .bind(path)
+                .bind(index_dir_id);
+            let r = query.execute(&mut *tx).await;
+            if let Err(err) = r {
+                // 回滚事务
+                to_global_result(tx.rollback().await)?;
+                return Err(GlobalError {
+                    msg: err.to_string(),
+                });
+            }
+        }
+        to_global_result(tx.commit().await)?;
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 10:40:36
+/// @Description: 批量标记提取失败的文件
+/// @param failures: (文件路径, 失败原因)
+pub async fn update_failures(
+    pool: &SqlitePool,
+    index_dir_id: i64,
+    failures: Vec<(String, String)>,
+) -> GlobalResult<()> {
+    let chunk_size = 1000; // 每批次 1000 条
+    let sql = r#"UPDATE INDEX_FILE SET status=$1, error=$2 WHERE path=$3 AND index_dir_id=$4"#;
+    for chunk in failures.chunks(chunk_size) {
+        let mut tx = to_global_result(pool.begin().await)?;
+        for (path, error) in chunk {
+            let query = sqlx::query::<Sqlite>(sql)
+                .bind(STATUS_FAILED)
+                .bind(error)
+                .bind(path)
+                .bind(index_dir_id);
+            let r = query.execute(&mut *tx).await;
+            if let Err(err) = r {
+                // 回滚事务
+                to_global_result(tx.rollback().await)?;
+                return Err(GlobalError {
+                    msg: err.to_string(),
+                });
+            }
+        }
+        to_global_result(tx.commit().await)?;
+    }
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 10:46:12
+/// @Description: 查询提取失败的文件
+pub async fn select_failed(pool: &SqlitePool) -> GlobalResult<Vec<IndexFile>> {
+    let sql = r#"SELECT * FROM INDEX_FILE WHERE status=$1 ORDER BY id ASC"#;
+    let query = sqlx::query_as::<Sqlite, IndexFile>(sql).bind(STATUS_FAILED);
+    to_global_result(query.fetch_all(pool).await)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 10:50:27
+/// @Description: 删除文件夹下提取失败的文件记录, 重新扫描时按新文件插入(状态为已建索引)
+pub async fn delete_failed(pool: &SqlitePool, index_dir_id: i64) -> GlobalResult<()> {
+    let sql = r#"DELETE FROM INDEX_FILE WHERE index_dir_id=$1 AND status=$2"#;
+    let query = sqlx::query::<Sqlite>(sql)
+        .bind(index_dir_id)
+        .bind(STATUS_FAILED);
+    to_global_result(query.execute(pool).await)?;
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 11:35:10
+/// @Description: 查询有内容指纹的文件
+pub async fn select_with_fingerprint(pool: &SqlitePool) -> GlobalResult<Vec<IndexFile>> {
+    let sql = r#"SELECT * FROM INDEX_FILE WHERE fingerprint IS NOT NULL ORDER BY id ASC"#;
+
+    let query = sqlx::query_as::<Sqlite, IndexFile>(sql);
+    let all = to_global_result(query.fetch_all(pool).await)?;
+
+    Ok(all)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 11:41:27
+/// @Description: 根据路径查询内容指纹
+pub async fn fingerprints_by_paths(
+    pool: &SqlitePool,
+    paths: &Vec<String>,
+) -> GlobalResult<HashMap<String, i64>> {
+    let mut map = HashMap::new();
+    for chunk in paths.chunks(500) {
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+            r#"SELECT path, fingerprint FROM INDEX_FILE WHERE fingerprint IS NOT NULL AND path IN ("#,
+        );
+        let mut separated = query_builder.separated(", ");
+        for path in chunk {
+            separated.push_bind(path);
+        }
+        separated.push_unseparated(")");
+        let rows = to_global_result(query_builder.build().fetch_all(pool).await)?;
+        for row in rows {
+            map.insert(
+                row.get::<String, _>("path"),
+                row.get::<i64, _>("fingerprint"),
+            );
+        }
+    }
+    Ok(map)
+}
+
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-03 17:14:50
 /// @Description: 删除
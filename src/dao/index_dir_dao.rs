@@ -6,11 +6,14 @@ use sqlx::{FromRow, Sqlite, SqlitePool};
 
 use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
 
+use super::sqlite_dao;
+
 //索引的文件夹
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct IndexDir {
     pub id: i64,
     pub path: String,
+    pub ocr: bool, //是否OCR识别图片和扫描版PDF(默认不开启)
 }
 
 /// @Author: DengLibin
@@ -18,8 +21,7 @@ pub struct IndexDir {
 /// @Description: 建表 index_dri
 pub async fn create_index_dir_table(pool: &SqlitePool) -> GlobalResult<()> {
     let mut tx = to_global_result(pool.begin().await)?;
-    let sql =
-        r#"CREATE TABLE IF NOT EXISTS INDEX_DIR(id integer  PRIMARY KEY AUTOINCREMENT, path text)"#;
+    let sql = r#"CREATE TABLE IF NOT EXISTS INDEX_DIR(id integer  PRIMARY KEY AUTOINCREMENT, path text, ocr integer NOT NULL DEFAULT 0)"#;
 
     let query = sqlx::query::<Sqlite>(sql);
 
@@ -34,6 +36,9 @@ pub async fn create_index_dir_table(pool: &SqlitePool) -> GlobalResult<()> {
         //提交
         to_global_result(tx.commit().await)?;
     }
+    //旧版本建的表没有ocr列
+    sqlite_dao::add_column_if_missing(pool, "INDEX_DIR", "ocr", "integer NOT NULL DEFAULT 0")
+        .await?;
     Ok(())
 }
 
@@ -97,3 +102,13 @@ pub async fn delete(pool: &SqlitePool, id: i64) -> GlobalResult<()> {
     }
     Ok(())
 }
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:05:37
+/// @Description: 设置是否OCR
+pub async fn update_ocr(pool: &SqlitePool, id: i64, ocr: bool) -> GlobalResult<()> {
+    let sql = r#"UPDATE INDEX_DIR SET ocr=$1 WHERE id=$2"#;
+    let query = sqlx::query::<Sqlite>(sql).bind(ocr).bind(id);
+    to_global_result(query.execute(pool).await)?;
+    Ok(())
+}
@@ -3,7 +3,7 @@
 //! @Description: sqlite
 
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteConnectOptions, FromRow, Sqlite, SqlitePool};
+use sqlx::{sqlite::SqliteConnectOptions, FromRow, Row, Sqlite, SqlitePool};
 
 use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
 
@@ -13,8 +13,6 @@ pub struct SqliteUser {
     pub name: Option<String>,
 }
 
-
-
 /// @Author: DengLibin
 /// @Date: Create in 2024-11-05 10:15:28
 /// @Description: 获取sqlite连接池
@@ -27,7 +25,42 @@ pub async fn get_conn_pool(url: &str) -> GlobalResult<SqlitePool> {
     to_global_result(r)
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-13 10:20:16
+/// @Description: 整理数据库文件，回收删除数据占用的空间
+pub async fn vacuum(pool: &SqlitePool) -> GlobalResult<()> {
+    let sql = r#"VACUUM"#;
+    to_global_result(sqlx::query::<Sqlite>(sql).execute(pool).await)?;
+    Ok(())
+}
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 11:02:36
+/// @Description: 列不存在时添加(旧版本建的表)
+pub async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    column_type: &str,
+) -> GlobalResult<()> {
+    let sql = r#"SELECT count(1) FROM pragma_table_info($1) WHERE name=$2"#;
+    let count = to_global_result(
+        sqlx::query::<Sqlite>(sql)
+            .bind(table)
+            .bind(column)
+            .map(|row| row.get::<i64, _>(0))
+            .fetch_one(pool)
+            .await,
+    )?;
+    if count == 0 {
+        let sql = format!(
+            r#"ALTER TABLE {} ADD COLUMN "{}" {}"#,
+            table, column, column_type
+        );
+        to_global_result(sqlx::query::<Sqlite>(&sql).execute(pool).await)?;
+    }
+    Ok(())
+}
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-11-05 10:36:26
@@ -76,9 +109,9 @@ pub async fn add_demo(pool: &SqlitePool) -> GlobalResult<()> {
     Ok(())
 }
 
- /// @Author: DengLibin
- /// @Date: Create in 2024-11-05 11:43:38
- /// @Description: 查询
+/// @Author: DengLibin
+/// @Date: Create in 2024-11-05 11:43:38
+/// @Description: 查询
 pub async fn select_demo(pool: &SqlitePool) -> GlobalResult<()> {
     let sql = r#"SELECT * FROM USER"#;
 
@@ -0,0 +1,468 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-28 09:06:12
+//! @Description: 文档元数据: 标题、作者、主题、关键词、创建/修改时间、页数、应用程序
+//!
+//! 来源: extractous(tika)返回的元数据(PDF信息字典、ID3等)、office 2007的docProps/core.xml和app.xml、邮件头、
+//! 图片的EXIF(直接解析JPEG、PNG、TIFF, 不用tika)。
+//! tika不同格式的键名不同, 按顺序取第一个有值的键。
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::xml_utils::{self, XmlEvent},
+};
+
+lazy_static! {
+    //OpenDocument文档统计中的页数
+    static ref ODF_PAGE_COUNT_RE: Regex =
+        Regex::new(r#"<meta:document-statistic\s[^>]*meta:page-count="(\d+)""#).unwrap();
+}
+
+//tika元数据的键, 按优先顺序
+const TITLE_KEYS: [&str; 3] = ["dc:title", "pdf:docinfo:title", "title"];
+const AUTHOR_KEYS: [&str; 6] = [
+    "dc:creator",
+    "meta:author",
+    "Author",
+    "pdf:docinfo:creator",
+    "xmpDM:artist",
+    "creator",
+];
+const SUBJECT_KEYS: [&str; 4] = ["cp:subject", "pdf:docinfo:subject", "subject", "dc:subject"];
+const KEYWORDS_KEYS: [&str; 4] = [
+    "meta:keyword",
+    "pdf:docinfo:keywords",
+    "Keywords",
+    "cp:keywords",
+];
+const CREATED_KEYS: [&str; 6] = [
+    "dcterms:created",
+    "meta:creation-date",
+    "pdf:docinfo:created",
+    "Creation-Date",
+    "exif:DateTimeOriginal",
+    "xmpDM:releaseDate",
+];
+const MODIFIED_KEYS: [&str; 4] = [
+    "dcterms:modified",
+    "pdf:docinfo:modified",
+    "Last-Modified",
+    "meta:save-date",
+];
+const PAGE_COUNT_KEYS: [&str; 4] = [
+    "xmpTPg:NPages",
+    "meta:page-count",
+    "Page-Count",
+    "Slide-Count",
+];
+const APPLICATION_KEYS: [&str; 6] = [
+    "extended-properties:Application",
+    "Application-Name",
+    "xmp:CreatorTool",
+    "pdf:docinfo:creator_tool",
+    "pdf:producer",
+    "tiff:Software",
+];
+//照片没有应用程序时用相机型号
+const CAMERA_KEYS: [&str; 2] = ["tiff:Make", "tiff:Model"];
+
+//EXIF标签
+const EXIF_IMAGE_DESCRIPTION: u16 = 0x010e;
+const EXIF_MAKE: u16 = 0x010f;
+const EXIF_MODEL: u16 = 0x0110;
+const EXIF_SOFTWARE: u16 = 0x0131;
+const EXIF_DATE_TIME: u16 = 0x0132;
+const EXIF_ARTIST: u16 = 0x013b;
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const EXIF_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const EXIF_DATE_TIME_DIGITIZED: u16 = 0x9004;
+//Windows资源管理器写入的标题、作者等(UTF-16LE)
+const EXIF_XP_TITLE: u16 = 0x9c9b;
+const EXIF_XP_AUTHOR: u16 = 0x9c9d;
+const EXIF_XP_KEYWORDS: u16 = 0x9c9e;
+const EXIF_XP_SUBJECT: u16 = 0x9c9f;
+//TIFF文件最多读取的字节数(EXIF在文件开头)
+const TIFF_MAX_READ: u64 = 16 * 1024 * 1024;
+
+/// 文档元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocMetadata {
+    pub title: String,       //标题
+    pub author: String,      //作者
+    pub subject: String,     //主题
+    pub keywords: String,    //关键词
+    pub application: String, //创建文档的应用程序
+    pub created_time: i64,   //创建时间(毫秒), 未知为0
+    pub modified_time: i64,  //文档中记录的修改时间(毫秒), 未知为0
+    pub page_count: u64,     //页数(幻灯片数), 未知为0
+}
+
+impl DocMetadata {
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 09:30:41
+    /// @Description: 从tika元数据读取
+    pub fn from_tika(metadata: &HashMap<String, Vec<String>>) -> Self {
+        let first = |keys: &[&str]| -> String {
+            keys.iter()
+                .filter_map(|key| metadata.get(*key))
+                .flat_map(|values| values.iter())
+                .map(|value| value.trim())
+                .find(|value| !value.is_empty())
+                .unwrap_or("")
+                .to_string()
+        };
+        let mut application = first(&APPLICATION_KEYS);
+        if application.is_empty() {
+            application = CAMERA_KEYS
+                .iter()
+                .map(|key| first(&[key]))
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<String>>()
+                .join(" ");
+        }
+        DocMetadata {
+            title: first(&TITLE_KEYS),
+            author: first(&AUTHOR_KEYS),
+            subject: first(&SUBJECT_KEYS),
+            keywords: first(&KEYWORDS_KEYS),
+            application,
+            created_time: parse_time(&first(&CREATED_KEYS)),
+            modified_time: parse_time(&first(&MODIFIED_KEYS)),
+            page_count: first(&PAGE_COUNT_KEYS).parse().unwrap_or(0),
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 09:41:17
+    /// @Description: 是否没有任何元数据
+    pub fn is_empty(&self) -> bool {
+        *self == DocMetadata::default()
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 09:45:36
+/// @Description: office 2007格式(docx、xlsx、pptx)的元数据: docProps/core.xml和docProps/app.xml
+pub fn officex_metadata(file_path: &str) -> GlobalResult<DocMetadata> {
+    let file = to_global_result(File::open(file_path))?;
+    let mut archive = to_global_result(zip::ZipArchive::new(file))?;
+    let mut read_entry = |name: &str| -> String {
+        let mut content = String::new();
+        if let Ok(mut entry) = archive.by_name(name) {
+            let _ = entry.read_to_string(&mut content);
+        }
+        content
+    };
+    let core = read_entry("docProps/core.xml");
+    let app = read_entry("docProps/app.xml");
+    let page_count = ["Pages", "Slides"]
+        .iter()
+        .map(|tag| xml_tag_text(&app, tag))
+        .find(|value| !value.is_empty())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    Ok(DocMetadata {
+        title: xml_tag_text(&core, "dc:title"),
+        author: xml_tag_text(&core, "dc:creator"),
+        subject: xml_tag_text(&core, "dc:subject"),
+        keywords: xml_tag_text(&core, "cp:keywords"),
+        application: xml_tag_text(&app, "Application"),
+        created_time: parse_time(&xml_tag_text(&core, "dcterms:created")),
+        modified_time: parse_time(&xml_tag_text(&core, "dcterms:modified")),
+        page_count,
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 09:12:33
+/// @Description: OpenDocument格式(odt、ods、odp)的元数据: meta.xml
+pub fn odf_metadata(meta: &str) -> DocMetadata {
+    let author = ["dc:creator", "meta:initial-creator"]
+        .iter()
+        .map(|tag| xml_tag_text(meta, tag))
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    //页数在文档统计的属性中
+    let page_count = ODF_PAGE_COUNT_RE
+        .captures(meta)
+        .and_then(|caps| caps[1].parse().ok())
+        .unwrap_or(0);
+    DocMetadata {
+        title: xml_tag_text(meta, "dc:title"),
+        author,
+        subject: xml_tag_text(meta, "dc:subject"),
+        keywords: xml_tag_text(meta, "meta:keyword"),
+        application: xml_tag_text(meta, "meta:generator"),
+        created_time: parse_time(&xml_tag_text(meta, "meta:creation-date")),
+        modified_time: parse_time(&xml_tag_text(meta, "dc:date")),
+        page_count,
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 09:20:48
+/// @Description: EPUB的元数据: OPF文件中的metadata(Dublin Core)
+pub fn opf_metadata(opf: &str) -> DocMetadata {
+    DocMetadata {
+        title: xml_tag_text(opf, "dc:title"),
+        author: xml_tag_text(opf, "dc:creator"),
+        subject: xml_tag_text(opf, "dc:description"),
+        keywords: xml_tag_text(opf, "dc:subject"),
+        created_time: parse_time(&xml_tag_text(opf, "dc:date")),
+        ..Default::default()
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 16:05:12
+/// @Description: 图片的EXIF元数据(JPEG、PNG、TIFF), 没有EXIF或格式不支持时为空
+pub fn exif_metadata(file_path: &str) -> GlobalResult<DocMetadata> {
+    let mut file = to_global_result(File::open(file_path))?;
+    let tiff = match exif_block(&mut file) {
+        Ok(Some(tiff)) => tiff,
+        _ => return Ok(DocMetadata::default()),
+    };
+    let tags = exif_tags(&tiff).unwrap_or_default();
+    let ascii = |tag: u16| -> String {
+        let value = tags
+            .get(&tag)
+            .map(|v| String::from_utf8_lossy(v))
+            .unwrap_or_default();
+        value
+            .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+            .to_string()
+    };
+    let xp = |tag: u16| -> String {
+        let units = tags
+            .get(&tag)
+            .map(|v| {
+                v.chunks_exact(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                    .collect::<Vec<u16>>()
+            })
+            .unwrap_or_default();
+        let value = String::from_utf16_lossy(&units);
+        value
+            .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+            .to_string()
+    };
+    let first = |values: Vec<String>| {
+        values
+            .into_iter()
+            .find(|v| !v.is_empty())
+            .unwrap_or_default()
+    };
+    let mut application = ascii(EXIF_SOFTWARE);
+    if application.is_empty() {
+        application = [ascii(EXIF_MAKE), ascii(EXIF_MODEL)]
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+    Ok(DocMetadata {
+        title: first(vec![ascii(EXIF_IMAGE_DESCRIPTION), xp(EXIF_XP_TITLE)]),
+        author: first(vec![ascii(EXIF_ARTIST), xp(EXIF_XP_AUTHOR)]),
+        subject: xp(EXIF_XP_SUBJECT),
+        keywords: xp(EXIF_XP_KEYWORDS),
+        application,
+        created_time: parse_time(&first(vec![
+            ascii(EXIF_DATE_TIME_ORIGINAL),
+            ascii(EXIF_DATE_TIME_DIGITIZED),
+        ])),
+        modified_time: parse_time(&ascii(EXIF_DATE_TIME)),
+        page_count: 0,
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 16:12:40
+/// @Description: 取图片中的EXIF数据块(TIFF格式): JPEG的APP1段、PNG的eXIf块、TIFF文件本身
+/// 只读取文件开头到EXIF所在位置, 不读取图像数据
+fn exif_block(file: &mut File) -> std::io::Result<Option<Vec<u8>>> {
+    let mut head = [0u8; 8];
+    file.read_exact(&mut head)?;
+    //JPEG: FF D8, 之后是段(FF 标记, 2字节长度), 图像数据(SOS)之前
+    if head[..2] == [0xff, 0xd8] {
+        file.seek(SeekFrom::Start(2))?;
+        loop {
+            let mut marker = [0u8; 4];
+            file.read_exact(&mut marker)?;
+            if marker[0] != 0xff || marker[1] == 0xda || marker[1] == 0xd9 {
+                return Ok(None);
+            }
+            let len = u16::from_be_bytes([marker[2], marker[3]]) as usize;
+            if len < 2 {
+                return Ok(None);
+            }
+            if marker[1] != 0xe1 {
+                file.seek(SeekFrom::Current(len as i64 - 2))?;
+                continue;
+            }
+            let mut data = vec![0u8; len - 2];
+            file.read_exact(&mut data)?;
+            if let Some(tiff) = data.strip_prefix(b"Exif\0\0") {
+                return Ok(Some(tiff.to_vec()));
+            }
+        }
+    }
+    //PNG: 8字节签名, 之后是块(4字节长度, 4字节类型, 数据, 4字节CRC)
+    if head == *b"\x89PNG\r\n\x1a\n" {
+        loop {
+            let mut chunk = [0u8; 8];
+            file.read_exact(&mut chunk)?;
+            let len = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize;
+            match &chunk[4..] {
+                b"eXIf" => {
+                    let mut data = vec![0u8; len];
+                    file.read_exact(&mut data)?;
+                    return Ok(Some(data));
+                }
+                b"IDAT" | b"IEND" => return Ok(None),
+                _ => {
+                    file.seek(SeekFrom::Current(len as i64 + 4))?;
+                }
+            }
+        }
+    }
+    //TIFF: II*\0 或 MM\0*
+    if head[..4] == *b"II*\0" || head[..4] == *b"MM\0*" {
+        file.seek(SeekFrom::Start(0))?;
+        let mut data = vec![];
+        file.take(TIFF_MAX_READ).read_to_end(&mut data)?;
+        return Ok(Some(data));
+    }
+    Ok(None)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 16:25:07
+/// @Description: 解析EXIF(TIFF格式)中IFD0和EXIF子IFD的标签, 返回标签 -> 原始值
+/// 只取字符串和字节类型的值, 数据不完整时返回None
+fn exif_tags(tiff: &[u8]) -> Option<HashMap<u16, Vec<u8>>> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| -> Option<u16> {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(match little_endian {
+            true => u16::from_le_bytes(bytes),
+            false => u16::from_be_bytes(bytes),
+        })
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        })
+    };
+    if u16_at(2)? != 42 {
+        return None;
+    }
+
+    //读取一个IFD的标签, 返回其中EXIF子IFD的位置
+    let read_ifd = |ifd: usize, tags: &mut HashMap<u16, Vec<u8>>| -> Option<Option<usize>> {
+        let mut exif_ifd = None;
+        for i in 0..u16_at(ifd)? as usize {
+            let entry = ifd + 2 + i * 12;
+            let tag = u16_at(entry)?;
+            let value_type = u16_at(entry + 2)?;
+            let value_count = u32_at(entry + 4)? as usize;
+            if tag == EXIF_IFD_POINTER {
+                exif_ifd = Some(u32_at(entry + 8)? as usize);
+                continue;
+            }
+            //1: BYTE, 2: ASCII, 7: UNDEFINED, 每个值1字节; 超过4字节时存的是偏移
+            if !matches!(value_type, 1 | 2 | 7) {
+                continue;
+            }
+            let start = match value_count <= 4 {
+                true => entry + 8,
+                false => u32_at(entry + 8)? as usize,
+            };
+            if let Some(value) = tiff.get(start..start.checked_add(value_count)?) {
+                tags.insert(tag, value.to_vec());
+            }
+        }
+        Some(exif_ifd)
+    };
+
+    let mut tags: HashMap<u16, Vec<u8>> = HashMap::new();
+    if let Some(exif_ifd) = read_ifd(u32_at(4)? as usize, &mut tags)? {
+        //子IFD不完整时保留IFD0的标签
+        let _ = read_ifd(exif_ifd, &mut tags);
+    }
+    Some(tags)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 10:02:50
+/// @Description: xml中第一个该标签的文本(已反转义), 没有时为空
+fn xml_tag_text(xml: &str, tag: &str) -> String {
+    let mut text: Option<String> = None;
+    for event in xml_utils::events(xml) {
+        match event {
+            XmlEvent::Start { name, .. } if text.is_none() && name == tag => {
+                text = Some(String::new());
+            }
+            XmlEvent::Text(t) => {
+                if let Some(text) = text.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            XmlEvent::End { name } if text.is_some() && name == tag => break,
+            _ => {}
+        }
+    }
+    text.map(|text| text.trim().to_string()).unwrap_or_default()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 10:15:31
+/// @Description: 解析时间(毫秒), 支持RFC3339、没有时区的时间(按本地时间)、EXIF格式(2025:02:28 10:15:31)、日期, 失败时为0
+pub fn parse_time(text: &str) -> i64 {
+    let text = text.trim();
+    if text.is_empty() {
+        return 0;
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(text) {
+        return time.timestamp_millis();
+    }
+    let formats = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y:%m:%d %H:%M:%S",
+    ];
+    for format in formats {
+        if let Ok(time) = NaiveDateTime::parse_from_str(text, format) {
+            return local_millis(time);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return local_millis(date.and_hms_opt(0, 0, 0).unwrap_or_default());
+    }
+    0
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 10:20:06
+/// @Description: 本地时间转毫秒
+pub fn local_millis(time: NaiveDateTime) -> i64 {
+    match Local.from_local_datetime(&time).earliest() {
+        Some(time) => time.timestamp_millis(),
+        None => time.and_utc().timestamp_millis(),
+    }
+}
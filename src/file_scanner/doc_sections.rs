@@ -0,0 +1,627 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-03 10:05:47
+//! @Description: 文档的结构单元: PDF的页、pptx的幻灯片、xlsx的工作表、docx标题下的内容
+//!
+//! 建索引时按结构单元切分内容片段, 每个片段记录所在位置(第47页、Sheet2等), 搜索结果显示命中的位置。
+//! office 2007格式直接读zip中的xml; PDF用extractous输出的xhtml, 每页是一个<div class="page">。
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::Read,
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::xml_utils::{self, XmlEvent},
+};
+
+use super::extractor_registry::{MIME_DOCX, MIME_PPTX, MIME_XLSX};
+
+//docx标题作为位置时的最大长度(字符数)
+const MAX_HEADING_LEN: usize = 50;
+
+/// 结构单元
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextSection {
+    pub location: String, //位置: 第47页、第3张幻灯片、工作表名、docx标题, 没有时为空
+    pub text: String,     //内容
+    #[serde(default)]
+    pub record: bool, //是否表格的一行(内容是"列名: 值")
+    #[serde(default)]
+    pub line: u64, //起始行号(从1开始), 不为0时建索引按行切分, 片段保留换行并记录起始行号
+}
+
+impl TextSection {
+    pub fn new(location: impl Into<String>, text: impl Into<String>) -> Self {
+        TextSection {
+            location: location.into(),
+            text: text.into(),
+            record: false,
+            line: 0,
+        }
+    }
+
+    /// 按行切分的文本(日志、源代码等), 从start_line行开始
+    pub fn lines(start_line: u64, text: impl Into<String>) -> Self {
+        TextSection {
+            line: start_line,
+            ..TextSection::new("", text)
+        }
+    }
+
+    /// 表格的一行
+    pub fn record(location: impl Into<String>, text: impl Into<String>) -> Self {
+        TextSection {
+            record: true,
+            ..TextSection::new(location, text)
+        }
+    }
+}
+
+/// 工作表
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sheet {
+    pub name: String,            //名称
+    pub rows: Vec<Vec<String>>,  //行(单元格按列号排列, 空单元格为空字符串)
+    pub row_numbers: Vec<usize>, //与rows对应的行号(从1开始)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:15:20
+/// @Description: PDF页的位置
+pub fn page_location(page_no: usize) -> String {
+    format!("第{}页", page_no)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:16:02
+/// @Description: 幻灯片的位置
+pub fn slide_location(slide_no: usize) -> String {
+    format!("第{}张幻灯片", slide_no)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:17:38
+/// @Description: 所有单元的内容(换行分隔)
+pub fn join_sections(sections: &[TextSection]) -> String {
+    sections
+        .iter()
+        .map(|section| section.text.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:20:31
+/// @Description: 按页拆分的文本(页之间是\f, 如OCR结果), 只有一页时为空
+pub fn page_sections(text: &str) -> Vec<TextSection> {
+    let pages = text.split('\u{c}').collect::<Vec<&str>>();
+    if pages.len() < 2 {
+        return vec![];
+    }
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| TextSection::new(page_location(i + 1), page))
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:26:14
+/// @Description: office 2007格式的结构单元: docx按标题、pptx按幻灯片、xlsx按工作表
+pub fn officex_sections(file_path: &str, mime: &str) -> GlobalResult<Vec<TextSection>> {
+    let file = to_global_result(File::open(file_path))?;
+    let mut archive = to_global_result(ZipArchive::new(file))?;
+    let sections = match mime {
+        MIME_DOCX => docx_sections(&mut archive),
+        MIME_PPTX => pptx_sections(&mut archive),
+        MIME_XLSX => read_sheets(&mut archive)
+            .into_iter()
+            .map(|sheet| TextSection::new(sheet.name.clone(), sheet_text(&sheet)))
+            .collect(),
+        _ => vec![],
+    };
+    Ok(sections
+        .into_iter()
+        .filter(|section| !section.text.trim().is_empty())
+        .collect())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:32:45
+/// @Description: 读取xlsx的所有工作表(按工作簿中的顺序)
+pub fn xlsx_sheets(file_path: &str) -> GlobalResult<Vec<Sheet>> {
+    let file = to_global_result(File::open(file_path))?;
+    let mut archive = to_global_result(ZipArchive::new(file))?;
+    Ok(read_sheets(&mut archive))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 10:25:33
+/// @Description: 工作表的内容: 单元格之间是制表符, 行之间换行
+pub fn sheet_text(sheet: &Sheet) -> String {
+    sheet
+        .rows
+        .iter()
+        .map(|row| row.join("\t"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:40:12
+/// @Description: PDF的页(extractous输出的xhtml), 没有分页时整个正文作为一个没有位置的单元
+pub fn pdf_sections(xhtml: &str) -> Vec<TextSection> {
+    let mut pages: Vec<String> = vec![];
+    let mut body_text = String::new();
+    let mut in_body = false;
+    let mut page_depth = 0;
+    for event in xml_utils::events(xhtml) {
+        match event {
+            XmlEvent::Start { name: "body", .. } => in_body = true,
+            XmlEvent::End { name: "body" } => in_body = false,
+            XmlEvent::Start { name: "div", attrs } => {
+                if page_depth > 0 {
+                    page_depth += 1;
+                } else if xml_utils::attr(attrs, "class").is_some_and(|class| class == "page") {
+                    page_depth = 1;
+                    pages.push(String::new());
+                }
+            }
+            XmlEvent::End { name } => {
+                let text = match pages.last_mut() {
+                    Some(page) if page_depth > 0 => page,
+                    _ => &mut body_text,
+                };
+                if let Some(separator) = html_separator(name) {
+                    text.push(separator);
+                }
+                if name == "div" && page_depth > 0 {
+                    page_depth -= 1;
+                }
+            }
+            XmlEvent::Empty { name: "br", .. } => match pages.last_mut() {
+                Some(page) if page_depth > 0 => page.push('\n'),
+                _ => body_text.push('\n'),
+            },
+            XmlEvent::Text(text) if in_body => match pages.last_mut() {
+                Some(page) if page_depth > 0 => page.push_str(&text),
+                _ => body_text.push_str(&text),
+            },
+            _ => {}
+        }
+    }
+    if pages.is_empty() {
+        if body_text.trim().is_empty() {
+            return vec![];
+        }
+        return vec![TextSection::new("", body_text)];
+    }
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| TextSection::new(page_location(i + 1), page))
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 10:05:21
+/// @Description: (x)html正文的文本, 跳过head、script、style
+pub fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_body = false;
+    let mut skip_depth = 0;
+    for event in xml_utils::events(html) {
+        match event {
+            XmlEvent::Start { name, .. } => match xml_utils::local_name(name) {
+                "body" => in_body = true,
+                "script" | "style" => skip_depth += 1,
+                _ => {}
+            },
+            XmlEvent::End { name } => {
+                let name = xml_utils::local_name(name);
+                match name {
+                    "body" => in_body = false,
+                    "script" | "style" => skip_depth -= 1,
+                    _ => {}
+                }
+                if let (Some(separator), true) = (html_separator(name), in_body) {
+                    text.push(separator);
+                }
+            }
+            XmlEvent::Empty { name, .. } if in_body && xml_utils::local_name(name) == "br" => {
+                text.push('\n')
+            }
+            XmlEvent::Text(content) if in_body && skip_depth <= 0 => text.push_str(&content),
+            _ => {}
+        }
+    }
+    text
+}
+
+//块级元素结束时的分隔: 段落、标题等换行, 单元格用制表符
+fn html_separator(name: &str) -> Option<char> {
+    match name {
+        "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => Some('\n'),
+        "td" | "th" => Some('\t'),
+        _ => None,
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 10:58:36
+/// @Description: docx按标题拆分, 第一个标题之前的内容没有位置
+/// 标题: 段落样式是标题(heading n、title)或有大纲级别
+fn docx_sections(archive: &mut ZipArchive<File>) -> Vec<TextSection> {
+    let heading_styles = heading_style_ids(&read_entry(archive, "word/styles.xml"));
+    let document = read_entry(archive, "word/document.xml");
+
+    let mut sections: Vec<TextSection> = vec![TextSection::default()];
+    let mut paragraph = String::new();
+    let mut p_depth = 0;
+    let mut is_heading = false;
+    let mut in_text = false;
+    for event in xml_utils::events(&document) {
+        match event {
+            XmlEvent::Start { name: "w:p", .. } => {
+                if p_depth == 0 {
+                    paragraph.clear();
+                    is_heading = false;
+                }
+                p_depth += 1;
+            }
+            XmlEvent::End { name: "w:p" } if p_depth > 0 => {
+                p_depth -= 1;
+                if p_depth > 0 {
+                    continue;
+                }
+                let heading = paragraph.trim();
+                if is_heading && !heading.is_empty() {
+                    let location = heading.chars().take(MAX_HEADING_LEN).collect::<String>();
+                    sections.push(TextSection::new(location, ""));
+                }
+                if let Some(section) = sections.last_mut() {
+                    section.text.push_str(&paragraph);
+                    section.text.push('\n');
+                }
+            }
+            XmlEvent::Empty {
+                name: "w:pStyle",
+                attrs,
+            } => {
+                is_heading |=
+                    xml_utils::attr(attrs, "w:val").is_some_and(|id| heading_styles.contains(&id));
+            }
+            XmlEvent::Empty {
+                name: "w:outlineLvl",
+                attrs,
+            } => is_heading |= is_outline_level(attrs),
+            XmlEvent::Start { name: "w:t", .. } => in_text = true,
+            XmlEvent::End { name: "w:t" } => in_text = false,
+            XmlEvent::Text(text) if in_text => paragraph.push_str(&text),
+            XmlEvent::Empty { name: "w:tab", .. } => paragraph.push('\t'),
+            XmlEvent::Empty {
+                name: "w:br" | "w:cr",
+                ..
+            } => paragraph.push('\n'),
+            _ => {}
+        }
+    }
+    sections
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 11:10:27
+/// @Description: 标题样式的id(中文版Word的标题样式id是数字, 按样式名称判断)
+fn heading_style_ids(styles: &str) -> HashSet<String> {
+    let mut ids: HashSet<String> = HashSet::new();
+    let mut style_id: Option<String> = None;
+    let mut is_heading = false;
+    for event in xml_utils::events(styles) {
+        match event {
+            XmlEvent::Start {
+                name: "w:style",
+                attrs,
+            } => {
+                let paragraph = xml_utils::attr(attrs, "w:type").is_some_and(|t| t == "paragraph");
+                style_id = xml_utils::attr(attrs, "w:styleId").filter(|_| paragraph);
+                is_heading = false;
+            }
+            XmlEvent::Empty {
+                name: "w:name",
+                attrs,
+            } => {
+                let name = xml_utils::attr(attrs, "w:val")
+                    .unwrap_or_default()
+                    .to_lowercase();
+                if name.starts_with("heading") || name == "title" {
+                    is_heading = true;
+                }
+            }
+            XmlEvent::Empty {
+                name: "w:outlineLvl",
+                attrs,
+            } => is_heading |= is_outline_level(attrs),
+            XmlEvent::End { name: "w:style" } => {
+                if let (Some(id), true) = (style_id.take(), is_heading) {
+                    ids.insert(id);
+                }
+            }
+            _ => {}
+        }
+    }
+    ids
+}
+
+//大纲级别0-8为标题, 9为正文
+fn is_outline_level(attrs: &str) -> bool {
+    xml_utils::attr(attrs, "w:val")
+        .and_then(|level| level.parse::<u32>().ok())
+        .is_some_and(|level| level < 9)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 11:22:50
+/// @Description: pptx按幻灯片拆分, 顺序以presentation.xml为准
+fn pptx_sections(archive: &mut ZipArchive<File>) -> Vec<TextSection> {
+    let presentation = read_entry(archive, "ppt/presentation.xml");
+    let targets = rels_targets(archive, "ppt/_rels/presentation.xml.rels", "ppt/");
+    let mut slide_files = xml_utils::events(&presentation)
+        .filter_map(|event| match event {
+            XmlEvent::Empty {
+                name: "p:sldId",
+                attrs,
+            } => xml_utils::attr(attrs, "r:id").and_then(|id| targets.get(&id).cloned()),
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    if slide_files.is_empty() {
+        slide_files = numbered_entries(archive, r"^ppt/slides/slide(\d+)\.xml$");
+    }
+
+    let mut sections: Vec<TextSection> = vec![];
+    for (i, slide_file) in slide_files.iter().enumerate() {
+        let slide = read_entry(archive, slide_file);
+        let mut text = String::new();
+        let mut in_text = false;
+        for event in xml_utils::events(&slide) {
+            match event {
+                XmlEvent::Start { name: "a:t", .. } => in_text = true,
+                XmlEvent::End { name: "a:t" } => in_text = false,
+                XmlEvent::Text(t) if in_text => text.push_str(&t),
+                XmlEvent::End { name: "a:p" } => text.push('\n'),
+                _ => {}
+            }
+        }
+        sections.push(TextSection::new(slide_location(i + 1), text));
+    }
+    sections
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 11:36:18
+/// @Description: 读取xlsx的工作表, 顺序以workbook.xml为准
+fn read_sheets(archive: &mut ZipArchive<File>) -> Vec<Sheet> {
+    let shared_strings = shared_strings(&read_entry(archive, "xl/sharedStrings.xml"));
+    let workbook = read_entry(archive, "xl/workbook.xml");
+    let targets = rels_targets(archive, "xl/_rels/workbook.xml.rels", "xl/");
+    let sheet_files = xml_utils::events(&workbook)
+        .filter_map(|event| match event {
+            XmlEvent::Empty {
+                name: "sheet",
+                attrs,
+            } => {
+                let name = xml_utils::attr(attrs, "name").unwrap_or_default();
+                let target =
+                    xml_utils::attr(attrs, "r:id").and_then(|id| targets.get(&id).cloned());
+                target.map(|target| (name, target))
+            }
+            _ => None,
+        })
+        .collect::<Vec<(String, String)>>();
+
+    sheet_files
+        .into_iter()
+        .map(|(name, sheet_file)| {
+            let (row_numbers, rows) =
+                sheet_rows(&read_entry(archive, &sheet_file), &shared_strings);
+            Sheet {
+                name,
+                rows,
+                row_numbers,
+            }
+        })
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 11:48:55
+/// @Description: 共享字符串表, 跳过注音(rPh)
+fn shared_strings(xml: &str) -> Vec<String> {
+    let mut strings: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut in_text = false;
+    let mut in_phonetic = false;
+    for event in xml_utils::events(xml) {
+        match event {
+            XmlEvent::Start { name: "si", .. } => current.clear(),
+            XmlEvent::End { name: "si" } => strings.push(current.clone()),
+            XmlEvent::Empty { name: "si", .. } => strings.push("".into()),
+            XmlEvent::Start { name: "rPh", .. } => in_phonetic = true,
+            XmlEvent::End { name: "rPh" } => in_phonetic = false,
+            XmlEvent::Start { name: "t", .. } => in_text = true,
+            XmlEvent::End { name: "t" } => in_text = false,
+            XmlEvent::Text(text) if in_text && !in_phonetic => current.push_str(&text),
+            _ => {}
+        }
+    }
+    strings
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 11:58:41
+/// @Description: 工作表的行号和行, 单元格按列号排列, 跳过空行
+fn sheet_rows(xml: &str, shared_strings: &[String]) -> (Vec<usize>, Vec<Vec<String>>) {
+    let mut row_numbers: Vec<usize> = vec![];
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut row_no = 0;
+    let mut cell_type = String::new();
+    let mut cell_column: Option<usize> = None;
+    let mut value = String::new();
+    let mut in_value = false;
+    for event in xml_utils::events(xml) {
+        match event {
+            XmlEvent::Start { name: "row", attrs } => {
+                row.clear();
+                //没有行号时接着上一行
+                row_no = xml_utils::attr(attrs, "r")
+                    .and_then(|r| r.parse::<usize>().ok())
+                    .unwrap_or(row_no + 1);
+            }
+            XmlEvent::End { name: "row" } => {
+                while row.last().is_some_and(|cell| cell.is_empty()) {
+                    row.pop();
+                }
+                if !row.is_empty() {
+                    row_numbers.push(row_no);
+                    rows.push(row.clone());
+                }
+            }
+            XmlEvent::Start { name: "c", attrs } => {
+                cell_type = xml_utils::attr(attrs, "t").unwrap_or_default();
+                cell_column = xml_utils::attr(attrs, "r").and_then(|r| column_index(&r));
+                value.clear();
+            }
+            XmlEvent::End { name: "c" } => {
+                let text = match cell_type.as_str() {
+                    "s" => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| shared_strings.get(i).cloned())
+                        .unwrap_or_default(),
+                    "b" => match value.trim() {
+                        "1" => "TRUE".into(),
+                        _ => "FALSE".into(),
+                    },
+                    _ => value.clone(),
+                };
+                let column = cell_column.unwrap_or(row.len());
+                if row.len() <= column {
+                    row.resize(column + 1, String::new());
+                }
+                row[column] = text;
+            }
+            //单元格的值(v)或内联字符串(is/t)
+            XmlEvent::Start {
+                name: "v" | "t", ..
+            } => in_value = true,
+            XmlEvent::End { name: "v" | "t" } => in_value = false,
+            XmlEvent::Text(text) if in_value => value.push_str(&text),
+            _ => {}
+        }
+    }
+    (row_numbers, rows)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 12:10:06
+/// @Description: 单元格引用的列号(从0开始): B12 -> 1
+fn column_index(cell_ref: &str) -> Option<usize> {
+    let letters = cell_ref
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>();
+    if letters.is_empty() {
+        return None;
+    }
+    let index = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0_usize, |acc, b| acc * 26 + (b - b'A' + 1) as usize);
+    Some(index - 1)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 12:15:33
+/// @Description: 关系文件中 id -> zip中的路径
+/// @param base_dir: 相对路径的基准目录, 如 xl/
+fn rels_targets(
+    archive: &mut ZipArchive<File>,
+    rels_file: &str,
+    base_dir: &str,
+) -> HashMap<String, String> {
+    let rels = read_entry(archive, rels_file);
+    let mut targets: HashMap<String, String> = HashMap::new();
+    for event in xml_utils::events(&rels) {
+        if let XmlEvent::Empty {
+            name: "Relationship",
+            attrs,
+        } = event
+        {
+            let id = xml_utils::attr(attrs, "Id");
+            let target = xml_utils::attr(attrs, "Target");
+            if let (Some(id), Some(target)) = (id, target) {
+                let path = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => normalize_path(&format!("{}{}", base_dir, target)),
+                };
+                targets.insert(id, path);
+            }
+        }
+    }
+    targets
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 12:18:02
+/// @Description: 去掉路径中的 ./ 和 ../
+pub fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = vec![];
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 12:24:19
+/// @Description: 按名称中的编号排序的zip条目
+fn numbered_entries(archive: &mut ZipArchive<File>, pattern: &str) -> Vec<String> {
+    let re = match Regex::new(pattern) {
+        Ok(re) => re,
+        Err(_) => return vec![],
+    };
+    let mut entries = archive
+        .file_names()
+        .filter_map(|name| {
+            let number = re.captures(name)?.get(1)?.as_str().parse::<usize>().ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect::<Vec<(usize, String)>>();
+    entries.sort();
+    entries.into_iter().map(|(_, name)| name).collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 12:30:47
+/// @Description: 读取zip中的文本文件, 没有时为空
+pub fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> String {
+    let mut content = String::new();
+    if let Ok(mut entry) = archive.by_name(name) {
+        let _ = entry.read_to_string(&mut content);
+    }
+    content
+}
@@ -0,0 +1,272 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-27 09:08:36
+//! @Description: 邮件(eml、mbox、Maildir)
+//!
+//! eml和Maildir中的单个邮件: 主题、发件人、收件人、日期和正文(quoted-printable、base64、字符集已解码),
+//! 附件交给对应的提取器提取, 附件中的邮件递归处理。
+//! mbox: 扫描时和压缩包一样拆到 xxx.mbox.out 文件夹下, 每封邮件一个eml文件, 作为单独的文档索引。
+
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use log::error;
+use mail_parser::{mailbox::mbox::MessageIterator, Address, Message, MessageParser, MimeHeaders};
+
+use crate::{
+    sys::global::{to_global_result, GlobalError, GlobalResult},
+    util::text_utils,
+};
+
+use super::{
+    doc_metadata::DocMetadata,
+    extractor_registry::{self, ExtractedText, TextExtractor, MIME_MBOX},
+};
+
+//附件中的邮件最多递归的层数
+const MAX_DEPTH: usize = 3;
+//拆分mbox时文件名中主题的最大长度(字符数)
+const MAX_SUBJECT_LEN: usize = 40;
+
+/// 邮件
+pub struct EmailExtractor;
+
+impl TextExtractor for EmailExtractor {
+    fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        let bytes = to_global_result(fs::read(file_path))?;
+        match MessageParser::default().parse(&bytes) {
+            Some(message) => Ok(ExtractedText {
+                text: email_text(&message, 0),
+                metadata: email_metadata(&message),
+                ..Default::default()
+            }),
+            None => Err(GlobalError::new(format!("解析邮件失败:{}", file_path))),
+        }
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 09:20:14
+/// @Description: 邮件文本: 邮件头、正文、附件
+/// @param depth: 当前层数(附件中的邮件)
+pub fn email_text(message: &Message, depth: usize) -> String {
+    let mut lines: Vec<String> = vec![];
+    if let Some(subject) = message.subject() {
+        lines.push(format!("主题: {}", subject));
+    }
+    let headers = [
+        ("发件人", message.from()),
+        ("收件人", message.to()),
+        ("抄送", message.cc()),
+    ];
+    for (label, address) in headers {
+        if let Some(address) = address {
+            lines.push(format!("{}: {}", label, address_text(address)));
+        }
+    }
+    if let Some(date) = message.date() {
+        lines.push(format!(
+            "日期: {}",
+            text_utils::format_time(date.to_timestamp() * 1000)
+        ));
+    }
+    lines.push("".into());
+
+    //html正文会转成文本
+    for i in 0..message.text_body_count() {
+        if let Some(body) = message.body_text(i) {
+            lines.push(body.to_string());
+        }
+    }
+    for attachment in message.attachments() {
+        let name = attachment.attachment_name().unwrap_or("");
+        let text = match attachment.message() {
+            Some(inner) if depth < MAX_DEPTH => email_text(inner, depth + 1),
+            Some(_) => continue,
+            None => match attachment_text(name, attachment.contents()) {
+                Ok(text) => text,
+                Err(e) => {
+                    error!("提取附件内容异常:{}:{}", name, e);
+                    "".into()
+                }
+            },
+        };
+        lines.push("".into());
+        lines.push(format!("附件: {}", name));
+        lines.push(text);
+    }
+    lines.join("\n")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 10:36:45
+/// @Description: 邮件的元数据: 标题为主题, 作者为发件人, 创建时间为邮件日期
+fn email_metadata(message: &Message) -> DocMetadata {
+    DocMetadata {
+        title: message.subject().unwrap_or("").to_string(),
+        author: message.from().map(address_text).unwrap_or_default(),
+        created_time: message
+            .date()
+            .map(|date| date.to_timestamp() * 1000)
+            .unwrap_or(0),
+        ..Default::default()
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 09:41:52
+/// @Description: 地址: 名称 <邮箱>, 多个用逗号分隔
+fn address_text(address: &Address) -> String {
+    address
+        .iter()
+        .map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(email)) => format!("{} <{}>", name, email),
+            (Some(name), None) => name.to_string(),
+            (None, Some(email)) => email.to_string(),
+            (None, None) => "".into(),
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 09:50:27
+/// @Description: 附件写到临时文件夹, 按识别出的类型提取
+fn attachment_text(name: &str, contents: &[u8]) -> GlobalResult<String> {
+    //只取文件名, 防止名称中有路径
+    let file_name = Path::new(name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("attachment");
+    let temp_dir = std::env::temp_dir().join(format!("email_{}", uuid::Uuid::new_v4().simple()));
+    to_global_result(fs::create_dir_all(&temp_dir))?;
+    let temp_file = temp_dir.join(file_name);
+    let r = fs::write(&temp_file, contents)
+        .map_err(|e| GlobalError::new(e.to_string()))
+        .and_then(|_| extractor_registry::extract_text(&temp_file.display().to_string()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    Ok(r?.text)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 10:05:13
+/// @Description: 是否mbox文件: 扩展名为mbox、mbx, 或没有扩展名(如Thunderbird的Inbox)且内容是mbox
+pub fn is_mbox_file(file_path: &str) -> bool {
+    let ext = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "mbox" | "mbx" => true,
+        "" => matches!(extractor_registry::sniff_mime(file_path), Ok(mime) if mime == MIME_MBOX),
+        _ => false,
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 10:12:40
+/// @Description: 拆分mbox, 每封邮件写到out_dir下一个eml文件(修改时间为邮件日期), 返回邮件数量
+pub fn split_mbox(file_path: &str, out_dir: &str) -> GlobalResult<usize> {
+    let file = to_global_result(File::open(file_path))?;
+    to_global_result(fs::create_dir_all(out_dir))?;
+    let mut count = 0;
+    for message in MessageIterator::new(file) {
+        let message = match message {
+            Ok(message) => message,
+            Err(_) => {
+                error!("读取mbox异常:{}", file_path);
+                break;
+            }
+        };
+        count += 1;
+        let contents = message.unwrap_contents();
+        let parsed = MessageParser::default().parse_headers(&contents);
+        let subject = parsed
+            .as_ref()
+            .and_then(|parsed| parsed.subject())
+            .map(file_name_of)
+            .filter(|subject| !subject.is_empty())
+            .unwrap_or_else(|| "无主题".into());
+        let eml_file = format!("{}/{:05}_{}.eml", out_dir, count, subject);
+        to_global_result(fs::write(&eml_file, &contents))?;
+
+        let date = parsed.as_ref().and_then(|parsed| parsed.date());
+        if let Some(date) = date.filter(|date| date.to_timestamp() > 0) {
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(date.to_timestamp() as u64);
+            let r = File::options()
+                .write(true)
+                .open(&eml_file)
+                .and_then(|file| file.set_modified(time));
+            if let Err(e) = r {
+                error!("设置邮件修改时间异常:{}:{}", eml_file, e);
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 10:30:58
+/// @Description: 主题转成文件名: 去掉文件名中不能用的字符, 截断
+fn file_name_of(subject: &str) -> String {
+    subject
+        .chars()
+        .filter(|c| !c.is_control() && !"\\/:*?\"<>|".contains(*c))
+        .take(MAX_SUBJECT_LEN)
+        .collect::<String>()
+        .trim()
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 10:36:21
+/// @Description: 是否像邮件: 开头是完整的邮件头(名称: 值), 且有From和Date、Subject、To、Message-ID之一
+pub fn looks_like_email(head: &[u8]) -> bool {
+    //只看完整的行
+    let end = match head.iter().rposition(|b| *b == b'\n') {
+        Some(end) => end,
+        None => return false,
+    };
+    let text = String::from_utf8_lossy(&head[..end]);
+    let mut names: Vec<String> = vec![];
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            break;
+        }
+        //折行
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((name, _))
+                if !name.is_empty()
+                    && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') =>
+            {
+                names.push(name.to_ascii_lowercase())
+            }
+            _ => return false,
+        }
+    }
+    let has = |name: &str| names.iter().any(|n| n == name);
+    has("from") && (has("date") || has("subject") || has("to") || has("message-id"))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-27 10:48:07
+/// @Description: 是否像mbox: 第一行是"From "分隔行, 后面是邮件头
+pub fn looks_like_mbox(head: &[u8]) -> bool {
+    if !head.starts_with(b"From ") {
+        return false;
+    }
+    match head.iter().position(|b| *b == b'\n') {
+        Some(i) => looks_like_email(&head[i + 1..]),
+        None => false,
+    }
+}
@@ -0,0 +1,176 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-10 15:30:12
+//! @Description: EPUB电子书, 直接读zip中的OPF和章节xhtml, 不依赖tika
+//!
+//! META-INF/container.xml指向OPF文件, 按OPF中spine的顺序读取章节, 每章一个结构单元,
+//! 位置是章节的第一个标题(没有时用title, 再没有时用文件名)。
+
+use std::{collections::HashMap, fs::File};
+
+use zip::ZipArchive;
+
+use crate::{
+    sys::global::{to_global_result, GlobalError, GlobalResult},
+    util::xml_utils::{self, XmlEvent},
+};
+
+use super::{
+    doc_metadata,
+    doc_sections::{self, TextSection},
+    extractor_registry::{ExtractedText, TextExtractor},
+};
+
+//章节标题作为位置时的最大长度(字符数)
+const MAX_TITLE_LEN: usize = 50;
+
+/// EPUB电子书
+pub struct EpubExtractor;
+
+impl TextExtractor for EpubExtractor {
+    fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        let file = to_global_result(File::open(file_path))?;
+        let mut archive = to_global_result(ZipArchive::new(file))?;
+        let container = doc_sections::read_entry(&mut archive, "META-INF/container.xml");
+        let opf_path = xml_utils::events(&container)
+            .find_map(|event| match event {
+                XmlEvent::Start {
+                    name: "rootfile",
+                    attrs,
+                }
+                | XmlEvent::Empty {
+                    name: "rootfile",
+                    attrs,
+                } => xml_utils::attr(attrs, "full-path"),
+                _ => None,
+            })
+            .ok_or_else(|| GlobalError::new(format!("EPUB没有OPF文件:{}", file_path)))?;
+        let opf = doc_sections::read_entry(&mut archive, &opf_path);
+        //章节的路径相对于OPF所在目录
+        let base_dir = match opf_path.rfind('/') {
+            Some(i) => &opf_path[..i + 1],
+            None => "",
+        };
+        let sections = spine_chapters(&opf)
+            .into_iter()
+            .filter_map(|href| {
+                let path = doc_sections::normalize_path(&format!("{}{}", base_dir, href));
+                let xhtml = doc_sections::read_entry(&mut archive, &path);
+                let text = doc_sections::html_text(&xhtml);
+                if text.trim().is_empty() {
+                    return None;
+                }
+                Some(TextSection::new(chapter_title(&xhtml, &href), text))
+            })
+            .collect::<Vec<TextSection>>();
+        Ok(ExtractedText {
+            text: doc_sections::join_sections(&sections),
+            sections,
+            metadata: doc_metadata::opf_metadata(&opf),
+            ..Default::default()
+        })
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 15:45:27
+/// @Description: 按spine顺序的章节路径(相对于OPF, 已去掉锚点和百分号编码)
+pub fn spine_chapters(opf: &str) -> Vec<String> {
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine: Vec<String> = vec![];
+    for event in xml_utils::events(opf) {
+        let (name, attrs) = match event {
+            XmlEvent::Start { name, attrs } | XmlEvent::Empty { name, attrs } => (name, attrs),
+            _ => continue,
+        };
+        match xml_utils::local_name(name) {
+            "item" => {
+                let id = xml_utils::attr(attrs, "id");
+                let href = xml_utils::attr(attrs, "href");
+                if let (Some(id), Some(href)) = (id, href) {
+                    manifest.insert(id, href);
+                }
+            }
+            "itemref" => {
+                if let Some(idref) = xml_utils::attr(attrs, "idref") {
+                    spine.push(idref);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut chapters: Vec<String> = vec![];
+    for idref in spine {
+        if let Some(href) = manifest.get(&idref) {
+            let href = percent_decode(href.split('#').next().unwrap_or(""));
+            if !href.is_empty() && !chapters.contains(&href) {
+                chapters.push(href);
+            }
+        }
+    }
+    chapters
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 16:02:50
+/// @Description: 章节标题: 第一个h1~h3, 没有时用title, 再没有时用文件名
+pub fn chapter_title(xhtml: &str, href: &str) -> String {
+    let mut title = String::new();
+    let mut heading = String::new();
+    let mut in_title = false;
+    let mut in_heading = false;
+    for event in xml_utils::events(xhtml) {
+        match event {
+            XmlEvent::Start { name, .. } => match xml_utils::local_name(name) {
+                "title" => in_title = true,
+                "h1" | "h2" | "h3" => in_heading = true,
+                _ => {}
+            },
+            XmlEvent::End { name } => match xml_utils::local_name(name) {
+                "title" => in_title = false,
+                "h1" | "h2" | "h3" => {
+                    if !heading.trim().is_empty() {
+                        break;
+                    }
+                    in_heading = false;
+                }
+                _ => {}
+            },
+            XmlEvent::Text(text) if in_heading => heading.push_str(&text),
+            XmlEvent::Text(text) if in_title => title.push_str(&text),
+            _ => {}
+        }
+    }
+    let file_name = href.rsplit('/').next().unwrap_or(href).to_string();
+    [heading, title, file_name]
+        .into_iter()
+        .map(|text| text.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .find(|text| !text.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_TITLE_LEN)
+        .collect()
+}
+
+//百分号编码的路径: %E7%AC%AC.xhtml -> 第.xhtml
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,385 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-11 09:20:14
+//! @Description: 文本提取子进程, 隔离extractous/tika中的卡死和崩溃
+//!
+//! 主程序用--extract-worker参数启动自己作为子进程, 通过标准输入输出一行一个json收发请求和结果。
+//! 每个文件有超时时间和内存上限, 超过时杀掉子进程, 这个文件记为失败; 子进程退出后下一个文件会重新启动。
+//! 超时、内存上限、子进程数量在数据目录下extract_worker.yaml配置, 没有设置子进程程序时在当前进程提取。
+
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::Path,
+    process::Stdio,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::{Mutex, Semaphore},
+    time::Instant,
+};
+
+use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
+
+use super::{
+    extractor_registry::{self, ExtractedText},
+    ocr,
+};
+
+//启动子进程的参数: --extract-worker <数据目录>
+pub const WORKER_ARG: &str = "--extract-worker";
+//配置文件(数据目录下)
+pub const WORKER_CONFIG_FILE: &str = "extract_worker.yaml";
+//结果行的前缀, 其他输出(native库打印的日志等)忽略
+const RESPONSE_PREFIX: &str = "@extract@";
+//检查超时和内存的间隔
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+lazy_static! {
+    //子进程程序, 一般是主程序自己
+    static ref WORKER_PROGRAM: RwLock<Option<String>> = RwLock::new(None);
+    //子进程池, 没有设置子进程程序或者配置中关闭时为None
+    static ref WORKER_POOL: RwLock<Option<Arc<ExtractWorkerPool>>> = RwLock::new(None);
+}
+
+/// 提取子进程配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractWorkerConfig {
+    pub enabled: bool,        //是否在子进程中提取
+    pub workers: usize,       //子进程数量(同时提取的文件数)
+    pub timeout_secs: u64,    //每个文件的超时时间(秒)
+    pub memory_limit_mb: u64, //子进程的内存上限(MB), 0为不限制
+}
+
+impl Default for ExtractWorkerConfig {
+    fn default() -> Self {
+        ExtractWorkerConfig {
+            enabled: true,
+            workers: 4,
+            timeout_secs: 300,
+            memory_limit_mb: 2048,
+        }
+    }
+}
+
+/// 提取请求
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkerRequest {
+    file_path: String,
+    ocr: bool,
+}
+
+/// 提取结果
+#[derive(Debug, Serialize, Deserialize)]
+enum WorkerResponse {
+    Ok(ExtractedText),
+    Err(String),
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 09:31:45
+/// @Description: 设置子进程程序(主程序启动界面前调用)
+pub fn set_worker_program(program: &str) {
+    *WORKER_PROGRAM.write().unwrap() = Some(program.into());
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 09:33:20
+/// @Description: 加载配置, 文件不存在时用默认配置; 重建子进程池(旧的子进程用的是之前的数据目录)
+pub fn load_config(data_dir: &str) -> GlobalResult<()> {
+    let config_file = format!("{}/{}", data_dir, WORKER_CONFIG_FILE);
+    let config = if Path::new(&config_file).is_file() {
+        let content = to_global_result(fs::read_to_string(&config_file))?;
+        to_global_result(serde_yaml::from_str::<ExtractWorkerConfig>(&content))?
+    } else {
+        ExtractWorkerConfig::default()
+    };
+    info!("提取子进程配置:{:?}", config);
+    let program = WORKER_PROGRAM.read().unwrap().clone();
+    *WORKER_POOL.write().unwrap() = match program {
+        Some(program) if config.enabled => {
+            Some(Arc::new(ExtractWorkerPool::new(&program, data_dir, config)))
+        }
+        _ => None,
+    };
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 09:36:02
+/// @Description: 配置文件路径, 不存在时按默认配置创建
+pub fn config_file(data_dir: &str) -> GlobalResult<String> {
+    let config_file = format!("{}/{}", data_dir, WORKER_CONFIG_FILE);
+    if !Path::new(&config_file).is_file() {
+        let content = to_global_result(serde_yaml::to_string(&ExtractWorkerConfig::default()))?;
+        to_global_result(fs::write(&config_file, content))?;
+    }
+    Ok(config_file)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 09:38:27
+/// @Description: 当前的子进程池
+pub fn worker_pool() -> Option<Arc<ExtractWorkerPool>> {
+    WORKER_POOL.read().unwrap().clone()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-11 09:40:51
+/// @Description: 子进程入口: 从标准输入读请求, 结果写到标准输出, 输入关闭时退出
+pub fn run_worker(data_dir: &str) -> GlobalResult<()> {
+    if let Err(e) = ocr::load_config(data_dir) {
+        error!("加载OCR配置异常:{}", e);
+    }
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = to_global_result(line)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<WorkerRequest>(&line) {
+            //提取器中的panic不用重启子进程
+            Ok(request) => std::panic::catch_unwind(|| {
+                extractor_registry::extract_text_with_ocr(&request.file_path, request.ocr)
+            })
+            .unwrap_or_else(|_| Err(GlobalError::new("提取文本时panic".into())))
+            .map_or_else(|e| WorkerResponse::Err(e.msg), WorkerResponse::Ok),
+            Err(e) => WorkerResponse::Err(format!("请求格式错误:{}", e)),
+        };
+        let json = to_global_result(serde_json::to_string(&response))?;
+        to_global_result(writeln!(stdout, "{}{}", RESPONSE_PREFIX, json))?;
+        to_global_result(stdout.flush())?;
+    }
+    Ok(())
+}
+
+/// 提取子进程池
+pub struct ExtractWorkerPool {
+    program: String,                 //子进程程序
+    data_dir: String,                //数据目录, 子进程按它加载OCR配置
+    config: ExtractWorkerConfig,     //配置
+    idle: Mutex<Vec<WorkerProcess>>, //空闲的子进程
+    permits: Semaphore,              //同时提取的文件数
+}
+
+impl ExtractWorkerPool {
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-11 09:52:16
+    /// @Description: 新建, 子进程在提取时才启动
+    pub fn new(program: &str, data_dir: &str, config: ExtractWorkerConfig) -> Self {
+        let workers = config.workers.max(1);
+        ExtractWorkerPool {
+            program: program.into(),
+            data_dir: data_dir.into(),
+            config,
+            idle: Mutex::new(vec![]),
+            permits: Semaphore::new(workers),
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-11 09:55:43
+    /// @Description: 在子进程中提取文本; 超时、超过内存上限、子进程崩溃时返回错误, 并结束这个子进程
+    pub async fn extract(&self, file_path: &str, ocr: bool) -> GlobalResult<ExtractedText> {
+        let _permit = to_global_result(self.permits.acquire().await)?;
+        let idle = self.idle.lock().await.pop();
+        let mut worker = match idle {
+            Some(worker) => worker,
+            None => WorkerProcess::spawn(&self.program, &self.data_dir)?,
+        };
+        let request = WorkerRequest {
+            file_path: file_path.into(),
+            ocr,
+        };
+        match worker.request(&request, &self.config).await {
+            Ok(response) => {
+                self.idle.lock().await.push(worker);
+                match response {
+                    WorkerResponse::Ok(extracted) => Ok(extracted),
+                    WorkerResponse::Err(msg) => Err(GlobalError::new(msg)),
+                }
+            }
+            Err(e) => {
+                error!("提取子进程异常, 结束子进程:{}:{}", file_path, e);
+                worker.kill().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-11 10:02:08
+    /// @Description: 空闲的子进程数量
+    pub async fn idle_count(&self) -> usize {
+        self.idle.lock().await.len()
+    }
+}
+
+/// 一个提取子进程
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl WorkerProcess {
+    fn spawn(program: &str, data_dir: &str) -> GlobalResult<Self> {
+        let mut cmd = Command::new(program);
+        cmd.arg(WORKER_ARG)
+            .arg(data_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => return Err(GlobalError::new(format!("启动提取子进程失败:{}", e))),
+        };
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(GlobalError::new("提取子进程没有标准输入输出".into()));
+        };
+        Ok(WorkerProcess {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    /// 发送请求并等待结果, 等待时检查超时和内存
+    async fn request(
+        &mut self,
+        request: &WorkerRequest,
+        config: &ExtractWorkerConfig,
+    ) -> GlobalResult<WorkerResponse> {
+        let line = to_global_result(serde_json::to_string(request))? + "\n";
+        let WorkerProcess {
+            child,
+            stdin,
+            stdout,
+        } = self;
+        if let Err(e) = stdin.write_all(line.as_bytes()).await {
+            return Err(GlobalError::new(format!("提取子进程已退出:{}", e)));
+        }
+        to_global_result(stdin.flush().await)?;
+
+        let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+        let memory_limit = config.memory_limit_mb * 1024 * 1024;
+        let response = read_response(stdout);
+        tokio::pin!(response);
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                response = &mut response => {
+                    return match response? {
+                        Some(response) => Ok(response),
+                        None => {
+                            let status = to_global_result(child.wait().await)?;
+                            Err(GlobalError::new(format!("提取子进程异常退出:{}", status)))
+                        }
+                    };
+                }
+                _ = interval.tick() => {
+                    if Instant::now() >= deadline {
+                        return Err(GlobalError::new(format!(
+                            "提取超时({}秒)",
+                            config.timeout_secs
+                        )));
+                    }
+                    if let (true, Some(memory)) = (memory_limit > 0, process_memory(child)) {
+                        if memory > memory_limit {
+                            return Err(GlobalError::new(format!(
+                                "提取内存超出上限({}MB)",
+                                config.memory_limit_mb
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn kill(mut self) {
+        let _ = self.child.kill().await;
+    }
+}
+
+//读一个结果, 子进程退出(输出关闭)时返回None
+async fn read_response(
+    stdout: &mut BufReader<ChildStdout>,
+) -> GlobalResult<Option<WorkerResponse>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if to_global_result(stdout.read_line(&mut line).await)? == 0 {
+            return Ok(None);
+        }
+        if let Some(json) = line.trim_end().strip_prefix(RESPONSE_PREFIX) {
+            return to_global_result(serde_json::from_str::<WorkerResponse>(json)).map(Some);
+        }
+    }
+}
+
+//子进程占用的内存(字节), 取不到时为None
+#[cfg(target_os = "linux")]
+fn process_memory(child: &Child) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", child.id()?)).ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kb * 1024)
+}
+
+//子进程占用的内存(字节, 工作集), 取不到时为None
+#[cfg(windows)]
+fn process_memory(child: &Child) -> Option<u64> {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn K32GetProcessMemoryInfo(
+            process: *mut c_void,
+            counters: *mut ProcessMemoryCounters,
+            cb: u32,
+        ) -> i32;
+    }
+
+    let handle = child.raw_handle()?;
+    let mut counters = ProcessMemoryCounters {
+        cb: std::mem::size_of::<ProcessMemoryCounters>() as u32,
+        ..Default::default()
+    };
+    let ok = unsafe { K32GetProcessMemoryInfo(handle as *mut c_void, &mut counters, counters.cb) };
+    (ok != 0).then_some(counters.working_set_size as u64)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn process_memory(_child: &Child) -> Option<u64> {
+    None
+}
@@ -0,0 +1,546 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-24 09:05:31
+//! @Description: 文本提取器注册表，按MIME类型选择提取器
+//!
+//! MIME类型先按文件头(魔数)识别，识别不了再看是否文本文件，最后按扩展名。
+//! 邮件(eml、mbox)按邮件头识别。
+//! 查找提取器时先找完整类型(image/png)，再找大类(image/*)，都没有时用默认提取器(extractous)。
+//! 可以用register_extractor注册新格式或覆盖内置的提取器。
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use extractous::Extractor;
+use lazy_static::lazy_static;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::charset,
+};
+
+use super::{
+    doc_metadata::{self, DocMetadata},
+    doc_sections::{self, TextSection},
+    email_extractor::{self, EmailExtractor},
+    epub_extractor::EpubExtractor,
+    is_compress_file, ocr,
+    odf_extractor::OdfExtractor,
+    rtf_extractor::RtfExtractor,
+    table_records,
+};
+
+//识别类型时读取的文件头长度
+const SNIFF_LEN: usize = 8192;
+//tika提取的文本最大长度
+const TEXT_MAX_LEN: i32 = 500_000;
+//tika输出的xhtml最大长度: 每段、每页都有标签, 短行多的PDF标签比文本还长
+const XHTML_MAX_LEN: i32 = TEXT_MAX_LEN * 10;
+
+pub const MIME_DOCX: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+pub const MIME_XLSX: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+pub const MIME_PPTX: &str =
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+pub const MIME_ODT: &str = "application/vnd.oasis.opendocument.text";
+pub const MIME_ODS: &str = "application/vnd.oasis.opendocument.spreadsheet";
+pub const MIME_ODP: &str = "application/vnd.oasis.opendocument.presentation";
+pub const MIME_EPUB: &str = "application/epub+zip";
+pub const MIME_RTF: &str = "application/rtf";
+pub const MIME_PDF: &str = "application/pdf";
+pub const MIME_TEXT: &str = "text/plain";
+pub const MIME_CSV: &str = "text/csv";
+pub const MIME_TSV: &str = "text/tab-separated-values";
+pub const MIME_ARCHIVE: &str = "application/x-archive";
+pub const MIME_OCTET_STREAM: &str = "application/octet-stream";
+pub const MIME_EML: &str = "message/rfc822";
+pub const MIME_MBOX: &str = "application/mbox";
+
+/// 提取结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExtractedText {
+    pub text: String,               //文本内容
+    pub encoding: Option<String>,   //文本文件识别出的编码
+    pub metadata: DocMetadata,      //文档元数据
+    pub sections: Vec<TextSection>, //结构单元(PDF页、幻灯片、工作表等), 有时text为所有单元的内容
+}
+
+impl From<String> for ExtractedText {
+    fn from(text: String) -> Self {
+        ExtractedText {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+/// 文本提取器, 在阻塞线程中调用
+pub trait TextExtractor: Send + Sync {
+    /// 提取文件的文本内容, mime为识别出的类型
+    fn extract(&self, file_path: &str, mime: &str) -> GlobalResult<ExtractedText>;
+}
+
+lazy_static! {
+    static ref REGISTRY: RwLock<ExtractorRegistry> = RwLock::new(ExtractorRegistry::default());
+
+    //内容提取器， 最长 TEXT_MAX_LEN
+    static ref ARC_EXTRACTOR: Arc<Extractor> =
+        Arc::new(Extractor::new().set_extract_string_max_length(TEXT_MAX_LEN));
+
+    //输出xhtml的提取器(PDF分页), 长度按xhtml算(包括标签), 放宽到能容纳TEXT_MAX_LEN的文本
+    static ref ARC_XML_EXTRACTOR: Arc<Extractor> = Arc::new(
+        Extractor::new()
+            .set_extract_string_max_length(XHTML_MAX_LEN)
+            .set_xml_output(true)
+    );
+}
+
+/// 提取器注册表
+pub struct ExtractorRegistry {
+    extractors: HashMap<String, Arc<dyn TextExtractor>>, //MIME类型(可以是 image/* 这样的大类) -> 提取器
+    default_extractor: Arc<dyn TextExtractor>,           //没有注册的类型用的提取器
+}
+
+impl Default for ExtractorRegistry {
+    /// 内置提取器
+    fn default() -> Self {
+        let mut registry = ExtractorRegistry {
+            extractors: HashMap::new(),
+            default_extractor: Arc::new(TikaExtractor),
+        };
+        //office 2007格式
+        registry.register(MIME_DOCX, Arc::new(OfficeXExtractor));
+        registry.register(MIME_XLSX, Arc::new(OfficeXExtractor));
+        registry.register(MIME_PPTX, Arc::new(OfficeXExtractor));
+        //OpenDocument、RTF、EPUB直接解析, 不依赖tika
+        registry.register(MIME_ODT, Arc::new(OdfExtractor));
+        registry.register(MIME_ODS, Arc::new(OdfExtractor));
+        registry.register(MIME_ODP, Arc::new(OdfExtractor));
+        registry.register(MIME_RTF, Arc::new(RtfExtractor));
+        registry.register(MIME_EPUB, Arc::new(EpubExtractor));
+        //图片只取元数据(EXIF), 压缩包不提取
+        registry.register("image/*", Arc::new(MetadataOnlyExtractor));
+        registry.register(MIME_ARCHIVE, Arc::new(EmptyExtractor));
+        registry.register("application/zip", Arc::new(EmptyExtractor));
+        registry.register("text/*", Arc::new(PlainTextExtractor));
+        //表格按行
+        registry.register(MIME_CSV, Arc::new(CsvExtractor));
+        registry.register(MIME_TSV, Arc::new(CsvExtractor));
+        //邮件, mbox在扫描时拆成单个邮件
+        registry.register(MIME_EML, Arc::new(EmailExtractor));
+        registry.register(MIME_MBOX, Arc::new(EmptyExtractor));
+        registry
+    }
+}
+
+impl ExtractorRegistry {
+    /// 注册提取器, 已有的同类型提取器被覆盖
+    pub fn register(&mut self, mime: &str, extractor: Arc<dyn TextExtractor>) {
+        self.extractors.insert(mime.to_lowercase(), extractor);
+    }
+
+    /// 设置默认提取器
+    pub fn set_default(&mut self, extractor: Arc<dyn TextExtractor>) {
+        self.default_extractor = extractor;
+    }
+
+    /// 按MIME类型查找提取器: 完整类型 -> 大类 -> 默认
+    pub fn get(&self, mime: &str) -> Arc<dyn TextExtractor> {
+        let mime = mime.to_lowercase();
+        if let Some(extractor) = self.extractors.get(&mime) {
+            return extractor.clone();
+        }
+        if let Some((top, _)) = mime.split_once('/') {
+            if let Some(extractor) = self.extractors.get(&format!("{}/*", top)) {
+                return extractor.clone();
+            }
+        }
+        self.default_extractor.clone()
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 09:30:12
+/// @Description: 注册提取器(全局), 覆盖同类型已有的提取器
+pub fn register_extractor(mime: &str, extractor: Arc<dyn TextExtractor>) {
+    REGISTRY.write().unwrap().register(mime, extractor);
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 09:33:40
+/// @Description: 按MIME类型取提取器(全局)
+pub fn extractor_for(mime: &str) -> Arc<dyn TextExtractor> {
+    REGISTRY.read().unwrap().get(mime)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 09:36:22
+/// @Description: 识别类型并用对应的提取器提取文本(阻塞)
+pub fn extract_text(file_path: &str) -> GlobalResult<ExtractedText> {
+    extract_text_with_ocr(file_path, false)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:40:15
+/// @Description: 提取文本, ocr为true时图片和扫描版PDF再用OCR识别(阻塞)
+/// OCR失败(没有安装、超时等)时保留提取器的结果
+pub fn extract_text_with_ocr(file_path: &str, ocr: bool) -> GlobalResult<ExtractedText> {
+    let mime = sniff_mime(file_path)?;
+    let mut extracted = extractor_for(&mime).extract(file_path, &mime)?;
+    if ocr && ocr::is_ocr_mime(&mime) {
+        if mime == MIME_PDF {
+            //提取器有分页时只识别文字太少的页, 没有分页时用pdftotext的分页
+            let paged = extracted.sections.iter().all(|s| !s.location.is_empty());
+            let mut pages = match paged {
+                true => extracted.sections.iter().map(|s| s.text.clone()).collect(),
+                false => vec![],
+            };
+            match ocr::recognize_pdf_pages(file_path, &mut pages) {
+                Ok(true) => {
+                    if paged && pages.len() == extracted.sections.len() {
+                        for (section, page) in extracted.sections.iter_mut().zip(pages) {
+                            section.text = page;
+                        }
+                    } else {
+                        extracted.sections = pages
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, page)| {
+                                TextSection::new(doc_sections::page_location(i + 1), page)
+                            })
+                            .collect();
+                    }
+                    extracted.text = doc_sections::join_sections(&extracted.sections);
+                }
+                Ok(false) => {}
+                Err(e) => error!("{}:OCR识别失败:{}", file_path, e),
+            }
+        } else {
+            match ocr::recognize_image_file(file_path) {
+                Ok(text) => extracted.text = text,
+                Err(e) => error!("{}:OCR识别失败:{}", file_path, e),
+            }
+        }
+    }
+    Ok(extracted)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 09:40:05
+/// @Description: 识别文件的MIME类型: 魔数 -> 文本 -> 扩展名
+pub fn sniff_mime(file_path: &str) -> GlobalResult<String> {
+    let mut file = to_global_result(File::open(file_path))?;
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    to_global_result(file.by_ref().take(SNIFF_LEN as u64).read_to_end(&mut head))?;
+    let ext = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    if let Some(mime) = mime_by_magic(&head, &ext) {
+        return Ok(mime.into());
+    }
+    //UTF-16文本有大量0字节
+    if charset::is_utf16(&head) || (!head.is_empty() && is_text(&head)) {
+        return Ok(match ext.as_str() {
+            "csv" => MIME_CSV,
+            "tsv" => MIME_TSV,
+            //邮件头很长时文件头里看不到From:等, 按扩展名
+            "eml" => MIME_EML,
+            "mbox" | "mbx" => MIME_MBOX,
+            _ => MIME_TEXT,
+        }
+        .into());
+    }
+    Ok(mime_by_ext(file_path, &ext).into())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 09:52:48
+/// @Description: 按文件头识别
+/// zip、OLE2是容器格式, 再看内容或扩展名区分具体的文档类型
+fn mime_by_magic(head: &[u8], ext: &str) -> Option<&'static str> {
+    let starts = |magic: &[u8]| head.starts_with(magic);
+    if starts(b"%PDF-") {
+        return Some(MIME_PDF);
+    }
+    if starts(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if starts(b"\xff\xd8\xff") {
+        return Some("image/jpeg");
+    }
+    if starts(b"GIF87a") || starts(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if starts(b"BM") && ext == "bmp" {
+        return Some("image/bmp");
+    }
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    if starts(b"II*\x00") || starts(b"MM\x00*") {
+        return Some("image/tiff");
+    }
+    if starts(b"PK\x03\x04") {
+        //OpenDocument、EPUB的第一个条目是不压缩的mimetype
+        if let Some(mime) = zip_mimetype(head) {
+            return Some(mime);
+        }
+        //zip中的目录名
+        let mime = if contains(head, b"word/") {
+            MIME_DOCX
+        } else if contains(head, b"xl/") {
+            MIME_XLSX
+        } else if contains(head, b"ppt/") {
+            MIME_PPTX
+        } else {
+            match ext {
+                "docx" => MIME_DOCX,
+                "xlsx" => MIME_XLSX,
+                "pptx" => MIME_PPTX,
+                "odt" => MIME_ODT,
+                "ods" => MIME_ODS,
+                "odp" => MIME_ODP,
+                "epub" => MIME_EPUB,
+                "zip" => "application/zip",
+                _ => return None,
+            }
+        };
+        return Some(mime);
+    }
+    if starts(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+        return Some(match ext {
+            "doc" => "application/msword",
+            "xls" => "application/vnd.ms-excel",
+            "ppt" => "application/vnd.ms-powerpoint",
+            "msi" => MIME_ARCHIVE,
+            _ => "application/x-ole-storage",
+        });
+    }
+    if starts(b"Rar!\x1a\x07")
+        || starts(b"7z\xbc\xaf\x27\x1c")
+        || starts(b"\x1f\x8b")
+        || starts(b"\xfd7zXZ\x00")
+        || starts(b"MSCF")
+        || (head.len() > 262 && &head[257..262] == b"ustar")
+    {
+        return Some(MIME_ARCHIVE);
+    }
+    if starts(b"{\\rtf") {
+        return Some(MIME_RTF);
+    }
+    //邮件头是文本, 要在判断文本之前
+    if email_extractor::looks_like_mbox(head) {
+        return Some(MIME_MBOX);
+    }
+    if email_extractor::looks_like_email(head) {
+        return Some(MIME_EML);
+    }
+    None
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 10:10:26
+/// @Description: 按扩展名识别(文件头识别不了且不是文本时)
+fn mime_by_ext(file_path: &str, ext: &str) -> &'static str {
+    if is_compress_file(file_path) {
+        return MIME_ARCHIVE;
+    }
+    match ext {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "pdf" => MIME_PDF,
+        "docx" => MIME_DOCX,
+        "xlsx" => MIME_XLSX,
+        "pptx" => MIME_PPTX,
+        "odt" => MIME_ODT,
+        "ods" => MIME_ODS,
+        "odp" => MIME_ODP,
+        "epub" => MIME_EPUB,
+        "rtf" => MIME_RTF,
+        "txt" | "log" | "md" => MIME_TEXT,
+        "csv" => MIME_CSV,
+        "tsv" => MIME_TSV,
+        "eml" => MIME_EML,
+        "mbox" | "mbx" => MIME_MBOX,
+        _ => MIME_OCTET_STREAM,
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 16:20:35
+/// @Description: zip第一个条目是不压缩的mimetype时, 按其内容识别(OpenDocument、EPUB)
+fn zip_mimetype(head: &[u8]) -> Option<&'static str> {
+    //本地文件头: 压缩方式(8-9)、压缩后大小(18-21)、文件名长度(26-27)、扩展字段长度(28-29)、文件名(30-)
+    let u16_at = |i: usize| Some(u16::from_le_bytes(head.get(i..i + 2)?.try_into().ok()?) as usize);
+    let name_len = u16_at(26)?;
+    let extra_len = u16_at(28)?;
+    if u16_at(8)? != 0 || head.get(30..30 + name_len)? != b"mimetype" {
+        return None;
+    }
+    let size = u32::from_le_bytes(head.get(18..22)?.try_into().ok()?) as usize;
+    let start = 30 + name_len + extra_len;
+    let mimetype = head.get(start..start + size)?;
+    [MIME_ODT, MIME_ODS, MIME_ODP, MIME_EPUB]
+        .into_iter()
+        .find(|mime| mimetype == mime.as_bytes())
+}
+
+fn contains(head: &[u8], pattern: &[u8]) -> bool {
+    head.windows(pattern.len()).any(|w| w == pattern)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-24 10:18:51
+/// @Description: 是否文本: 非打印字符少于10%(按比例比较, 不足10字节的短文件也适用)
+fn is_text(head: &[u8]) -> bool {
+    let non_printable_count = head
+        .iter()
+        .filter(|&&byte| byte < 0x09 || (byte > 0x0D && byte < 0x20))
+        .count();
+    non_printable_count * 10 < head.len()
+}
+
+/// office 2007格式(docx、xlsx、pptx)
+pub struct OfficeXExtractor;
+
+impl TextExtractor for OfficeXExtractor {
+    fn extract(&self, file_path: &str, mime: &str) -> GlobalResult<ExtractedText> {
+        //按结构单元读取(xlsx按行), 读不到时整篇提取
+        let sections = match mime {
+            MIME_XLSX => table_records::xlsx_records(file_path),
+            _ => doc_sections::officex_sections(file_path, mime),
+        };
+        let sections = sections.unwrap_or_else(|e| {
+            error!("{}:读取文档结构失败:{}", file_path, e);
+            vec![]
+        });
+        let mut extracted = if sections.is_empty() {
+            let r = match mime {
+                MIME_DOCX => rust_common::office_text_extractor::extract_text_from_docx(file_path),
+                MIME_XLSX => rust_common::office_text_extractor::extract_text_from_xlsx(file_path),
+                MIME_PPTX => rust_common::office_text_extractor::extract_text_from_pptx(file_path),
+                _ => Err("officex不支持的文件格式".into()),
+            };
+            ExtractedText::from(to_global_result(r)?)
+        } else {
+            ExtractedText {
+                text: doc_sections::join_sections(&sections),
+                sections,
+                ..Default::default()
+            }
+        };
+        match doc_metadata::officex_metadata(file_path) {
+            Ok(metadata) => extracted.metadata = metadata,
+            Err(e) => error!("{}:读取文档属性失败:{}", file_path, e),
+        }
+        Ok(extracted)
+    }
+}
+
+/// 文本文件, 自动识别编码, 按行建索引
+pub struct PlainTextExtractor;
+
+impl TextExtractor for PlainTextExtractor {
+    fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        let buffer = to_global_result(std::fs::read(file_path))?;
+        let decoded = charset::decode(&buffer);
+        if decoded.lossy {
+            info!("{}:按{}解码有无法识别的字符", file_path, decoded.encoding);
+        }
+        //按行切分, 搜索结果能定位到行
+        let sections = match decoded.text.trim().is_empty() {
+            true => vec![],
+            false => vec![TextSection::lines(1, decoded.text.as_str())],
+        };
+        Ok(ExtractedText {
+            text: decoded.text,
+            encoding: Some(decoded.encoding.to_string()),
+            sections,
+            ..Default::default()
+        })
+    }
+}
+
+/// CSV、TSV, 自动识别编码和分隔符, 按行建索引
+pub struct CsvExtractor;
+
+impl TextExtractor for CsvExtractor {
+    fn extract(&self, file_path: &str, mime: &str) -> GlobalResult<ExtractedText> {
+        let mut extracted = PlainTextExtractor.extract(file_path, mime)?;
+        let ext = match mime {
+            MIME_TSV => "tsv",
+            _ => "csv",
+        };
+        let delimiter = table_records::sniff_delimiter(&extracted.text, ext);
+        let records = table_records::csv_records(&extracted.text, delimiter);
+        //只有表头时和文本文件一样按行
+        if !records.is_empty() {
+            extracted.text = doc_sections::join_sections(&records);
+            extracted.sections = records;
+        }
+        Ok(extracted)
+    }
+}
+
+/// extractous(pdf、doc等), 元数据来自PDF信息字典、office文档属性、ID3等
+/// PDF输出xhtml, 按页拆分
+pub struct TikaExtractor;
+
+impl TextExtractor for TikaExtractor {
+    fn extract(&self, file_path: &str, mime: &str) -> GlobalResult<ExtractedText> {
+        if mime == MIME_PDF {
+            return match ARC_XML_EXTRACTOR.extract_file_to_string(file_path) {
+                Ok((xhtml, metadata)) => {
+                    let sections = doc_sections::pdf_sections(&xhtml);
+                    Ok(ExtractedText {
+                        text: doc_sections::join_sections(&sections),
+                        sections,
+                        metadata: DocMetadata::from_tika(&metadata),
+                        ..Default::default()
+                    })
+                }
+                Err(_) => Ok(ExtractedText::default()),
+            };
+        }
+        match ARC_EXTRACTOR.extract_file_to_string(file_path) {
+            Ok((text, metadata)) => Ok(ExtractedText {
+                text,
+                metadata: DocMetadata::from_tika(&metadata),
+                ..Default::default()
+            }),
+            Err(_) => Ok(ExtractedText::default()),
+        }
+    }
+}
+
+/// 只取元数据不要内容(图片的EXIF, 直接解析, 不用tika)
+pub struct MetadataOnlyExtractor;
+
+impl TextExtractor for MetadataOnlyExtractor {
+    fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        Ok(ExtractedText {
+            metadata: doc_metadata::exif_metadata(file_path)?,
+            ..Default::default()
+        })
+    }
+}
+
+/// 不提取内容(图片、压缩包)
+pub struct EmptyExtractor;
+
+impl TextExtractor for EmptyExtractor {
+    fn extract(&self, _file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        Ok(ExtractedText::default())
+    }
+}
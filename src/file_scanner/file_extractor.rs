@@ -7,7 +7,7 @@ use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-use crate::component::comp_7z::decompress_file;
+use crate::component::comp_7z::{decompress_file, SEVEN_Z_PATH};
 use crate::sys::global::{to_global_result, GlobalResult};
 
 use log::{error, info};
@@ -15,7 +15,7 @@ use tokio::sync::mpsc::Sender;
 use tokio::sync::{mpsc, Semaphore};
 use tokio::{fs, task};
 
-use super::is_compress_file;
+use super::{email_extractor, is_compress_file};
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-07-26 10:15:53
@@ -53,7 +53,7 @@ pub async fn extract_file(file_path: &str, file_sender: Sender<String>) -> Globa
     if let Err(e) = r {
         error!("删除out解压目录异常:{}", e);
     }
-    let extractor = FileExtractor::new(r#"./7z/7z.exe"#, 10000);
+    let extractor = FileExtractor::new(SEVEN_Z_PATH, 10000);
     extractor.start(file_path.into(), file_sender).await
 }
 
@@ -178,15 +178,30 @@ async fn do_extract_file(
         if is_compress_file(file_path.as_str()) {
             //压缩文件本身发送出去
             to_global_result(file_sender.send(file_path.clone()).await)?;
-            return decompress_and_send(
-                seven_z_path,
+            return unpack_and_send(
                 file_path.clone(),
                 tx.clone(),
                 counter.clone(),
+                move |file, out| decompress_file(seven_z_path.as_str(), file, out),
             )
             .await;
         }
-        //2.普通文件: 提取文件
+        //2.mbox: 每封邮件拆成一个eml文件, 和压缩包一样放到.out文件夹
+        if email_extractor::is_mbox_file(file_path.as_str()) {
+            to_global_result(file_sender.send(file_path.clone()).await)?;
+            return unpack_and_send(
+                file_path.clone(),
+                tx.clone(),
+                counter.clone(),
+                |file, out| {
+                    email_extractor::split_mbox(file, out).map(|count| {
+                        info!("拆分mbox:{},邮件数量:{}", file, count);
+                    })
+                },
+            )
+            .await;
+        }
+        //3.普通文件: 提取文件
         //提取到的文件发送出去
         to_global_result(file_sender.send(file_path).await)?;
         //计数器减1
@@ -220,13 +235,17 @@ async fn do_extract_file(
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-19 08:48:09
-/// @Description: 解压缩文件，并发送出去
-async fn decompress_and_send(
-    seven_z_path: Arc<String>,
+/// @Description: 解压缩文件(压缩包、mbox)到.out文件夹，并发送出去
+/// @params: unpack 解压函数(文件路径, 解压路径), 在阻塞线程中执行
+async fn unpack_and_send<F>(
     file_path: String,
     tx: Arc<Sender<String>>,
     counter: Arc<AtomicUsize>,
-) -> GlobalResult<()> {
+    unpack: F,
+) -> GlobalResult<()>
+where
+    F: FnOnce(&str, &str) -> GlobalResult<()> + Send + 'static,
+{
     //解压路径
     let out_dir = format!("{}.out", file_path);
 
@@ -236,7 +255,7 @@ async fn decompress_and_send(
     let counter2 = counter.clone();
     //解压 耗时任务 交给阻塞线程池
     task::spawn_blocking(move || {
-        let r = decompress_file(seven_z_path.as_str(), file_path.as_str(), out_dir.as_str());
+        let r = unpack(file_path.as_str(), out_dir.as_str());
         match r {
             //解压成功
             Ok(()) => {
@@ -304,6 +323,27 @@ fn decrement_counter(counter: &Arc<AtomicUsize>) -> bool {
     return counter.fetch_sub(1, Ordering::SeqCst) == 1;
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 14:20:36
+/// @Description: 显示用的路径, 去掉解压文件夹的.out后缀(a.zip.out/b.txt => a.zip/b.txt)
+/// 只去掉压缩包、mbox解压时加的后缀, 其他以.out结尾的文件夹不变
+pub fn display_path(file_path: &str) -> String {
+    let mut path = String::with_capacity(file_path.len());
+    let mut start = 0;
+    while let Some(i) = file_path[start..].find(".out/") {
+        let end = start + i;
+        path.push_str(&file_path[start..end]);
+        let unpacked = &file_path[..end];
+        if !is_compress_file(unpacked) && !email_extractor::is_mbox_file(unpacked) {
+            path.push_str(".out");
+        }
+        path.push('/');
+        start = end + ".out/".len();
+    }
+    path.push_str(&file_path[start..]);
+    path
+}
+
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-02 16:22:15
 /// @Description: 删除生成的临时文件夹 .out
@@ -1,11 +1,21 @@
 use std::path::Path;
 
+pub mod doc_metadata;
+pub mod doc_sections;
+pub mod email_extractor;
+pub mod epub_extractor;
+pub mod extract_worker;
+pub mod extractor_registry;
 pub mod file_extractor;
 pub mod file_text_extractor;
+pub mod ocr;
+pub mod odf_extractor;
+pub mod rtf_extractor;
+pub mod table_records;
 
- /// @Author: DengLibin
- /// @Date: Create in 2024-12-19 12:05:04
- /// @Description: 是否压缩文件
+/// @Author: DengLibin
+/// @Date: Create in 2024-12-19 12:05:04
+/// @Description: 是否压缩文件
 pub fn is_compress_file(file_path: &str) -> bool {
     if let Some(ext) = Path::new(file_path).extension() {
         if let Some(file_ext) = ext.to_str() {
@@ -24,4 +34,4 @@ pub fn is_compress_file(file_path: &str) -> bool {
         }
     }
     false
-}
\ No newline at end of file
+}
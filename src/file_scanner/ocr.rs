@@ -0,0 +1,341 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-26 09:10:22
+//! @Description: OCR, 调用本机安装的tesseract识别图片和扫描版PDF
+//!
+//! 图片直接识别; PDF只识别文字太少的页(扫描页): 用pdftoppm转成图片再识别, 结果并入提取器的分页,
+//! 提取器没有分页时用pdftotext取文本层(页之间是\f)。
+//! 每个文件有总的超时时间, 超时杀掉进程。识别结果按文件内容缓存在数据目录下ocr_cache。
+//! 程序路径、语言、超时等在数据目录下ocr.yaml配置, 是否识别在每个索引文件夹上单独开启。
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+    sync::RwLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sys::global::{to_global_result, GlobalError, GlobalResult},
+    util::fingerprint,
+};
+
+use super::extractor_registry::MIME_PDF;
+
+//配置文件(数据目录下)
+pub const OCR_CONFIG_FILE: &str = "ocr.yaml";
+//缓存文件夹(数据目录下)
+const OCR_CACHE_DIR: &str = "ocr_cache";
+//文本层的字符数(不含空白)少于此时按扫描页识别
+const MIN_PAGE_TEXT_LEN: usize = 10;
+//检查进程是否结束的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+lazy_static! {
+    static ref OCR_CONFIG: RwLock<OcrConfig> = RwLock::new(OcrConfig::default());
+    //缓存文件夹, 没有加载配置时不缓存
+    static ref CACHE_DIR: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// OCR配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrConfig {
+    pub tesseract: String, //tesseract程序路径
+    pub pdftoppm: String,  //pdftoppm程序路径(poppler), PDF页转图片
+    pub pdftotext: String, //pdftotext程序路径(poppler), 取PDF文本层
+    pub lang: String,      //识别语言
+    pub timeout_secs: u64, //每个文件的超时时间(秒)
+    pub dpi: u32,          //PDF页转图片的分辨率
+    pub max_pages: usize,  //PDF最多识别的扫描页数
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        OcrConfig {
+            tesseract: "tesseract".into(),
+            pdftoppm: "pdftoppm".into(),
+            pdftotext: "pdftotext".into(),
+            lang: "chi_sim+eng".into(),
+            timeout_secs: 120,
+            dpi: 300,
+            max_pages: 100,
+        }
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:21:35
+/// @Description: 加载配置, 文件不存在时用默认配置
+pub fn load_config(data_dir: &str) -> GlobalResult<()> {
+    let config_file = format!("{}/{}", data_dir, OCR_CONFIG_FILE);
+    let config = if Path::new(&config_file).is_file() {
+        let content = to_global_result(fs::read_to_string(&config_file))?;
+        to_global_result(serde_yaml::from_str::<OcrConfig>(&content))?
+    } else {
+        OcrConfig::default()
+    };
+    info!("OCR配置:{:?}", config);
+    *OCR_CONFIG.write().unwrap() = config;
+    *CACHE_DIR.write().unwrap() = Some(format!("{}/{}", data_dir, OCR_CACHE_DIR));
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:26:10
+/// @Description: 配置文件路径, 不存在时按默认配置创建
+pub fn config_file(data_dir: &str) -> GlobalResult<String> {
+    let config_file = format!("{}/{}", data_dir, OCR_CONFIG_FILE);
+    if !Path::new(&config_file).is_file() {
+        let content = to_global_result(serde_yaml::to_string(&OcrConfig::default()))?;
+        to_global_result(fs::write(&config_file, content))?;
+    }
+    Ok(config_file)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:30:44
+/// @Description: 当前配置
+pub fn config() -> OcrConfig {
+    OCR_CONFIG.read().unwrap().clone()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:33:18
+/// @Description: 是否需要OCR的类型(图片、PDF)
+pub fn is_ocr_mime(mime: &str) -> bool {
+    mime.starts_with("image/") || mime == MIME_PDF
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:36:52
+/// @Description: 识别图片中的文字(阻塞)
+pub fn recognize_image_file(file_path: &str) -> GlobalResult<String> {
+    let config = config();
+    let bytes = to_global_result(fs::read(file_path))?;
+    let cache_file = cache_file(&bytes, &config, "txt");
+    if let Some(cache_file) = cache_file.as_ref() {
+        if let Ok(text) = fs::read_to_string(cache_file) {
+            return Ok(text);
+        }
+    }
+
+    let work_dir = work_dir()?;
+    let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+    let r = recognize_image(file_path, &work_dir, "image", &config, deadline);
+    let _ = fs::remove_dir_all(&work_dir);
+
+    let text = r?;
+    if let Some(cache_file) = cache_file.as_ref() {
+        to_global_result(fs::write(cache_file, &text))?;
+    }
+    Ok(text)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 09:48:27
+/// @Description: 识别PDF的扫描页(阻塞), 其他页保持原样, 有页被识别时返回true
+/// @param pages: 每页的文本(提取器的分页), 为空时用pdftotext取文本层; 识别结果替换对应的页
+/// 最多识别max_pages个扫描页, 之后的页保留原文本
+pub fn recognize_pdf_pages(file_path: &str, pages: &mut Vec<String>) -> GlobalResult<bool> {
+    let config = config();
+    let bytes = to_global_result(fs::read(file_path))?;
+    let cache_file = cache_file(&bytes, &config, "json");
+    let work_dir = work_dir()?;
+    let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+    let r = (|| -> GlobalResult<Vec<(usize, String)>> {
+        if pages.is_empty() {
+            *pages = text_layer_pages(file_path, &work_dir, &config, deadline)?;
+        }
+        if let Some(cache_file) = cache_file.as_ref() {
+            let cached = fs::read_to_string(cache_file)
+                .ok()
+                .and_then(|json| serde_json::from_str::<Vec<(usize, String)>>(&json).ok());
+            if let Some(cached) = cached {
+                return Ok(cached);
+            }
+        }
+        let scanned_pages = pages
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| is_scanned_page(page))
+            .map(|(i, _)| i + 1)
+            .take(config.max_pages)
+            .collect::<Vec<usize>>();
+        let mut recognized: Vec<(usize, String)> = vec![];
+        for page_no in scanned_pages {
+            let text = recognize_pdf_page(file_path, page_no, &work_dir, &config, deadline)?;
+            recognized.push((page_no, text));
+        }
+        if let Some(cache_file) = cache_file.as_ref() {
+            let json = to_global_result(serde_json::to_string(&recognized))?;
+            to_global_result(fs::write(cache_file, json))?;
+        }
+        Ok(recognized)
+    })();
+    let _ = fs::remove_dir_all(&work_dir);
+
+    let recognized = r?;
+    for (page_no, text) in recognized.iter() {
+        if let Some(page) = pages.get_mut(page_no - 1) {
+            *page = text.clone();
+        }
+    }
+    Ok(!recognized.is_empty())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 11:20:40
+/// @Description: pdftotext取所有页的文本层
+fn text_layer_pages(
+    file_path: &str,
+    work_dir: &str,
+    config: &OcrConfig,
+    deadline: Instant,
+) -> GlobalResult<Vec<String>> {
+    let text_file = format!("{}/layer.txt", work_dir);
+    let mut cmd = Command::new(&config.pdftotext);
+    cmd.args(["-enc", "UTF-8"]).arg(file_path).arg(&text_file);
+    run_with_deadline(cmd, deadline)?;
+    let layer = to_global_result(fs::read_to_string(&text_file))?;
+
+    let mut pages = layer
+        .split('\u{c}')
+        .map(|page| page.to_string())
+        .collect::<Vec<String>>();
+    //最后一页后面也有\f
+    if pages.len() > 1 && pages[pages.len() - 1].trim().is_empty() {
+        pages.pop();
+    }
+    Ok(pages)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 11:26:15
+/// @Description: PDF的一页转成图片再识别
+fn recognize_pdf_page(
+    file_path: &str,
+    page_no: usize,
+    work_dir: &str,
+    config: &OcrConfig,
+    deadline: Instant,
+) -> GlobalResult<String> {
+    let page_no = page_no.to_string();
+    let image_prefix = format!("{}/page", work_dir);
+    let mut cmd = Command::new(&config.pdftoppm);
+    cmd.args([
+        "-r",
+        &config.dpi.to_string(),
+        "-gray",
+        "-png",
+        "-singlefile",
+    ])
+    .args(["-f", &page_no, "-l", &page_no])
+    .arg(file_path)
+    .arg(&image_prefix);
+    run_with_deadline(cmd, deadline)?;
+    let image_file = format!("{}.png", image_prefix);
+    recognize_image(&image_file, work_dir, "page", config, deadline)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:02:13
+/// @Description: tesseract识别图片
+/// @param out_name: 输出文件名(不含扩展名), tesseract会加上.txt
+fn recognize_image(
+    image_file: &str,
+    work_dir: &str,
+    out_name: &str,
+    config: &OcrConfig,
+    deadline: Instant,
+) -> GlobalResult<String> {
+    let out_base = format!("{}/{}", work_dir, out_name);
+    let mut cmd = Command::new(&config.tesseract);
+    cmd.arg(image_file)
+        .arg(&out_base)
+        .args(["-l", &config.lang])
+        //中文字之间不加空格
+        .args(["-c", "preserve_interword_spaces=1"]);
+    run_with_deadline(cmd, deadline)?;
+    to_global_result(fs::read_to_string(format!("{}.txt", out_base)))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:10:40
+/// @Description: 执行命令, 超过截止时间时结束进程
+fn run_with_deadline(mut cmd: Command, deadline: Instant) -> GlobalResult<()> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return Err(GlobalError::new(format!("启动{}失败:{}", program, e))),
+    };
+    loop {
+        if let Some(status) = to_global_result(child.try_wait())? {
+            if status.success() {
+                return Ok(());
+            }
+            return Err(GlobalError::new(format!("{}执行失败:{}", program, status)));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(GlobalError::new(format!("{}执行超时", program)));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:21:06
+/// @Description: 是否扫描页(没有文本层)
+fn is_scanned_page(page: &str) -> bool {
+    page.chars().filter(|c| !c.is_whitespace()).count() < MIN_PAGE_TEXT_LEN
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:25:33
+/// @Description: 缓存文件(文件内容哈希_语言_分辨率_最多页数), 没有加载配置时为None
+/// @param ext: 图片的结果是txt, PDF的结果(页号, 文本)是json
+fn cache_file(bytes: &[u8], config: &OcrConfig, ext: &str) -> Option<String> {
+    let cache_dir = CACHE_DIR.read().unwrap().clone()?;
+    let hash = fingerprint::fnv1a(bytes);
+    Some(format!(
+        "{}/{:016x}_{}_{}_{}.{}",
+        cache_dir,
+        hash,
+        config.lang.replace('+', "_"),
+        config.dpi,
+        config.max_pages,
+        ext
+    ))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-26 10:30:18
+/// @Description: 临时工作目录(缓存文件夹下, 用完删除)
+fn work_dir() -> GlobalResult<String> {
+    let cache_dir = CACHE_DIR
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().display().to_string());
+    let work_dir = format!("{}/tmp_{}", cache_dir, uuid::Uuid::new_v4().simple());
+    to_global_result(fs::create_dir_all(&work_dir))?;
+    Ok(work_dir)
+}
@@ -0,0 +1,256 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-10 10:40:05
+//! @Description: OpenDocument格式(odt、ods、odp), 直接读zip中的content.xml和meta.xml, 不依赖tika
+//!
+//! odt按标题(text:h)拆分, odp按幻灯片(draw:page)拆分, ods按行建索引(和xlsx一样)。
+
+use std::fs::File;
+
+use zip::ZipArchive;
+
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::xml_utils::{self, XmlEvent},
+};
+
+use super::{
+    doc_metadata,
+    doc_sections::{self, Sheet, TextSection},
+    extractor_registry::{ExtractedText, TextExtractor, MIME_ODP, MIME_ODS},
+    table_records,
+};
+
+//标题作为位置时的最大长度(字符数)
+const MAX_HEADING_LEN: usize = 50;
+//重复的非空单元格最多展开的数量(ods用属性表示连续相同的单元格, 可能很大)
+const MAX_REPEATED_CELLS: usize = 100;
+
+/// OpenDocument格式
+pub struct OdfExtractor;
+
+impl TextExtractor for OdfExtractor {
+    fn extract(&self, file_path: &str, mime: &str) -> GlobalResult<ExtractedText> {
+        let file = to_global_result(File::open(file_path))?;
+        let mut archive = to_global_result(ZipArchive::new(file))?;
+        let content = doc_sections::read_entry(&mut archive, "content.xml");
+        let sections = match mime {
+            MIME_ODS => table_records::sheet_records(ods_sheets(&content)),
+            MIME_ODP => odp_sections(&content),
+            _ => odt_sections(&content),
+        }
+        .into_iter()
+        .filter(|section| !section.text.trim().is_empty())
+        .collect::<Vec<TextSection>>();
+        let metadata =
+            doc_metadata::odf_metadata(&doc_sections::read_entry(&mut archive, "meta.xml"));
+        Ok(ExtractedText {
+            text: doc_sections::join_sections(&sections),
+            sections,
+            metadata,
+            ..Default::default()
+        })
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 10:52:37
+/// @Description: odt按标题拆分, 第一个标题之前的内容没有位置
+pub fn odt_sections(content: &str) -> Vec<TextSection> {
+    let mut sections: Vec<TextSection> = vec![TextSection::default()];
+    let mut reader = ParagraphReader::default();
+    for event in xml_utils::events(content) {
+        let is_heading_end = matches!(event, XmlEvent::End { name: "text:h" });
+        if let Some(paragraph) = reader.read(event) {
+            let heading = paragraph.trim();
+            if is_heading_end && !heading.is_empty() {
+                let location = heading.chars().take(MAX_HEADING_LEN).collect::<String>();
+                sections.push(TextSection::new(location, ""));
+            }
+            if let Some(section) = sections.last_mut() {
+                section.text.push_str(&paragraph);
+                section.text.push('\n');
+            }
+        }
+    }
+    sections
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 11:05:14
+/// @Description: odp按幻灯片拆分(包括备注)
+pub fn odp_sections(content: &str) -> Vec<TextSection> {
+    let mut sections: Vec<TextSection> = vec![];
+    let mut reader = ParagraphReader::default();
+    for event in xml_utils::events(content) {
+        if let XmlEvent::Start {
+            name: "draw:page", ..
+        } = event
+        {
+            sections.push(TextSection::new(
+                doc_sections::slide_location(sections.len() + 1),
+                "",
+            ));
+            continue;
+        }
+        if let (Some(paragraph), Some(section)) = (reader.read(event), sections.last_mut()) {
+            section.text.push_str(&paragraph);
+            section.text.push('\n');
+        }
+    }
+    sections
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 11:16:42
+/// @Description: ods的工作表, 展开重复的行和单元格, 跳过空行
+pub fn ods_sheets(content: &str) -> Vec<Sheet> {
+    let mut sheets: Vec<Sheet> = vec![];
+    let mut reader = ParagraphReader::default();
+    let mut row: Vec<String> = vec![];
+    let mut row_no = 0;
+    let mut row_repeat = 1;
+    //还没有放进行里的空单元格数量, 后面有非空单元格时才补上
+    let mut empty_cells = 0;
+    let mut cell = String::new();
+    let mut cell_repeat = 1;
+    for event in xml_utils::events(content) {
+        match event {
+            XmlEvent::Start {
+                name: "table:table",
+                attrs,
+            } => {
+                sheets.push(Sheet {
+                    name: xml_utils::attr(attrs, "table:name").unwrap_or_default(),
+                    ..Default::default()
+                });
+                row_no = 0;
+            }
+            XmlEvent::Start {
+                name: "table:table-row",
+                attrs,
+            } => {
+                row.clear();
+                empty_cells = 0;
+                row_repeat = repeat_count(attrs, "table:number-rows-repeated");
+            }
+            XmlEvent::Empty {
+                name: "table:table-row",
+                attrs,
+            } => row_no += repeat_count(attrs, "table:number-rows-repeated"),
+            XmlEvent::End {
+                name: "table:table-row",
+            } => {
+                if let (false, Some(sheet)) = (row.is_empty(), sheets.last_mut()) {
+                    sheet.row_numbers.push(row_no + 1);
+                    sheet.rows.push(row.clone());
+                }
+                row_no += row_repeat;
+            }
+            XmlEvent::Start {
+                name: "table:table-cell" | "table:covered-table-cell",
+                attrs,
+            } => {
+                cell.clear();
+                cell_repeat = repeat_count(attrs, "table:number-columns-repeated");
+            }
+            XmlEvent::Empty {
+                name: "table:table-cell" | "table:covered-table-cell",
+                attrs,
+            } => empty_cells += repeat_count(attrs, "table:number-columns-repeated"),
+            XmlEvent::End {
+                name: "table:table-cell" | "table:covered-table-cell",
+            } => {
+                let text = cell.trim_end_matches('\n');
+                if text.trim().is_empty() {
+                    empty_cells += cell_repeat;
+                } else {
+                    row.resize(row.len() + empty_cells, String::new());
+                    empty_cells = 0;
+                    for _ in 0..cell_repeat.min(MAX_REPEATED_CELLS) {
+                        row.push(text.to_string());
+                    }
+                }
+            }
+            event => {
+                if let Some(paragraph) = reader.read(event) {
+                    cell.push_str(&paragraph);
+                    cell.push('\n');
+                }
+            }
+        }
+    }
+    sheets
+}
+
+//重复次数属性, 没有时为1
+fn repeat_count(attrs: &str, name: &str) -> usize {
+    xml_utils::attr(attrs, name)
+        .and_then(|count| count.parse::<usize>().ok())
+        .filter(|count| *count > 0)
+        .unwrap_or(1)
+}
+
+/// 读取段落(text:p、text:h), 嵌套的段落(注释、文本框)并入外层段落
+#[derive(Default)]
+struct ParagraphReader {
+    paragraph: String, //当前段落
+    depth: usize,      //段落嵌套层数
+    skip_depth: usize, //修订记录(已删除的内容)的层数
+}
+
+impl ParagraphReader {
+    /// 读一个事件, 最外层段落结束时返回段落内容
+    fn read(&mut self, event: XmlEvent) -> Option<String> {
+        match event {
+            XmlEvent::Start {
+                name: "text:tracked-changes",
+                ..
+            } => self.skip_depth += 1,
+            XmlEvent::End {
+                name: "text:tracked-changes",
+            } => self.skip_depth = self.skip_depth.saturating_sub(1),
+            _ if self.skip_depth > 0 => {}
+            XmlEvent::Start {
+                name: "text:p" | "text:h",
+                ..
+            } => {
+                if self.depth == 0 {
+                    self.paragraph.clear();
+                } else {
+                    self.paragraph.push('\n');
+                }
+                self.depth += 1;
+            }
+            XmlEvent::Empty {
+                name: "text:p" | "text:h",
+                ..
+            } if self.depth == 0 => return Some("".into()),
+            XmlEvent::End {
+                name: "text:p" | "text:h",
+            } if self.depth > 0 => {
+                self.depth -= 1;
+                if self.depth == 0 {
+                    return Some(std::mem::take(&mut self.paragraph));
+                }
+            }
+            XmlEvent::Text(text) if self.depth > 0 => self.paragraph.push_str(&text),
+            //连续空格: <text:s text:c="3"/>
+            XmlEvent::Empty {
+                name: "text:s",
+                attrs,
+            } if self.depth > 0 => {
+                let count = repeat_count(attrs, "text:c");
+                self.paragraph.push_str(&" ".repeat(count));
+            }
+            XmlEvent::Empty {
+                name: "text:tab", ..
+            } if self.depth > 0 => self.paragraph.push('\t'),
+            XmlEvent::Empty {
+                name: "text:line-break",
+                ..
+            } if self.depth > 0 => self.paragraph.push('\n'),
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,380 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-10 14:05:36
+//! @Description: RTF, 直接解析控制字, 不依赖tika
+//!
+//! 跳过字体表、颜色表、样式表、图片、二进制数据(\binN)和可忽略的目标(\*), 信息组(\info)中的标题、作者等作为元数据。
+//! \'hh按当前字体的字符集(\fcharset)或文档的代码页(\ansicpg)解码, \uN是unicode字符, 之后跳过\ucN个替代字符。
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use encoding_rs::Encoding;
+
+use crate::sys::global::{to_global_result, GlobalResult};
+
+use super::{
+    doc_metadata::{self, DocMetadata},
+    extractor_registry::{ExtractedText, TextExtractor},
+};
+
+//不输出文本的目标
+const SKIP_DESTINATIONS: [&str; 14] = [
+    "colortbl",
+    "stylesheet",
+    "pict",
+    "object",
+    "objdata",
+    "fldinst",
+    "listtable",
+    "listoverridetable",
+    "revtbl",
+    "rsidtbl",
+    "xmlnstbl",
+    "themedata",
+    "datastore",
+    "latentstyles",
+];
+
+/// RTF
+pub struct RtfExtractor;
+
+impl TextExtractor for RtfExtractor {
+    fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+        let bytes = to_global_result(std::fs::read(file_path))?;
+        let (text, metadata) = rtf_text(&bytes);
+        Ok(ExtractedText {
+            text,
+            metadata,
+            ..Default::default()
+        })
+    }
+}
+
+/// 文本输出到哪里
+#[derive(Clone, Copy, PartialEq)]
+enum Destination {
+    Text,                    //正文
+    Skip,                    //跳过
+    FontTable,               //字体表
+    Info,                    //信息组
+    InfoField(&'static str), //信息组中的字段(title、author等)
+    InfoTime(&'static str),  //信息组中的时间(creatim、revtim)
+}
+
+/// 组的状态, 进入组时复制, 离开组时恢复
+#[derive(Clone, Copy)]
+struct GroupState {
+    destination: Destination,
+    codepage: u32, //当前字体的代码页, 0为文档的代码页
+    uc: usize,     //\uN之后跳过的替代字符数
+}
+
+/// RTF解析
+struct RtfParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    state: GroupState,
+    stack: Vec<GroupState>,
+    ansi_codepage: u32,                     //文档的代码页(\ansicpg)
+    font_codepages: HashMap<i32, u32>,      //字体号 -> 代码页
+    defining_font: i32,                     //字体表中正在定义的字体
+    pending: Vec<u8>,                       //待解码的字节(多字节字符的\'hh连续出现)
+    skip_chars: usize,                      //\uN之后还要跳过的替代字符数
+    text: String,                           //正文
+    fields: HashMap<&'static str, String>,  //信息组中的字段
+    times: HashMap<&'static str, [i32; 5]>, //信息组中的时间: 年月日时分
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 14:20:51
+/// @Description: RTF的文本和元数据
+pub fn rtf_text(bytes: &[u8]) -> (String, DocMetadata) {
+    let mut parser = RtfParser {
+        bytes,
+        pos: 0,
+        state: GroupState {
+            destination: Destination::Text,
+            codepage: 0,
+            uc: 1,
+        },
+        stack: vec![],
+        ansi_codepage: 1252,
+        font_codepages: HashMap::new(),
+        defining_font: 0,
+        pending: vec![],
+        skip_chars: 0,
+        text: String::new(),
+        fields: HashMap::new(),
+        times: HashMap::new(),
+    };
+    parser.parse();
+    let field = |name: &str| parser.fields.get(name).map(|v| v.trim().to_string());
+    let time = |name: &str| {
+        parser
+            .times
+            .get(name)
+            .and_then(|[year, month, day, hour, minute]| {
+                NaiveDate::from_ymd_opt(*year, (*month).max(1) as u32, (*day).max(1) as u32)?
+                    .and_hms_opt(*hour as u32, *minute as u32, 0)
+            })
+            .map(doc_metadata::local_millis)
+            .unwrap_or(0)
+    };
+    let metadata = DocMetadata {
+        title: field("title").unwrap_or_default(),
+        author: field("author").unwrap_or_default(),
+        subject: field("subject").unwrap_or_default(),
+        keywords: field("keywords").unwrap_or_default(),
+        created_time: time("creatim"),
+        modified_time: time("revtim"),
+        ..Default::default()
+    };
+    (parser.text, metadata)
+}
+
+impl RtfParser<'_> {
+    fn parse(&mut self) {
+        while self.pos < self.bytes.len() {
+            let b = self.bytes[self.pos];
+            self.pos += 1;
+            match b {
+                b'{' => {
+                    self.flush();
+                    self.stack.push(self.state);
+                }
+                b'}' => {
+                    self.flush();
+                    self.skip_chars = 0;
+                    if let Some(state) = self.stack.pop() {
+                        self.state = state;
+                    }
+                }
+                b'\\' => self.control(),
+                b'\r' | b'\n' => {}
+                _ => {
+                    if !self.skip_char() {
+                        self.pending.push(b);
+                    }
+                }
+            }
+        }
+        self.flush();
+    }
+
+    /// 控制字或控制符号, pos在反斜杠之后
+    fn control(&mut self) {
+        let Some(&b) = self.bytes.get(self.pos) else {
+            return;
+        };
+        self.pos += 1;
+        match b {
+            b'\'' => {
+                let hex = self.bytes.get(self.pos..self.pos + 2).unwrap_or_default();
+                self.pos += hex.len();
+                let byte = std::str::from_utf8(hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let (Some(byte), false) = (byte, self.skip_char()) {
+                    self.pending.push(byte);
+                }
+            }
+            b'*' => self.state.destination = Destination::Skip,
+            b'\\' | b'{' | b'}' if !self.skip_char() => self.pending.push(b),
+            b'~' => self.push_char(' '),
+            b'_' => self.push_char('-'),
+            b'\r' | b'\n' => self.push_char('\n'),
+            b'a'..=b'z' | b'A'..=b'Z' => {
+                let start = self.pos - 1;
+                while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_alphabetic() {
+                    self.pos += 1;
+                }
+                let word = String::from_utf8_lossy(&self.bytes[start..self.pos]).to_string();
+                let param_start = self.pos;
+                if self.bytes.get(self.pos) == Some(&b'-') {
+                    self.pos += 1;
+                }
+                while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                let param = std::str::from_utf8(&self.bytes[param_start..self.pos])
+                    .ok()
+                    .and_then(|param| param.parse::<i32>().ok());
+                //控制字后面的一个空格是分隔符
+                if self.bytes.get(self.pos) == Some(&b' ') {
+                    self.pos += 1;
+                }
+                //\binN之后是N个字节的二进制数据, 不按RTF解析
+                if word == "bin" {
+                    let len = param.unwrap_or(0).max(0) as usize;
+                    self.pos = (self.pos + len).min(self.bytes.len());
+                    return;
+                }
+                self.control_word(&word, param);
+            }
+            //其他控制符号(\-可选连字符等)忽略
+            _ => {}
+        }
+    }
+
+    fn control_word(&mut self, word: &str, param: Option<i32>) {
+        //\uN的替代字符可以是控制字
+        if word != "u" && self.skip_char() {
+            return;
+        }
+        match word {
+            "ansicpg" => self.ansi_codepage = param.unwrap_or(1252) as u32,
+            "uc" => self.state.uc = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(param) = param {
+                    let code = if param < 0 { param + 65536 } else { param };
+                    if let Some(c) = char::from_u32(code as u32) {
+                        self.push_char(c);
+                    }
+                    self.skip_chars = self.state.uc;
+                }
+            }
+            "fonttbl" => self.state.destination = Destination::FontTable,
+            "f" => {
+                let font = param.unwrap_or(0);
+                if self.state.destination == Destination::FontTable {
+                    self.defining_font = font;
+                } else {
+                    self.flush();
+                    self.state.codepage = self.font_codepages.get(&font).copied().unwrap_or(0);
+                }
+            }
+            "fcharset" => {
+                if let Some(codepage) = charset_codepage(param.unwrap_or(0)) {
+                    self.font_codepages.insert(self.defining_font, codepage);
+                }
+            }
+            "cpg" if self.state.destination == Destination::FontTable => {
+                self.font_codepages
+                    .insert(self.defining_font, param.unwrap_or(0) as u32);
+            }
+            "info" => self.state.destination = Destination::Info,
+            "title" | "author" | "subject" | "keywords"
+                if self.state.destination == Destination::Info =>
+            {
+                let field = match word {
+                    "title" => "title",
+                    "author" => "author",
+                    "subject" => "subject",
+                    _ => "keywords",
+                };
+                self.state.destination = Destination::InfoField(field);
+            }
+            "creatim" | "revtim" if self.state.destination == Destination::Info => {
+                let field = match word {
+                    "creatim" => "creatim",
+                    _ => "revtim",
+                };
+                self.state.destination = Destination::InfoTime(field);
+            }
+            "yr" | "mo" | "dy" | "hr" | "min" => {
+                if let Destination::InfoTime(field) = self.state.destination {
+                    let index = ["yr", "mo", "dy", "hr", "min"]
+                        .iter()
+                        .position(|w| *w == word)
+                        .unwrap_or(0);
+                    self.times.entry(field).or_default()[index] = param.unwrap_or(0);
+                }
+            }
+            "par" | "line" | "sect" | "page" | "row" => self.push_char('\n'),
+            "tab" | "cell" => self.push_char('\t'),
+            "emdash" => self.push_char('—'),
+            "endash" => self.push_char('–'),
+            "bullet" => self.push_char('•'),
+            "lquote" => self.push_char('‘'),
+            "rquote" => self.push_char('’'),
+            "ldblquote" => self.push_char('“'),
+            "rdblquote" => self.push_char('”'),
+            _ if SKIP_DESTINATIONS.contains(&word) => self.state.destination = Destination::Skip,
+            _ => {}
+        }
+    }
+
+    /// \uN之后的替代字符, 需要跳过时返回true
+    fn skip_char(&mut self) -> bool {
+        if self.skip_chars > 0 {
+            self.skip_chars -= 1;
+            return true;
+        }
+        false
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.skip_char() {
+            return;
+        }
+        self.flush();
+        self.output(&c.to_string());
+    }
+
+    /// 按当前代码页解码待解码的字节
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let codepage = match self.state.codepage {
+            0 => self.ansi_codepage,
+            codepage => codepage,
+        };
+        let (text, _, _) = codepage_encoding(codepage).decode(&self.pending);
+        let text = text.to_string();
+        self.pending.clear();
+        self.output(&text);
+    }
+
+    fn output(&mut self, text: &str) {
+        match self.state.destination {
+            Destination::Text => self.text.push_str(text),
+            Destination::InfoField(field) => self.fields.entry(field).or_default().push_str(text),
+            _ => {}
+        }
+    }
+}
+
+//字体字符集(\fcharset)对应的代码页, 不认识的用文档的代码页
+fn charset_codepage(charset: i32) -> Option<u32> {
+    let codepage = match charset {
+        0 => 1252,
+        128 => 932,
+        129 => 949,
+        134 => 936,
+        136 => 950,
+        161 => 1253,
+        162 => 1254,
+        163 => 1258,
+        177 => 1255,
+        178 => 1256,
+        186 => 1257,
+        204 => 1251,
+        222 => 874,
+        238 => 1250,
+        _ => return None,
+    };
+    Some(codepage)
+}
+
+//代码页对应的编码, 不认识的按windows-1252
+fn codepage_encoding(codepage: u32) -> &'static Encoding {
+    match codepage {
+        874 => encoding_rs::WINDOWS_874,
+        932 => encoding_rs::SHIFT_JIS,
+        936 => encoding_rs::GBK,
+        949 => encoding_rs::EUC_KR,
+        950 => encoding_rs::BIG5,
+        1250 => encoding_rs::WINDOWS_1250,
+        1251 => encoding_rs::WINDOWS_1251,
+        1253 => encoding_rs::WINDOWS_1253,
+        1254 => encoding_rs::WINDOWS_1254,
+        1255 => encoding_rs::WINDOWS_1255,
+        1256 => encoding_rs::WINDOWS_1256,
+        1257 => encoding_rs::WINDOWS_1257,
+        1258 => encoding_rs::WINDOWS_1258,
+        10000 => encoding_rs::MACINTOSH,
+        65001 => encoding_rs::UTF_8,
+        _ => encoding_rs::WINDOWS_1252,
+    }
+}
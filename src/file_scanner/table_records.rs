@@ -0,0 +1,146 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-05 09:10:42
+//! @Description: 表格(CSV、TSV、xlsx)按行建索引
+//!
+//! 第一个非空行作为表头, 之后每一行是一条记录, 内容是"列名: 值"(空值跳过), 位置是行号(xlsx带工作表名)。
+//! 搜索结果按表头展示命中的行。
+
+use crate::sys::global::GlobalResult;
+
+use super::doc_sections::{self, Sheet, TextSection};
+
+//记录中列之间的分隔
+const FIELD_SEPARATOR: &str = "; ";
+//识别分隔符时看的行数
+const SNIFF_LINES: usize = 10;
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 09:18:05
+/// @Description: 行的位置
+pub fn row_location(row_no: usize) -> String {
+    format!("第{}行", row_no)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 09:20:37
+/// @Description: 按表头展示一行: 列名: 值; 列名: 值, 空值跳过, 没有列名时用第几列
+pub fn record_text(headers: &[String], row: &[String]) -> String {
+    row.iter()
+        .enumerate()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(i, value)| {
+            let header = headers
+                .get(i)
+                .map(|header| header.trim())
+                .filter(|header| !header.is_empty())
+                .map(|header| header.to_string())
+                .unwrap_or_else(|| format!("第{}列", i + 1));
+            format!("{}: {}", header, value.trim())
+        })
+        .collect::<Vec<String>>()
+        .join(FIELD_SEPARATOR)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 09:31:14
+/// @Description: 表格的记录, rows: (行号, 单元格), 第一个非空行是表头; 没有数据行时为空
+/// location_prefix: 位置的前缀(工作表名), 为空时位置只有行号
+pub fn table_records(location_prefix: &str, rows: Vec<(usize, Vec<String>)>) -> Vec<TextSection> {
+    let mut rows = rows
+        .into_iter()
+        .filter(|(_, row)| row.iter().any(|cell| !cell.trim().is_empty()));
+    let headers = match rows.next() {
+        Some((_, headers)) => headers,
+        None => return vec![],
+    };
+    rows.map(|(row_no, row)| {
+        let location = match location_prefix.is_empty() {
+            true => row_location(row_no),
+            false => format!("{} {}", location_prefix, row_location(row_no)),
+        };
+        TextSection::record(location, record_text(&headers, &row))
+    })
+    .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 09:45:52
+/// @Description: 识别分隔符: tsv用制表符, 其他取前几行中每行出现次数最稳定的(逗号、分号、制表符、竖线), 默认逗号
+pub fn sniff_delimiter(text: &str, ext: &str) -> u8 {
+    if ext == "tsv" {
+        return b'\t';
+    }
+    let lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect::<Vec<&str>>();
+    let mut best = (b',', 0);
+    for delimiter in [b',', b';', b'\t', b'|'] {
+        let counts = lines
+            .iter()
+            .map(|line| line.bytes().filter(|b| *b == delimiter).count())
+            .collect::<Vec<usize>>();
+        //每行都出现才算, 取最少的次数
+        let min = counts.iter().copied().min().unwrap_or(0);
+        if min > best.1 {
+            best = (delimiter, min);
+        }
+    }
+    best.0
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 10:02:19
+/// @Description: CSV文本的记录, 行号是记录在文件中开始的行(从1开始, 引号中有换行时一条记录占多行), 每行列数可以不同
+pub fn csv_records(text: &str, delimiter: u8) -> Vec<TextSection> {
+    let text = text.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+    let mut rows: Vec<(usize, Vec<String>)> = vec![];
+    //记录的位置从前面的空行开始, 自己数行号
+    let mut line = 1;
+    let mut offset = 0;
+    for record in reader.records().map_while(Result::ok) {
+        let start = record
+            .position()
+            .map(|p| p.byte() as usize)
+            .filter(|start| *start >= offset && text.is_char_boundary(*start))
+            .unwrap_or(offset);
+        let rest = &text[start..];
+        let start = start + rest.len() - rest.trim_start_matches(['\r', '\n']).len();
+        line += text[offset..start].matches('\n').count();
+        offset = start;
+        let row = record.iter().map(|cell| cell.to_string()).collect();
+        rows.push((line, row));
+    }
+    table_records("", rows)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-05 10:15:40
+/// @Description: xlsx所有工作表的记录
+pub fn xlsx_records(file_path: &str) -> GlobalResult<Vec<TextSection>> {
+    let sheets = doc_sections::xlsx_sheets(file_path)?;
+    Ok(sheet_records(sheets))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-10 10:32:16
+/// @Description: 工作表(xlsx、ods)的记录, 位置: 工作表名 第N行; 只有表头的工作表整个作为一个单元
+pub fn sheet_records(sheets: Vec<Sheet>) -> Vec<TextSection> {
+    let mut records: Vec<TextSection> = vec![];
+    for sheet in sheets {
+        let text = doc_sections::sheet_text(&sheet);
+        let rows = sheet.row_numbers.into_iter().zip(sheet.rows).collect();
+        let mut sheet_records = table_records(&sheet.name, rows);
+        if sheet_records.is_empty() && !text.trim().is_empty() {
+            sheet_records.push(TextSection::new(sheet.name, text));
+        }
+        records.append(&mut sheet_records);
+    }
+    records
+}
@@ -6,7 +6,9 @@
 #![windows_subsystem = "windows"]
 
 use rust_my_doc_search::{
-    sys::global::{to_global_result, GlobalResult},
+    app,
+    file_scanner::extract_worker,
+    sys::global::{to_global_result, GlobalError, GlobalResult},
     ui::main_win::show_win,
 };
 use tokio::runtime::Builder;
@@ -24,14 +26,46 @@ fn cre_tokio_runtime(max_blocking_treads: usize) -> tokio::runtime::Runtime {
 }
 
 fn start_ui() -> GlobalResult<()> {
+    //文本在子进程(程序自己)中提取
+    match std::env::current_exe() {
+        Ok(exe) => extract_worker::set_worker_program(&exe.display().to_string()),
+        Err(e) => log::error!("获取程序路径失败, 在当前进程提取文本:{}", e),
+    }
     // 创建一个 Tokio 运行时，配置线程池的大小
     let runtime = cre_tokio_runtime(16);
     let r = show_win(runtime);
     to_global_result(r)
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-20 11:02:17
+/// @Description: 命令行参数: --workspace <名称> 指定本次运行使用的工作区
+fn parse_args() -> GlobalResult<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let mut i = 1;
+    while i < args.len() {
+        if args[i] == "--workspace" || args[i] == "-w" {
+            match args.get(i + 1) {
+                Some(workspace) => app::use_workspace(workspace)?,
+                None => return Err(GlobalError::new(format!("{} 缺少工作区名称", args[i]))),
+            }
+            i += 1;
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
 fn main() -> GlobalResult<()> {
     // async_run_web()
-
+    //提取文本的子进程: --extract-worker <数据目录>
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(extract_worker::WORKER_ARG) {
+        let data_dir = args.get(2).map(String::as_str).unwrap_or("");
+        return extract_worker::run_worker(data_dir);
+    }
+    parse_args()?;
+    //工作区目录不可用时不启动, 避免读写其他工作区的数据
+    app::get_data_dir()?;
     start_ui()
 }
@@ -1,2 +1,9 @@
+pub mod tantivy_explain;
+pub mod tantivy_facet;
 pub mod tantivy_jieba;
-pub mod tantivy_search;
\ No newline at end of file
+pub mod tantivy_metadata;
+pub mod tantivy_ngram;
+pub mod tantivy_regex;
+pub mod tantivy_search;
+pub mod tantivy_spell;
+pub mod tantivy_synonym;
@@ -0,0 +1,112 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-21 09:15:08
+//! @Description: 查询调试: 分词结果、解析后的查询树、命中文档的打分解释(Explanation)
+//!
+//! 用于调整词典、同义词、权重时查看结果为什么这样排序。
+
+use tantivy::{
+    collector::TopDocs,
+    query::{BooleanQuery, Occur, Query, TermQuery},
+    schema::IndexRecordOption,
+    Index, Term,
+};
+
+use crate::sys::global::{to_global_result, GlobalResult};
+
+use super::{
+    tantivy_jieba,
+    tantivy_search::{self, SearchOptions, FIELD_FILE_PATH},
+};
+
+/// 查询调试信息
+#[derive(Debug, Default)]
+pub struct QueryDebug {
+    pub tokens: Vec<String>, //jieba分词结果(含空白)
+    pub query_str: String,   //查询语句(同义词扩展后的)
+    pub query_tree: String,  //解析后的查询树(含筛选条件)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-21 09:22:47
+/// @Description: 查询调试信息
+/// @param text: 用户输入的原始文本
+/// @param query_str: 查询语句(jieba分词后的)
+/// @param exact_substring: 是否精确子串搜索(查询树为子串查询)
+pub fn debug_query(
+    index_obj: &Index,
+    text: &str,
+    query_str: &str,
+    exact_substring: bool,
+    options: &SearchOptions,
+) -> GlobalResult<QueryDebug> {
+    let query_tree = match build_query(index_obj, text, query_str, exact_substring, options)? {
+        Some(query) => format!("{:#?}", query),
+        None => "(空查询)".into(),
+    };
+    Ok(QueryDebug {
+        tokens: tantivy_jieba::tokenize(text),
+        query_str: query_str.to_string(),
+        query_tree,
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-21 09:40:33
+/// @Description: 文件的打分解释(JSON), 取该文件得分最高的内容片段, 文件没有命中时返回None
+/// @param file_path: 命中的文件路径
+pub fn explain_file(
+    index_obj: &Index,
+    text: &str,
+    query_str: &str,
+    exact_substring: bool,
+    options: &SearchOptions,
+    file_path: &str,
+) -> GlobalResult<Option<String>> {
+    let query = match build_query(index_obj, text, query_str, exact_substring, options)? {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+    let schema = index_obj.schema();
+    let path = to_global_result(schema.get_field(FIELD_FILE_PATH))?;
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    let path_query = TermQuery::new(
+        Term::from_field_text(path, file_path),
+        IndexRecordOption::Basic,
+    );
+    let file_query = BooleanQuery::new(vec![
+        (Occur::Must, query.box_clone()),
+        (Occur::Must, Box::new(path_query)),
+    ]);
+    let top_docs = to_global_result(searcher.search(&file_query, &TopDocs::with_limit(1)))?;
+    match top_docs.first() {
+        Some((_score, doc_address)) => {
+            let explanation = to_global_result(query.explain(&searcher, *doc_address))?;
+            Ok(Some(explanation.to_pretty_json()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-21 09:31:19
+/// @Description: 和搜索一致的查询(含筛选条件), 子串搜索没有可匹配的段时返回None
+fn build_query(
+    index_obj: &Index,
+    text: &str,
+    query_str: &str,
+    exact_substring: bool,
+    options: &SearchOptions,
+) -> GlobalResult<Option<Box<dyn Query>>> {
+    let query = if exact_substring {
+        match tantivy_search::substring_query(index_obj, text)? {
+            Some(query) => query,
+            None => return Ok(None),
+        }
+    } else {
+        tantivy_search::parse_query(index_obj, query_str)?
+    };
+    let schema = index_obj.schema();
+    Ok(Some(tantivy_search::filter_query(&schema, query, options)?))
+}
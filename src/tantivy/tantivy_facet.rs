@@ -0,0 +1,210 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-20 09:10:42
+//! @Description: 分面统计(文件类型、来源文件夹、修改年月、大小区间)
+//!
+//! 分面的值在建索引时算好存到快速字段，统计时按文件去重(一个文件有多个内容片段)。
+//! 统计某个分面时不带该分面自身的筛选条件，这样同一分面可以多选。
+
+use std::collections::{HashMap, HashSet};
+
+use tantivy::{collector::DocSetCollector, query::Query, Index, SegmentReader};
+
+use crate::sys::global::{to_global_result, GlobalResult};
+
+use super::tantivy_search::{
+    self, SearchOptions, FIELD_FILE_PATH, FIELD_FILE_TYPE, FIELD_INDEX_DIR_ID,
+    FIELD_MODIFIED_MONTH, FIELD_SIZE_BUCKET,
+};
+
+//大小区间(上限, 不含), 按顺序
+const SIZE_BUCKETS: [(u64, &str); 5] = [
+    (100 * 1024, "<100KB"),
+    (1024 * 1024, "100KB-1MB"),
+    (10 * 1024 * 1024, "1MB-10MB"),
+    (100 * 1024 * 1024, "10MB-100MB"),
+    (u64::MAX, ">100MB"),
+];
+
+/// 分面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FacetKind {
+    FileType,      //文件类型
+    SourceDir,     //来源文件夹(索引目录)
+    ModifiedMonth, //修改年月
+    SizeBucket,    //大小区间
+}
+
+impl FacetKind {
+    pub const ALL: [FacetKind; 4] = [
+        FacetKind::FileType,
+        FacetKind::SourceDir,
+        FacetKind::ModifiedMonth,
+        FacetKind::SizeBucket,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FacetKind::FileType => "文件类型",
+            FacetKind::SourceDir => "文件夹",
+            FacetKind::ModifiedMonth => "修改时间",
+            FacetKind::SizeBucket => "大小",
+        }
+    }
+
+    /// 快速字段名
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            FacetKind::FileType => FIELD_FILE_TYPE,
+            FacetKind::SourceDir => FIELD_INDEX_DIR_ID,
+            FacetKind::ModifiedMonth => FIELD_MODIFIED_MONTH,
+            FacetKind::SizeBucket => FIELD_SIZE_BUCKET,
+        }
+    }
+}
+
+/// 一个分面的统计结果
+#[derive(Debug)]
+pub struct FacetGroup {
+    pub kind: FacetKind,
+    pub counts: Vec<(String, u64)>, //(值, 命中文件数), 来源文件夹的值为索引目录id
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 09:25:16
+/// @Description: 文件类型(小写扩展名), 没有扩展名时为空
+pub fn file_type(file_name: &str) -> String {
+    match file_name.rfind('.') {
+        Some(i) if i > 0 => file_name[i + 1..].to_lowercase(),
+        _ => "".into(),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 09:28:03
+/// @Description: 修改年月(本地时间, 如 2025-02), 时间未知时为空
+pub fn modified_month(millis: i64) -> String {
+    if millis == 0 {
+        return "".into();
+    }
+    match chrono::DateTime::from_timestamp_millis(millis) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m")
+            .to_string(),
+        None => "".into(),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 09:31:47
+/// @Description: 大小区间
+pub fn size_bucket(file_size: u64) -> &'static str {
+    SIZE_BUCKETS
+        .iter()
+        .find(|(max, _)| file_size < *max)
+        .map(|(_, label)| *label)
+        .unwrap_or(SIZE_BUCKETS[SIZE_BUCKETS.len() - 1].1)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 09:40:20
+/// @Description: 统计当前查询各分面的命中文件数
+/// @param query: 查询(不含筛选条件), 每个分面统计时重新加上其他分面的筛选条件
+pub fn search_facets(
+    index_obj: &Index,
+    query: &dyn Query,
+    options: &SearchOptions,
+) -> GlobalResult<Vec<FacetGroup>> {
+    let schema = index_obj.schema();
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    let mut groups: Vec<FacetGroup> = vec![];
+    for kind in FacetKind::ALL {
+        //旧索引没有该快速字段
+        let is_fast = match schema.get_field(kind.field_name()) {
+            Ok(field) => schema.get_field_entry(field).is_fast(),
+            Err(_) => false,
+        };
+        if !is_fast {
+            continue;
+        }
+        let mut kind_options = options.clone();
+        match kind {
+            FacetKind::SourceDir => kind_options.index_dir_ids.clear(),
+            _ => kind_options.facet_filters.retain(|(k, _)| *k != kind),
+        }
+        let filtered = tantivy_search::filter_query(&schema, query.box_clone(), &kind_options)?;
+        let doc_addresses = to_global_result(searcher.search(&filtered, &DocSetCollector))?;
+
+        //按段分组, 每段只取一次快速字段
+        let mut segment_docs: HashMap<u32, Vec<u32>> = HashMap::new();
+        for doc_address in doc_addresses {
+            segment_docs
+                .entry(doc_address.segment_ord)
+                .or_default()
+                .push(doc_address.doc_id);
+        }
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        let mut seen_paths: HashSet<String> = HashSet::new();
+        for (segment_ord, docs) in segment_docs {
+            let segment_reader = searcher.segment_reader(segment_ord);
+            let path_of = tantivy_search::str_column(segment_reader, FIELD_FILE_PATH);
+            let value_of = facet_values(segment_reader, kind);
+            for doc in docs {
+                if !seen_paths.insert(path_of(doc)) {
+                    continue;
+                }
+                let value = value_of(doc);
+                if !value.is_empty() {
+                    *counts.entry(value).or_default() += 1;
+                }
+            }
+        }
+        groups.push(FacetGroup {
+            kind,
+            counts: sort_counts(kind, counts),
+        });
+    }
+    Ok(groups)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 10:12:35
+/// @Description: 分面值的取值函数
+fn facet_values(segment_reader: &SegmentReader, kind: FacetKind) -> Box<dyn Fn(u32) -> String> {
+    match kind {
+        FacetKind::SourceDir => {
+            let column = segment_reader.fast_fields().i64(FIELD_INDEX_DIR_ID).ok();
+            Box::new(move |doc| {
+                column
+                    .as_ref()
+                    .and_then(|column| column.first(doc))
+                    .map(|id| id.to_string())
+                    .unwrap_or_default()
+            })
+        }
+        _ => Box::new(tantivy_search::str_column(
+            segment_reader,
+            kind.field_name(),
+        )),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 10:20:44
+/// @Description: 排序: 年月从新到旧, 大小按区间顺序, 其他按数量从多到少
+fn sort_counts(kind: FacetKind, counts: HashMap<String, u64>) -> Vec<(String, u64)> {
+    let mut counts = counts.into_iter().collect::<Vec<(String, u64)>>();
+    match kind {
+        FacetKind::ModifiedMonth => counts.sort_by(|a, b| b.0.cmp(&a.0)),
+        FacetKind::SizeBucket => counts.sort_by_key(|(value, _)| {
+            SIZE_BUCKETS
+                .iter()
+                .position(|(_, label)| label == value)
+                .unwrap_or(SIZE_BUCKETS.len())
+        }),
+        _ => counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0))),
+    }
+    counts
+}
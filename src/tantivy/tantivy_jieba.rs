@@ -6,13 +6,32 @@
 //! It implements a [`JiebaTokenizer`](./struct.JiebaTokenizer.html) for the purpose.
 #![forbid(unsafe_code)]
 
+use std::{collections::HashSet, fs, path::Path, sync::RwLock};
+
 use lazy_static::lazy_static;
+use log::{error, info};
 use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
 
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::fingerprint,
+};
+
 lazy_static! {
-    static ref JIEBA: jieba_rs::Jieba = jieba_rs::Jieba::new();
+    static ref JIEBA: RwLock<jieba_rs::Jieba> = RwLock::new(jieba_rs::Jieba::new());
+    //停用词
+    static ref STOP_WORDS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
 }
 
+//用户词典目录(数据目录下)
+const USER_DICT_DIR: &str = "dict";
+//停用词文件(数据目录下)
+const STOP_WORDS_FILE: &str = "stop_words.txt";
+//词典签名文件(数据目录下)，记录建索引时使用的词典
+const DICT_SIGNATURE_FILE: &str = "dict.sig";
+//索引版本，分析器处理逻辑或索引字段变更时加1，使已有索引需要重建
+const INDEX_VERSION: u32 = 12;
+
 /// Tokenize the text using jieba_rs.
 ///
 /// Need to load dict on first tokenization.
@@ -74,15 +93,14 @@ impl Tokenizer for JiebaTokenizer {
     fn token_stream(&mut self, text: &str) -> JiebaTokenStream {
         let mut indices = text.char_indices().collect::<Vec<_>>();
         indices.push((text.len(), '\0'));
-        let orig_tokens = JIEBA.tokenize(text, jieba_rs::TokenizeMode::Search, true);
         let mut tokens = Vec::new();
-        for token in orig_tokens {
+        for (start, end) in jieba_spans(text) {
             tokens.push(Token {
-                offset_from: indices[token.start].0,
-                offset_to: indices[token.end].0,
-                position: token.start,
-                text: String::from(&text[(indices[token.start].0)..(indices[token.end].0)]),
-                position_length: token.end - token.start,
+                offset_from: indices[start].0,
+                offset_to: indices[end].0,
+                position: start,
+                text: String::from(&text[(indices[start].0)..(indices[end].0)]),
+                position_length: end - start,
             });
         }
         JiebaTokenStream { tokens, index: 0 }
@@ -96,19 +114,185 @@ pub fn tokenize(text: &str) -> Vec<String> {
     if text.is_empty() {
         return vec![];
     }
-    let orig_tokens = JIEBA.tokenize(text, jieba_rs::TokenizeMode::Search, true);
     let mut tokens = Vec::new();
     let mut indices = text.char_indices().collect::<Vec<_>>();
     indices.push((text.len(), '\0'));
-    for token in orig_tokens {
-        tokens.push(String::from(
-            &text[(indices[token.start].0)..(indices[token.end].0)],
-        ));
+    for (start, end) in jieba_spans(text) {
+        tokens.push(String::from(&text[(indices[start].0)..(indices[end].0)]));
     }
 
     tokens
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 11:15:40
+/// @Description: jieba分词, 返回词的字符位置(开始, 结束)
+/// jieba会把带重音的拉丁文单词拆开(Café => Caf, é), 相邻的拉丁文片段合并回一个词; 去掉停用词
+fn jieba_spans(text: &str) -> Vec<(usize, usize)> {
+    let jieba = JIEBA.read().unwrap();
+    let stop_words = STOP_WORDS.read().unwrap();
+    let orig_tokens = jieba.tokenize(text, jieba_rs::TokenizeMode::Search, true);
+
+    let mut spans: Vec<(usize, usize, bool)> = Vec::new(); //(开始, 结束, 是否拉丁文)
+    for token in orig_tokens {
+        let latin = is_latin_word(token.word);
+        if let Some(last) = spans.last_mut() {
+            if latin && last.2 && last.1 == token.start {
+                last.1 = token.end;
+                continue;
+            }
+        }
+        spans.push((token.start, token.end, latin));
+    }
+
+    let chars = text.chars().collect::<Vec<char>>();
+    spans
+        .into_iter()
+        .filter(|(start, end, _)| {
+            //停用词不区分大小写
+            let word = chars[*start..*end]
+                .iter()
+                .collect::<String>()
+                .to_lowercase();
+            !stop_words.contains(&word)
+        })
+        .map(|(start, end, _)| (start, end))
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 11:02:19
+/// @Description: 是否拉丁文等非中日韩文字组成的词
+pub fn is_latin_word(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_alphanumeric() && !is_cjk_char(c))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 11:03:52
+/// @Description: 是否中日韩字符
+pub fn is_cjk_char(c: char) -> bool {
+    matches!(c,
+        '\u{4E00}'..='\u{9FFF}'     //中日韩统一表意文字
+        | '\u{3400}'..='\u{4DBF}'   //扩展A
+        | '\u{20000}'..='\u{2FA1F}' //扩展B-F及兼容补充
+        | '\u{F900}'..='\u{FAFF}'   //兼容表意文字
+        | '\u{3040}'..='\u{30FF}'   //平假名 片假名
+        | '\u{AC00}'..='\u{D7AF}'   //韩文音节
+        | '\u{1100}'..='\u{11FF}'   //韩文字母
+    )
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-06 10:12:31
+/// @Description: 加载用户词典和停用词
+/// 用户词典: 数据目录下dict文件夹中的*.txt, 每行: 词 [词频] [词性]
+/// 停用词: 数据目录下stop_words.txt, 每行一个
+pub fn load_user_dict(data_dir: &str) -> GlobalResult<()> {
+    let mut jieba = jieba_rs::Jieba::new();
+    for dict_file in user_dict_files(data_dir)? {
+        let content = to_global_result(fs::read_to_string(&dict_file))?;
+        let mut count = 0;
+        for line in content.lines() {
+            let mut iter = line.split_whitespace();
+            if let Some(word) = iter.next() {
+                let freq = iter.next().and_then(|x| x.parse::<usize>().ok());
+                let tag = iter.next();
+                jieba.add_word(word, freq, tag);
+                count += 1;
+            }
+        }
+        info!("加载用户词典:{},词数量:{}", dict_file, count);
+    }
+
+    let mut stop_words = HashSet::new();
+    let stop_words_file = format!("{}/{}", data_dir, STOP_WORDS_FILE);
+    if Path::new(&stop_words_file).is_file() {
+        let content = to_global_result(fs::read_to_string(&stop_words_file))?;
+        for line in content.lines() {
+            let word = line.trim();
+            if !word.is_empty() {
+                stop_words.insert(word.to_lowercase());
+            }
+        }
+        info!("加载停用词数量:{}", stop_words.len());
+    }
+
+    *JIEBA.write().unwrap() = jieba;
+    *STOP_WORDS.write().unwrap() = stop_words;
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-06 10:30:08
+/// @Description: 用户词典文件列表(按文件名排序)
+fn user_dict_files(data_dir: &str) -> GlobalResult<Vec<String>> {
+    let dict_dir = format!("{}/{}", data_dir, USER_DICT_DIR);
+    let mut files = vec![];
+    if !Path::new(&dict_dir).is_dir() {
+        return Ok(files);
+    }
+    for entry in to_global_result(fs::read_dir(&dict_dir))? {
+        let path = to_global_result(entry)?.path();
+        let is_txt = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("txt"))
+            .unwrap_or(false);
+        if path.is_file() && is_txt {
+            if let Some(p) = path.to_str() {
+                files.push(p.replace("\\", "/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-06 10:41:55
+/// @Description: 词典签名(索引版本、用户词典和停用词内容的哈希)
+/// 用FNV-1a, 签名保存在文件中, 不能随编译器版本变化
+pub fn dict_signature(data_dir: &str) -> GlobalResult<String> {
+    let mut bytes = INDEX_VERSION.to_le_bytes().to_vec();
+    let mut files = user_dict_files(data_dir)?;
+    files.push(format!("{}/{}", data_dir, STOP_WORDS_FILE));
+    for file in files {
+        if let Ok(content) = fs::read(&file) {
+            //文件名和内容前加长度, 避免拼接后相同
+            for part in [file.as_bytes(), &content] {
+                bytes.extend((part.len() as u64).to_le_bytes());
+                bytes.extend(part);
+            }
+        }
+    }
+    Ok(format!("{:x}", fingerprint::fnv1a(&bytes)))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-06 10:52:17
+/// @Description: 词典是否已变更(与建索引时记录的签名不同), 变更后需要重建索引
+pub fn dict_changed(data_dir: &str) -> bool {
+    let sig_file = format!("{}/{}", data_dir, DICT_SIGNATURE_FILE);
+    //没有签名文件视为使用当前版本的默认词典(没有用户词典和停用词)建的索引
+    let saved = fs::read_to_string(sig_file)
+        .unwrap_or_else(|_| format!("{:x}", fingerprint::fnv1a(&INDEX_VERSION.to_le_bytes())));
+    match dict_signature(data_dir) {
+        Ok(sig) => saved.trim() != sig,
+        Err(e) => {
+            error!("计算词典签名异常:{}", e);
+            false
+        }
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-06 11:03:40
+/// @Description: 记录当前词典签名(重建索引时调用)
+pub fn save_dict_signature(data_dir: &str) -> GlobalResult<()> {
+    let sig = dict_signature(data_dir)?;
+    let sig_file = format!("{}/{}", data_dir, DICT_SIGNATURE_FILE);
+    to_global_result(fs::write(sig_file, sig))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tantivy::tantivy_jieba::JiebaTokenizer;
@@ -0,0 +1,262 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-28 13:05:18
+//! @Description: 元数据字段查询, 如 author:张三 title:"年度 报告" pages:>10 created:2024-03 modified:>2024
+//!
+//! 搜索框中 字段:值 形式的部分作为筛选条件(之间为且), 其余部分按普通查询。
+//! 字段名支持英文和中文(作者:张三), 冒号可以是中文冒号; 不认识的字段名按普通文本搜索。
+//! 页数支持 N、>N、>=N、<N、<=N; 创建、修改时间支持年、年-月、年-月-日, 可以加 > 或 < 表示之后、之前。
+
+use std::ops::Bound;
+
+use chrono::{Datelike, NaiveDate};
+use lazy_static::lazy_static;
+use regex::Regex;
+use tantivy::{
+    query::{PhraseQuery, Query, RangeQuery, TermQuery},
+    schema::{IndexRecordOption, Schema},
+    Term,
+};
+
+use crate::file_scanner::doc_metadata;
+
+use super::tantivy_search::{
+    self, FIELD_APPLICATION, FIELD_AUTHOR, FIELD_CREATED_TIME, FIELD_DOC_MODIFIED_TIME,
+    FIELD_KEYWORDS, FIELD_PAGE_COUNT, FIELD_SUBJECT, FIELD_TITLE,
+};
+
+lazy_static! {
+    //字段:值 (值可以用引号括起来包含空格), 或普通的一段文本
+    static ref PART_RE: Regex =
+        Regex::new(r#"([\p{L}\p{N}_]+)[:：]("[^"]*"|\S+)|"[^"]*"|\S+"#).unwrap();
+}
+
+/// 可查询的元数据字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    Title,       //标题
+    Author,      //作者
+    Subject,     //主题
+    Keywords,    //关键词
+    Application, //应用程序
+    PageCount,   //页数
+    Created,     //创建时间
+    Modified,    //文档中记录的修改时间
+}
+
+impl MetadataField {
+    pub const ALL: [MetadataField; 8] = [
+        MetadataField::Title,
+        MetadataField::Author,
+        MetadataField::Subject,
+        MetadataField::Keywords,
+        MetadataField::Application,
+        MetadataField::PageCount,
+        MetadataField::Created,
+        MetadataField::Modified,
+    ];
+
+    /// 搜索框中可用的字段名, 第一个为主名称
+    pub fn names(&self) -> &'static [&'static str] {
+        match self {
+            MetadataField::Title => &["title", "标题"],
+            MetadataField::Author => &["author", "作者"],
+            MetadataField::Subject => &["subject", "主题"],
+            MetadataField::Keywords => &["keywords", "keyword", "关键词"],
+            MetadataField::Application => &["app", "application", "应用"],
+            MetadataField::PageCount => &["pages", "页数"],
+            MetadataField::Created => &["created", "创建"],
+            MetadataField::Modified => &["modified", "修改"],
+        }
+    }
+
+    /// 索引字段名
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            MetadataField::Title => FIELD_TITLE,
+            MetadataField::Author => FIELD_AUTHOR,
+            MetadataField::Subject => FIELD_SUBJECT,
+            MetadataField::Keywords => FIELD_KEYWORDS,
+            MetadataField::Application => FIELD_APPLICATION,
+            MetadataField::PageCount => FIELD_PAGE_COUNT,
+            MetadataField::Created => FIELD_CREATED_TIME,
+            MetadataField::Modified => FIELD_DOC_MODIFIED_TIME,
+        }
+    }
+
+    /// 按字段名查找(不区分大小写)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        MetadataField::ALL
+            .into_iter()
+            .find(|field| field.names().contains(&name.as_str()))
+    }
+}
+
+/// 元数据筛选条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataFilter {
+    pub field: MetadataField,
+    pub value: String, //去掉引号的值
+}
+
+impl MetadataFilter {
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 13:30:44
+    /// @Description: 转成查询, 旧索引没有该字段或值没有可查询的词时为None
+    pub fn to_query(&self, schema: &Schema) -> Option<Box<dyn Query>> {
+        let field_name = self.field.field_name();
+        let field = schema.get_field(field_name).ok()?;
+        match self.field {
+            MetadataField::PageCount => {
+                let (lower, upper) = page_count_bounds(&self.value)?;
+                Some(Box::new(RangeQuery::new_u64_bounds(
+                    field_name.into(),
+                    lower,
+                    upper,
+                )))
+            }
+            MetadataField::Created | MetadataField::Modified => {
+                let (lower, upper) = time_bounds(&self.value)?;
+                Some(Box::new(RangeQuery::new_i64_bounds(
+                    field_name.into(),
+                    lower,
+                    upper,
+                )))
+            }
+            _ => {
+                //和建索引时一样分词, 多个词按短语匹配
+                let mut analyzer = tantivy_search::lower_analyzer();
+                let mut terms: Vec<(usize, Term)> = vec![];
+                let mut token_stream = analyzer.token_stream(&self.value);
+                while let Some(token) = token_stream.next() {
+                    terms.push((token.position, Term::from_field_text(field, &token.text)));
+                }
+                match terms.len() {
+                    0 => None,
+                    1 => Some(Box::new(TermQuery::new(
+                        terms.remove(0).1,
+                        IndexRecordOption::Basic,
+                    ))),
+                    _ => Some(Box::new(PhraseQuery::new_with_offset(terms))),
+                }
+            }
+        }
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 13:15:02
+/// @Description: 从搜索文本中取出元数据筛选条件, 返回(其余文本, 筛选条件)
+/// 值不合法(如 pages:abc)时按普通文本保留
+pub fn parse_filters(text: &str) -> (String, Vec<MetadataFilter>) {
+    let mut rest: Vec<&str> = vec![];
+    let mut filters: Vec<MetadataFilter> = vec![];
+    for caps in PART_RE.captures_iter(text) {
+        let part = caps.get(0).map(|m| m.as_str()).unwrap_or("");
+        let filter = match (caps.get(1), caps.get(2)) {
+            (Some(name), Some(value)) => {
+                MetadataField::from_name(name.as_str()).and_then(|field| {
+                    let value = value.as_str().trim_matches('"').trim().to_string();
+                    let valid = match field {
+                        MetadataField::PageCount => page_count_bounds(&value).is_some(),
+                        MetadataField::Created | MetadataField::Modified => {
+                            time_bounds(&value).is_some()
+                        }
+                        _ => !value.is_empty(),
+                    };
+                    valid.then_some(MetadataFilter { field, value })
+                })
+            }
+            _ => None,
+        };
+        match filter {
+            Some(filter) => filters.push(filter),
+            None => rest.push(part),
+        }
+    }
+    (rest.join(" "), filters)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 13:40:27
+/// @Description: 筛选条件转成查询(之间为且), 忽略无法查询的条件
+pub fn filter_queries(schema: &Schema, filters: &[MetadataFilter]) -> Vec<Box<dyn Query>> {
+    filters
+        .iter()
+        .filter_map(|filter| filter.to_query(schema))
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 13:48:10
+/// @Description: 比较符和值: >=10 -> (">=", "10")
+fn split_operator(value: &str) -> (&str, &str) {
+    for op in [">=", "<=", ">", "<", "="] {
+        if let Some(rest) = value.strip_prefix(op) {
+            return (op, rest.trim());
+        }
+    }
+    ("=", value.trim())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 13:52:36
+/// @Description: 页数范围
+fn page_count_bounds(value: &str) -> Option<(Bound<u64>, Bound<u64>)> {
+    let (op, number) = split_operator(value);
+    let number: u64 = number.parse().ok()?;
+    Some(match op {
+        ">" => (Bound::Excluded(number), Bound::Unbounded),
+        ">=" => (Bound::Included(number), Bound::Unbounded),
+        //没有页数的文档不索引该字段, 不会被<匹配到
+        "<" => (Bound::Unbounded, Bound::Excluded(number)),
+        "<=" => (Bound::Unbounded, Bound::Included(number)),
+        _ => (Bound::Included(number), Bound::Included(number)),
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 14:03:55
+/// @Description: 创建、修改时间范围(毫秒): 年、年-月、年-月-日表示的时间段, >为该时间段之后, <为之前
+fn time_bounds(value: &str) -> Option<(Bound<i64>, Bound<i64>)> {
+    let (op, date) = split_operator(value);
+    let (start, end) = date_period(date)?;
+    let start = doc_metadata::local_millis(start.and_hms_opt(0, 0, 0)?);
+    let end = doc_metadata::local_millis(end.and_hms_opt(0, 0, 0)?);
+    Some(match op {
+        ">" => (Bound::Included(end), Bound::Unbounded),
+        ">=" => (Bound::Included(start), Bound::Unbounded),
+        "<" => (Bound::Unbounded, Bound::Excluded(start)),
+        "<=" => (Bound::Unbounded, Bound::Excluded(end)),
+        _ => (Bound::Included(start), Bound::Excluded(end)),
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 14:10:21
+/// @Description: 日期表示的时间段[开始, 结束): 2024、2024-03、2024-03-05(也可以用/或.分隔)
+fn date_period(date: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let parts = date
+        .split(['-', '/', '.'])
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    match parts.as_slice() {
+        [year] => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, 1, 1)?;
+            Some((start, start.with_year(start.year() + 1)?))
+        }
+        [year, month] => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, *month, 1)?;
+            let end = match month {
+                12 => NaiveDate::from_ymd_opt(*year as i32 + 1, 1, 1)?,
+                _ => NaiveDate::from_ymd_opt(*year as i32, month + 1, 1)?,
+            };
+            Some((start, end))
+        }
+        [year, month, day] => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, *month, *day)?;
+            Some((start, start.succ_opt()?))
+        }
+        _ => None,
+    }
+}
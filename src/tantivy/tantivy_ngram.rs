@@ -0,0 +1,116 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-01-10 09:32:18
+//! @Description: 二元(bigram)分词器，用于子串搜索
+//!
+//! 按空白切分成片段，每个片段内相邻两个字符组成一个词，position为字符位置，
+//! 这样任意长度>=2的子串都可以用短语查询匹配，不受jieba分词边界影响。
+
+use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+/// 二元分词器
+///
+/// # Example
+/// ```rust
+/// use tantivy::tokenizer::*;
+/// let mut tokenizer = rust_my_doc_search::tantivy::tantivy_ngram::BigramTokenizer {};
+/// let mut token_stream = tokenizer.token_stream("张三丰");
+/// assert_eq!(token_stream.next().unwrap().text, "张三");
+/// assert_eq!(token_stream.next().unwrap().text, "三丰");
+/// assert!(token_stream.next().is_none());
+/// ```
+#[derive(Clone)]
+pub struct BigramTokenizer;
+
+/// [`BigramTokenizer`]产生的词流
+pub struct BigramTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl TokenStream for BigramTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+impl Tokenizer for BigramTokenizer {
+    type TokenStream<'a> = BigramTokenStream;
+
+    fn token_stream(&mut self, text: &str) -> BigramTokenStream {
+        let mut indices = text.char_indices().collect::<Vec<_>>();
+        indices.push((text.len(), '\0'));
+
+        let mut tokens = Vec::new();
+        //当前片段开始的字符位置
+        let mut run_start = 0;
+        for i in 0..indices.len() {
+            let is_end = i == indices.len() - 1 || indices[i].1.is_whitespace();
+            if !is_end {
+                continue;
+            }
+            let run_len = i - run_start;
+            if run_len == 1 {
+                //单个字符的片段，保留单字
+                tokens.push(new_token(text, &indices, run_start, run_start + 1));
+            }
+            if run_len >= 2 {
+                for start in run_start..i - 1 {
+                    tokens.push(new_token(text, &indices, start, start + 2));
+                }
+            }
+            run_start = i + 1;
+        }
+        BigramTokenStream { tokens, index: 0 }
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-10 09:50:41
+/// @Description: 根据字符位置创建词
+fn new_token(text: &str, indices: &[(usize, char)], start: usize, end: usize) -> Token {
+    Token {
+        offset_from: indices[start].0,
+        offset_to: indices[end].0,
+        position: start,
+        text: String::from(&text[indices[start].0..indices[end].0]),
+        position_length: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tantivy::tantivy_ngram::BigramTokenizer;
+
+    #[test]
+    fn it_works() {
+        use tantivy::tokenizer::*;
+        let mut tokenizer = BigramTokenizer {};
+        let mut token_stream = tokenizer.token_stream("张华考 A 北京");
+        let mut tokens = Vec::new();
+        while let Some(token) = token_stream.next() {
+            tokens.push((token.position, token.text.clone()));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                (0, "张华".to_string()),
+                (1, "华考".to_string()),
+                (4, "A".to_string()),
+                (6, "北京".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-17 09:20:35
+//! @Description: 正则搜索(grep模式)
+//!
+//! 正则只能匹配一个分词内部的文本时(如 E\d{4})，先用RegexQuery在内容字段的词典中找候选文档，
+//! 否则查询所有文档。候选文档按搜索选项筛选、排序后，再用完整的正则在存储的内容上验证，并返回匹配位置。
+//! 用词典找候选文档时，只返回在一个分词内部的匹配(如 v1.2E1234 分成 v1.2、E1234，2E1234 不算匹配)，
+//! 保证词典筛选不会漏掉文档。
+//! 查询所有文档时最多验证 REGEX_MAX_SCANNED 个文档片段。
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use tantivy::{
+    query::{AllQuery, Query, RegexQuery},
+    schema::{FieldType, Schema},
+    tokenizer::{TokenStream, Tokenizer},
+    Index, TantivyDocument,
+};
+
+use crate::sys::global::{to_global_result, GlobalError, GlobalResult};
+
+use super::{
+    tantivy_jieba::JiebaTokenizer,
+    tantivy_search::{self, FieldAnalyzer, IndexDocument, SearchOptions, FIELD_FILE_CONTENT},
+};
+
+//每次验证的候选文档数
+const REGEX_BATCH_SIZE: usize = 200;
+//查询所有文档时最多验证的文档片段数
+pub const REGEX_MAX_SCANNED: usize = 20_000;
+
+lazy_static! {
+    //以数字(\d 或 0-9)结尾, 后面可以是 {n} {n,} {n,m}(n>=1) +
+    static ref ENDS_WITH_DIGIT: Regex =
+        Regex::new(r"(\\d|[0-9])(\{[1-9][0-9]*(,[0-9]*)?\}|\+)?$").unwrap();
+}
+
+/// 正则命中的文档片段
+pub struct RegexHit {
+    pub doc: IndexDocument,
+    pub matches: Vec<(usize, usize)>, //匹配位置(file_content中的字节位置)
+}
+
+/// 正则搜索结果
+pub struct RegexSearchResult {
+    pub hits: Vec<RegexHit>,
+    pub scanned: usize,  //验证过的文档片段数
+    pub truncated: bool, //查询所有文档时达到验证上限, 后面的文档没有验证
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-17 09:35:48
+/// @Description: 正则搜索, 按搜索选项筛选(索引目录、分面、元数据)、排序
+/// @param pattern: 正则表达式
+pub fn search_regex(
+    index_obj: &Index,
+    pattern: &str,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<RegexSearchResult> {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => return Err(GlobalError::new(format!("正则表达式错误:{}", e))),
+    };
+    let schema = index_obj.schema();
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    let term_query = term_pattern(pattern, &schema)
+        .and_then(|term_pattern| RegexQuery::from_pattern(&term_pattern, body).ok());
+    //用词典筛选时只保留分词内部的匹配
+    let in_token = term_query.is_some();
+    let query: Box<dyn Query> = match term_query {
+        Some(query) => Box::new(query),
+        None => Box::new(AllQuery),
+    };
+
+    let skip = (page_num - 1) * page_size;
+    let mut hits: Vec<RegexHit> = vec![];
+    let mut matched_count = 0;
+    let mut offset = 0;
+    let mut scanned = 0;
+    let mut truncated = false;
+    //按排序分批取候选文档验证, 直到够一页或没有更多文档
+    while hits.len() < page_size {
+        if !in_token && scanned >= REGEX_MAX_SCANNED {
+            truncated = true;
+            break;
+        }
+        let doc_addresses = tantivy_search::search_doc_addresses(
+            &searcher,
+            &schema,
+            query.box_clone(),
+            options,
+            offset,
+            REGEX_BATCH_SIZE,
+        )?;
+        let exhausted = doc_addresses.len() < REGEX_BATCH_SIZE;
+        offset += doc_addresses.len();
+        for doc_address in doc_addresses {
+            let tantivy_document: TantivyDocument = to_global_result(searcher.doc(doc_address))?;
+            let doc = tantivy_search::to_index_doc(&tantivy_document, &schema);
+            scanned += 1;
+            let matches = find_matches(&regex, &doc.file_content, in_token);
+            if matches.is_empty() {
+                continue;
+            }
+            matched_count += 1;
+            if matched_count > skip {
+                hits.push(RegexHit { doc, matches });
+                if hits.len() >= page_size {
+                    break;
+                }
+            }
+        }
+        if exhausted {
+            break;
+        }
+    }
+    Ok(RegexSearchResult {
+        hits,
+        scanned,
+        truncated,
+    })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-17 10:20:33
+/// @Description: 正则在文本中的匹配位置(跳过空匹配)
+/// @param in_token: 是否只保留在一个分词内部的匹配
+fn find_matches(regex: &Regex, text: &str, in_token: bool) -> Vec<(usize, usize)> {
+    let matches = regex
+        .find_iter(text)
+        .filter(|m| !m.as_str().is_empty())
+        .map(|m| (m.start(), m.end()))
+        .collect::<Vec<(usize, usize)>>();
+    if !in_token || matches.is_empty() {
+        return matches;
+    }
+
+    //分词的(开始, 结束)字节位置, 按开始位置排序
+    let mut spans: Vec<(usize, usize)> = vec![];
+    let mut tokenizer = JiebaTokenizer {};
+    let mut token_stream = tokenizer.token_stream(text);
+    while let Some(token) = token_stream.next() {
+        spans.push((token.offset_from, token.offset_to));
+    }
+    spans.sort_unstable();
+
+    //匹配按开始位置有序, 开始位置不大于匹配开始的分词中, 最远的结束位置要覆盖匹配
+    let mut i = 0;
+    let mut covered_to = 0;
+    matches
+        .into_iter()
+        .filter(|(start, end)| {
+            while i < spans.len() && spans[i].0 <= *start {
+                covered_to = covered_to.max(spans[i].1);
+                i += 1;
+            }
+            covered_to >= *end
+        })
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-17 09:52:06
+/// @Description: 在词典上查找候选词的正则, 不能用词典时返回None
+/// jieba把连续的字母数字作为一个词，所以只用只能匹配字母数字的正则(字符类中不能有'-'等符号);
+/// 内容字段做了词干化时，词末尾的字母可能被改写(errors => error)，含字母的正则要以数字结尾(如 E\d{4})
+fn term_pattern(pattern: &str, schema: &Schema) -> Option<String> {
+    let body = schema.get_field(FIELD_FILE_CONTENT).ok()?;
+    let stemmed = match schema.get_field_entry(body).field_type() {
+        FieldType::Str(text_options) => {
+            text_options.get_indexing_options()?.tokenizer() == FieldAnalyzer::Stem.tokenizer_name()
+        }
+        _ => return None,
+    };
+
+    let chars = pattern.chars().collect::<Vec<char>>();
+    let mut in_class = false;
+    let mut in_repeat = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            //只允许 \d
+            '\\' => {
+                if chars.get(i + 1) != Some(&'d') {
+                    return None;
+                }
+                i += 1;
+            }
+            '[' if !in_class => {
+                if chars.get(i + 1) == Some(&'^') {
+                    return None;
+                }
+                in_class = true;
+            }
+            ']' if in_class => in_class = false,
+            '{' if !in_class && !in_repeat => in_repeat = true,
+            '}' if in_repeat => in_repeat = false,
+            ',' if in_repeat => {}
+            '*' | '+' | '|' | ')' if !in_class => {}
+            '?' if !in_class => {}
+            '(' if !in_class => {
+                //只允许非捕获分组 (?:
+                if chars.get(i + 1) == Some(&'?') {
+                    if chars.get(i + 2) != Some(&':') {
+                        return None;
+                    }
+                    i += 2;
+                }
+            }
+            c if c.is_ascii_digit() || c.is_ascii_alphabetic() => {
+                if c.is_ascii_alphabetic() && stemmed && !ends_with_digit(pattern) {
+                    return None;
+                }
+                //字符类中的范围(a-z)两端要同为数字、小写或大写字母; 字面的'-'不会出现在词内
+                if in_class && chars.get(i + 1) == Some(&'-') {
+                    let end = *chars.get(i + 2)?;
+                    if !same_kind(c, end) || end < c {
+                        return None;
+                    }
+                    i += 2;
+                }
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+    if in_class || in_repeat {
+        return None;
+    }
+    //词是小写的, 词典中的词要整个匹配
+    Some(format!("(?i).*(?:{}).*", pattern))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-17 10:48:21
+/// @Description: 正则的每个匹配是否都以数字结尾(没有分支, 最后是至少出现一次的数字)
+fn ends_with_digit(pattern: &str) -> bool {
+    !pattern.contains('|') && ENDS_WITH_DIGIT.is_match(pattern)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 11:05:42
+/// @Description: 两个字符是否同为数字、小写字母或大写字母
+fn same_kind(a: char, b: char) -> bool {
+    (a.is_ascii_digit() && b.is_ascii_digit())
+        || (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+        || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+}
@@ -2,75 +2,212 @@
 //! @Date: Create in 2024/4/14 18:37
 //! @Description
 
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::Bound;
+
+use crate::file_scanner::doc_metadata::DocMetadata;
+use crate::file_scanner::doc_sections::TextSection;
 use crate::sys::global::{to_global_result, GlobalResult};
-use crate::tantivy::tantivy_jieba::JiebaTokenizer;
-use egui::TextBuffer;
+use crate::tantivy::tantivy_facet::{self, FacetGroup, FacetKind};
+use crate::tantivy::tantivy_jieba::{self, JiebaTokenizer};
+use crate::tantivy::tantivy_metadata::{self, MetadataFilter};
+use crate::tantivy::tantivy_ngram::BigramTokenizer;
 
 use regex::Regex;
 use rust_common::file_util;
 use serde::{Deserialize, Serialize};
-use tantivy::collector::TopDocs;
+use tantivy::collector::{Collector, Count, DocSetCollector, TopDocs};
+use tantivy::columnar::Column;
 
 use tantivy::indexer::LogMergePolicy;
-use tantivy::query::QueryParser;
+use tantivy::query::{
+    BooleanQuery, MoreLikeThisQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query, QueryParser,
+    RangeQuery, TermQuery,
+};
 use tantivy::schema::{
-    IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, INDEXED, STORED, STRING,
+    Field, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, Value, FAST,
+    INDEXED, STORED, STRING,
 };
 
-use tantivy::tokenizer::TextAnalyzer;
-use tantivy::{doc, Index, TantivyDocument, Term};
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language, LowerCaser, Stemmer, TextAnalyzer, TokenStream,
+};
+use tantivy::{
+    doc, DocAddress, DocId, Index, Order, Searcher, SegmentReader, TantivyDocument, Term,
+};
 
 //内容长度
 const CONTETN_LEN: usize = 1000;
+//jieba搜索结果少于该数量时，用二元分词字段补充子串搜索结果
+const FALLBACK_MIN_HITS: usize = 5;
+//查找相似文档时，最多取源文件的内容片段数量
+const SIMILAR_SOURCE_MAX_CHUNKS: usize = 50;
+//每个文件最多返回的表格行命中数
+const MAX_RECORD_HITS_PER_FILE: usize = 20;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct IndexDocument {
     pub index_dir_id: i64,
     pub file_path: String,
     pub file_name: String,
     pub file_content: String,
+    pub modified_time: i64, //修改时间(毫秒)
+    pub file_size: u64,     //文件大小(字节)
+    #[serde(default)]
+    pub metadata: DocMetadata, //文档元数据(标题、作者等)
+    #[serde(default)]
+    pub location: String, //内容片段所在位置(第47页、Sheet2等), 没有时为空
+    #[serde(default)]
+    pub record: bool, //是否表格的一行(内容是"列名: 值")
+    #[serde(default)]
+    pub line: u64, //内容片段的起始行号(从1开始), 按行切分的文本文件才有, 内容保留换行
 }
 
 impl IndexDocument {
     pub fn new() -> Self {
-        IndexDocument {
-            index_dir_id: 0,
-            file_path: "".into(),
-            file_name: "".into(),
-            file_content: "".into(),
-        }
+        IndexDocument::default()
     }
 
     /// @Author: DengLibin
     /// @Date: Create in 2024-12-11 12:22:47
     /// @Description: 根据内容创建
     pub fn split_to_list(file_path: String, content: String, index_dir_id: i64) -> Vec<Self> {
-        let content = content.replace("\n", "").replace("\r", "");
+        Self::split_to_list_with_metadata(file_path, content, index_dir_id, DocMetadata::default())
+    }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 11:05:27
+    /// @Description: 根据内容和文档元数据创建, 每个内容片段都带元数据
+    pub fn split_to_list_with_metadata(
+        file_path: String,
+        content: String,
+        index_dir_id: i64,
+        metadata: DocMetadata,
+    ) -> Vec<Self> {
+        let sections = vec![TextSection::new("", content)];
+        Self::split_sections_to_list(file_path, sections, index_dir_id, metadata)
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 14:05:12
+    /// @Description: 按结构单元(PDF页、幻灯片、工作表等)创建, 片段不跨单元, 每个片段记录所在单元的位置
+    pub fn split_sections_to_list(
+        file_path: String,
+        sections: Vec<TextSection>,
+        index_dir_id: i64,
+        metadata: DocMetadata,
+    ) -> Vec<Self> {
         // 创建一个正则表达式，匹配多个空格
         let re = Regex::new(r"\s+").unwrap();
 
-        // 使用正则替换多个空格为单个空格
-        let binding = re.replace_all(&content, " ");
-        let content = binding.as_str();
-
-        let contents = Self::split_content(content, CONTETN_LEN);
         let file_path = file_path.replace("\\", "/");
         let mut file_name = String::new();
         let fr = file_path.rfind("/");
         if let Some(i) = fr {
             file_name.push_str(&file_path[i + 1..]);
         }
+        let (modified_time, file_size) = Self::file_metadata(&file_path);
+        //切分后的片段
+        let mut contents: Vec<TextSection> = vec![];
+        for section in sections {
+            //按行切分, 保留换行
+            if section.line > 0 {
+                for (line, item) in Self::split_lines(&section.text, section.line, CONTETN_LEN) {
+                    contents.push(TextSection {
+                        line,
+                        ..TextSection::new(section.location.clone(), item)
+                    });
+                }
+                continue;
+            }
+            //换行换成空格, 避免前后两行的词连在一起
+            let content = section.text.replace(['\n', '\r'], " ");
+            // 使用正则替换多个空格为单个空格
+            let binding = re.replace_all(&content, " ");
+            //空的单元跳过
+            if binding.trim().is_empty() {
+                continue;
+            }
+            for item in Self::split_content(binding.as_ref(), CONTETN_LEN) {
+                contents.push(TextSection {
+                    record: section.record,
+                    ..TextSection::new(section.location.clone(), item)
+                });
+            }
+        }
+        //至少有一个片段, 文件名也要能搜到
+        if contents.is_empty() {
+            contents.push(TextSection::default());
+        }
         contents
             .into_iter()
             .map(|item| Self {
                 index_dir_id,
                 file_path: file_path.clone(),
                 file_name: file_name.clone(),
-                file_content: item,
+                file_content: item.text,
+                modified_time,
+                file_size,
+                metadata: metadata.clone(),
+                location: item.location,
+                record: item.record,
+                line: item.line,
             })
             .collect::<Vec<Self>>()
     }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-19 09:40:26
+    /// @Description: 文件修改时间(毫秒)和大小, 读取失败时为0
+    fn file_metadata(file_path: &str) -> (i64, u64) {
+        match std::fs::metadata(file_path) {
+            Ok(metadata) => {
+                let modified_time = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as i64)
+                    .unwrap_or(0);
+                (modified_time, metadata.len())
+            }
+            Err(_) => (0, 0),
+        }
+    }
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-07 09:20:14
+    /// @Description: 按行切割, 片段不超过len字节(超长的行单独切割), 保留换行, 返回(起始行号, 片段)
+    /// 空白的片段跳过, 行号照常累加
+    fn split_lines(content: &str, start_line: u64, len: usize) -> Vec<(u64, String)> {
+        let mut contents: Vec<(u64, String)> = vec![];
+        let mut sub_content = String::new();
+        let mut sub_start = start_line;
+        let mut push = |line: u64, item: String| {
+            if !item.trim().is_empty() {
+                contents.push((line, item));
+            }
+        };
+        for (i, line) in content.split_inclusive('\n').enumerate() {
+            let line_no = start_line + i as u64;
+            let line = line.replace("\r", "");
+            if !sub_content.is_empty() && sub_content.len() + line.len() > len {
+                push(sub_start, std::mem::take(&mut sub_content));
+            }
+            if sub_content.is_empty() {
+                sub_start = line_no;
+            }
+            if line.len() > len {
+                for item in Self::split_content(&line, len) {
+                    push(line_no, item);
+                }
+                continue;
+            }
+            sub_content.push_str(&line);
+        }
+        push(sub_start, sub_content);
+        contents
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-12-11 12:23:04
     /// @Description: 切割
@@ -106,38 +243,131 @@ impl IndexDocument {
 }
 
 //字段名
-const FIELD_INDEX_DIR_ID: &str = "index_dir_id";
-const FIELD_FILE_ANME: &str = "file_name";
-const FIELD_FILE_PATH: &str = "file_path";
-const FIELD_FILE_CONTENT: &str = "file_content";
+pub const FIELD_INDEX_DIR_ID: &str = "index_dir_id";
+pub const FIELD_FILE_ANME: &str = "file_name";
+pub const FIELD_FILE_PATH: &str = "file_path";
+pub const FIELD_FILE_CONTENT: &str = "file_content";
+pub const FIELD_FILE_CONTENT_NGRAM: &str = "file_content_ngram"; //二元分词内容，用于子串搜索，不存储
+pub const FIELD_MODIFIED_TIME: &str = "modified_time"; //修改时间, 用于排序
+pub const FIELD_FILE_SIZE: &str = "file_size"; //文件大小, 用于排序
+pub const FIELD_FILE_NAME_SORT: &str = "file_name_sort"; //小写文件名, 用于排序, 不存储
+pub const FIELD_FILE_TYPE: &str = "file_type"; //文件类型, 用于分面
+pub const FIELD_MODIFIED_MONTH: &str = "modified_month"; //修改年月, 用于分面
+pub const FIELD_SIZE_BUCKET: &str = "size_bucket"; //大小区间, 用于分面
+pub const FIELD_TITLE: &str = "title"; //文档标题(元数据)
+pub const FIELD_AUTHOR: &str = "author"; //作者(元数据)
+pub const FIELD_SUBJECT: &str = "subject"; //主题(元数据)
+pub const FIELD_KEYWORDS: &str = "keywords"; //关键词(元数据)
+pub const FIELD_APPLICATION: &str = "application"; //创建文档的应用程序(元数据)
+pub const FIELD_CREATED_TIME: &str = "created_time"; //文档创建时间(元数据), 用于筛选
+pub const FIELD_DOC_MODIFIED_TIME: &str = "doc_modified_time"; //文档中记录的修改时间(元数据), 用于筛选
+pub const FIELD_PAGE_COUNT: &str = "page_count"; //页数(元数据), 用于筛选
+pub const FIELD_LOCATION: &str = "location"; //内容片段所在位置(第47页、Sheet2等), 只存储
+pub const FIELD_RECORD: &str = "record"; //是否表格的一行, 快速字段(限制每个文件的行命中数)
+pub const FIELD_LINE: &str = "line"; //内容片段的起始行号, 可按范围查询(取相邻片段的行)
 
 //分词器名称
-const JIEBA: &str = "jieba";
+const JIEBA: &str = "jieba"; //jieba + 小写 + ascii折叠 + 英文词干
+const JIEBA_LOWER: &str = "jieba_lower"; //jieba + 小写 + ascii折叠
+const JIEBA_RAW: &str = "jieba_raw"; //仅jieba分词
+const CJK_BIGRAM: &str = "cjk_bigram"; //二元分词 + 小写
+
+/// 字段使用的分析器, 中文词保持jieba分词结果, 拉丁文按选项处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldAnalyzer {
+    Stem,  //小写 + ascii折叠 + 英文词干
+    Lower, //小写 + ascii折叠
+    Raw,   //不处理
+}
+
+impl FieldAnalyzer {
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-08 09:40:12
+    /// @Description: 注册的分词器名称
+    pub fn tokenizer_name(&self) -> &'static str {
+        match self {
+            FieldAnalyzer::Stem => JIEBA,
+            FieldAnalyzer::Lower => JIEBA_LOWER,
+            FieldAnalyzer::Raw => JIEBA_RAW,
+        }
+    }
+}
+
+/// 各文本字段的分析器配置, 只在创建索引时生效(已有索引以schema中记录的为准)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerConfig {
+    pub file_name: FieldAnalyzer,
+    pub file_content: FieldAnalyzer,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        AnalyzerConfig {
+            file_name: FieldAnalyzer::Stem,
+            file_content: FieldAnalyzer::Stem,
+        }
+    }
+}
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-04-14 20:08:55
 /// @Description: 打开
 pub fn open_index(index_dir: &str) -> GlobalResult<Index> {
+    open_index_with_config(index_dir, &AnalyzerConfig::default())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 09:52:36
+/// @Description: 打开, 索引不存在时按分析器配置创建
+pub fn open_index_with_config(index_dir: &str, config: &AnalyzerConfig) -> GlobalResult<Index> {
     let bo = file_util::exist(index_dir);
-    let text_analyzer = TextAnalyzer::builder(JiebaTokenizer {}).build();
 
     let index = if !bo {
         //不存在则创建
-        create_index(index_dir).unwrap()
+        create_index(index_dir, config)?
     } else {
         to_global_result(Index::open_in_dir(index_dir))? // 获取索引
     };
 
     //注册jieba分词器
-
-    index.tokenizers().register(JIEBA, text_analyzer);
+    register_analyzers(&index);
     Ok(index)
 }
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 10:05:14
+/// @Description: 注册分析器
+pub fn register_analyzers(index: &Index) {
+    let stem_analyzer = TextAnalyzer::builder(JiebaTokenizer {})
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .filter(Stemmer::new(Language::English))
+        .build();
+    let raw_analyzer = TextAnalyzer::builder(JiebaTokenizer {}).build();
+    let bigram_analyzer = TextAnalyzer::builder(BigramTokenizer {})
+        .filter(LowerCaser)
+        .build();
+
+    index.tokenizers().register(JIEBA, stem_analyzer);
+    index.tokenizers().register(JIEBA_LOWER, lower_analyzer());
+    index.tokenizers().register(JIEBA_RAW, raw_analyzer);
+    index.tokenizers().register(CJK_BIGRAM, bigram_analyzer);
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-28 11:12:40
+/// @Description: jieba + 小写 + ascii折叠, 元数据字段用
+pub fn lower_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(JiebaTokenizer {})
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .build()
+}
+
 /// @Author: DengLibin
 /// @Date: Create in 2024-04-14 20:08:45
 /// @Description: 创建索引
-fn create_index(index_dir: &str) -> GlobalResult<Index> {
+fn create_index(index_dir: &str, config: &AnalyzerConfig) -> GlobalResult<Index> {
     // 定义Schema
     let bo = file_util::exist(index_dir);
     if !bo {
@@ -145,17 +375,38 @@ fn create_index(index_dir: &str) -> GlobalResult<Index> {
     }
     let mut schema_builder = Schema::builder();
 
-    let text_field_indexing = TextFieldIndexing::default()
-        .set_tokenizer(JIEBA)
+    schema_builder.add_i64_field(FIELD_INDEX_DIR_ID, INDEXED | STORED | FAST);
+    schema_builder.add_text_field(FIELD_FILE_ANME, text_options(config.file_name));
+    schema_builder.add_text_field(FIELD_FILE_CONTENT, text_options(config.file_content));
+    schema_builder.add_text_field(FIELD_FILE_PATH, STRING | STORED | FAST);
+    schema_builder.add_i64_field(FIELD_MODIFIED_TIME, STORED | FAST);
+    schema_builder.add_u64_field(FIELD_FILE_SIZE, STORED | FAST);
+    schema_builder.add_text_field(FIELD_FILE_NAME_SORT, STRING | FAST);
+    schema_builder.add_text_field(FIELD_FILE_TYPE, STRING | FAST);
+    schema_builder.add_text_field(FIELD_MODIFIED_MONTH, STRING | FAST);
+    schema_builder.add_text_field(FIELD_SIZE_BUCKET, STRING | FAST);
+    for name in [
+        FIELD_TITLE,
+        FIELD_AUTHOR,
+        FIELD_SUBJECT,
+        FIELD_KEYWORDS,
+        FIELD_APPLICATION,
+    ] {
+        schema_builder.add_text_field(name, text_options(FieldAnalyzer::Lower));
+    }
+    schema_builder.add_i64_field(FIELD_CREATED_TIME, INDEXED | STORED | FAST);
+    schema_builder.add_i64_field(FIELD_DOC_MODIFIED_TIME, INDEXED | STORED | FAST);
+    schema_builder.add_u64_field(FIELD_PAGE_COUNT, INDEXED | STORED | FAST);
+    schema_builder.add_text_field(FIELD_LOCATION, STORED);
+    schema_builder.add_bool_field(FIELD_RECORD, STORED | FAST);
+    schema_builder.add_u64_field(FIELD_LINE, INDEXED | STORED | FAST);
+    let ngram_indexing = TextFieldIndexing::default()
+        .set_tokenizer(CJK_BIGRAM)
         .set_index_option(IndexRecordOption::WithFreqsAndPositions);
-    let text_options = TextOptions::default()
-        .set_indexing_options(text_field_indexing)
-        .set_stored();
-
-    schema_builder.add_i64_field(FIELD_INDEX_DIR_ID, INDEXED);
-    schema_builder.add_text_field(FIELD_FILE_ANME, text_options.clone());
-    schema_builder.add_text_field(FIELD_FILE_CONTENT, text_options);
-    schema_builder.add_text_field(FIELD_FILE_PATH, STRING | STORED);
+    schema_builder.add_text_field(
+        FIELD_FILE_CONTENT_NGRAM,
+        TextOptions::default().set_indexing_options(ngram_indexing),
+    );
 
     let schema = schema_builder.build(); // 在目录中创建索引
                                          //let index = Index::create_in_ram(schema.clone()); // 获取索引写入器
@@ -176,20 +427,108 @@ fn create_index(index_dir: &str) -> GlobalResult<Index> {
 }
 
 /// @Author: DengLibin
-/// @Date: Create in 2024-04-15 10:34:37
-/// @Description: 添加文档
-pub fn insert_doc(index_obj: &mut Index, doc: &IndexDocument) -> GlobalResult<()> {
-    let index_dir_id = to_global_result(index_obj.schema().get_field(FIELD_INDEX_DIR_ID))?;
-    let title = to_global_result(index_obj.schema().get_field(FIELD_FILE_ANME))?;
-    let body = to_global_result(index_obj.schema().get_field(FIELD_FILE_CONTENT))?;
-    let path = to_global_result(index_obj.schema().get_field(FIELD_FILE_PATH))?;
+/// @Date: Create in 2025-01-10 11:20:05
+/// @Description: 删除索引目录并重新创建(字段变更后重建索引用)
+pub fn recreate_index(index_dir: &str, config: &AnalyzerConfig) -> GlobalResult<Index> {
+    if file_util::exist(index_dir) {
+        to_global_result(file_util::remove_dir_all(index_dir))?;
+    }
+    open_index_with_config(index_dir, config)
+}
 
-    let mut tantivy_doc = TantivyDocument::default();
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-08 10:21:47
+/// @Description: 文本字段选项
+fn text_options(analyzer: FieldAnalyzer) -> TextOptions {
+    let text_field_indexing = TextFieldIndexing::default()
+        .set_tokenizer(analyzer.tokenizer_name())
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    TextOptions::default()
+        .set_indexing_options(text_field_indexing)
+        .set_stored()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 10:02:15
+/// @Description: 转换为索引文档, 旧索引没有的字段跳过
+fn to_tantivy_doc(schema: &Schema, doc: &IndexDocument) -> GlobalResult<TantivyDocument> {
+    let index_dir_id = to_global_result(schema.get_field(FIELD_INDEX_DIR_ID))?;
+    let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+    let path = to_global_result(schema.get_field(FIELD_FILE_PATH))?;
 
+    let mut tantivy_doc = TantivyDocument::default();
     tantivy_doc.add_i64(index_dir_id, doc.index_dir_id);
     tantivy_doc.add_text(title, &doc.file_name);
     tantivy_doc.add_text(body, &doc.file_content);
     tantivy_doc.add_text(path, &doc.file_path);
+    if let Ok(ngram) = schema.get_field(FIELD_FILE_CONTENT_NGRAM) {
+        tantivy_doc.add_text(ngram, &doc.file_content);
+    }
+    if let Ok(modified_time) = schema.get_field(FIELD_MODIFIED_TIME) {
+        tantivy_doc.add_i64(modified_time, doc.modified_time);
+    }
+    if let Ok(file_size) = schema.get_field(FIELD_FILE_SIZE) {
+        tantivy_doc.add_u64(file_size, doc.file_size);
+    }
+    if let Ok(name_sort) = schema.get_field(FIELD_FILE_NAME_SORT) {
+        tantivy_doc.add_text(name_sort, doc.file_name.to_lowercase());
+    }
+    if let Ok(file_type) = schema.get_field(FIELD_FILE_TYPE) {
+        tantivy_doc.add_text(file_type, tantivy_facet::file_type(&doc.file_name));
+    }
+    if let Ok(modified_month) = schema.get_field(FIELD_MODIFIED_MONTH) {
+        let month = tantivy_facet::modified_month(doc.modified_time);
+        if !month.is_empty() {
+            tantivy_doc.add_text(modified_month, month);
+        }
+    }
+    if let Ok(size_bucket) = schema.get_field(FIELD_SIZE_BUCKET) {
+        tantivy_doc.add_text(size_bucket, tantivy_facet::size_bucket(doc.file_size));
+    }
+    //元数据, 没有的不加
+    let metadata = &doc.metadata;
+    let texts = [
+        (FIELD_TITLE, &metadata.title),
+        (FIELD_AUTHOR, &metadata.author),
+        (FIELD_SUBJECT, &metadata.subject),
+        (FIELD_KEYWORDS, &metadata.keywords),
+        (FIELD_APPLICATION, &metadata.application),
+    ];
+    for (name, value) in texts {
+        if let (Ok(field), false) = (schema.get_field(name), value.is_empty()) {
+            tantivy_doc.add_text(field, value);
+        }
+    }
+    let times = [
+        (FIELD_CREATED_TIME, metadata.created_time),
+        (FIELD_DOC_MODIFIED_TIME, metadata.modified_time),
+    ];
+    for (name, value) in times {
+        if let (Ok(field), true) = (schema.get_field(name), value != 0) {
+            tantivy_doc.add_i64(field, value);
+        }
+    }
+    if let (Ok(page_count), true) = (schema.get_field(FIELD_PAGE_COUNT), metadata.page_count > 0) {
+        tantivy_doc.add_u64(page_count, metadata.page_count);
+    }
+    if let (Ok(location), false) = (schema.get_field(FIELD_LOCATION), doc.location.is_empty()) {
+        tantivy_doc.add_text(location, &doc.location);
+    }
+    if let (Ok(record), true) = (schema.get_field(FIELD_RECORD), doc.record) {
+        tantivy_doc.add_bool(record, true);
+    }
+    if let (Ok(line), true) = (schema.get_field(FIELD_LINE), doc.line > 0) {
+        tantivy_doc.add_u64(line, doc.line);
+    }
+    Ok(tantivy_doc)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2024-04-15 10:34:37
+/// @Description: 添加文档
+pub fn insert_doc(index_obj: &mut Index, doc: &IndexDocument) -> GlobalResult<()> {
+    let tantivy_doc = to_tantivy_doc(&index_obj.schema(), doc)?;
 
     //创建writer
     let mut index_writer: tantivy::IndexWriter = to_global_result(index_obj.writer(50_000_000))?; // 添加文档
@@ -202,15 +541,12 @@ pub fn insert_doc(index_obj: &mut Index, doc: &IndexDocument) -> GlobalResult<()
 /// @Date: Create in 2024-12-10 17:56:29
 /// @Description: 批量添加
 pub fn insert_doc_list(index_obj: &mut Index, docs: &Vec<IndexDocument>) -> GlobalResult<()> {
-    let index_dir_id = to_global_result(index_obj.schema().get_field(FIELD_INDEX_DIR_ID))?;
-    let title = to_global_result(index_obj.schema().get_field(FIELD_FILE_ANME))?;
-    let body = to_global_result(index_obj.schema().get_field(FIELD_FILE_CONTENT))?;
-    let path = to_global_result(index_obj.schema().get_field(FIELD_FILE_PATH))?;
+    let schema = index_obj.schema();
 
     //创建writer 默认cpu核心数，最大8，会根据指定的缓冲区大小进行矫正
     let mut index_writer = to_global_result(index_obj.writer(200_000_000))?; // 添加文档
-    // let mut index_writer = to_global_result(index_obj.writer_with_num_threads(16, 320_000_000))?; // 添加文档
-    
+                                                                             // let mut index_writer = to_global_result(index_obj.writer_with_num_threads(16, 320_000_000))?; // 添加文档
+
     let merge_policy = LogMergePolicy::default();
     index_writer.set_merge_policy(Box::new(merge_policy));
 
@@ -218,20 +554,56 @@ pub fn insert_doc_list(index_obj: &mut Index, docs: &Vec<IndexDocument>) -> Glob
 
     for doc in docs {
         i += 1;
-        let mut tantivy_doc = TantivyDocument::default();
-        tantivy_doc.add_i64(index_dir_id, doc.index_dir_id);
-        tantivy_doc.add_text(title, &doc.file_name);
-        tantivy_doc.add_text(body, &doc.file_content);
-        tantivy_doc.add_text(path, &doc.file_path);
+        let tantivy_doc = to_tantivy_doc(&schema, doc)?;
         to_global_result(index_writer.add_document(tantivy_doc))?;
         if i % 10000 == 0 {
             to_global_result(index_writer.commit())?;
-         
         }
     }
     to_global_result(index_writer.commit())?;
     Ok(())
 }
+/// 结果排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortBy {
+    #[default]
+    Relevance, //相关度
+    ModifiedTime, //修改时间
+    FileName,     //文件名
+    FileSize,     //文件大小
+    FilePath,     //路径
+}
+
+impl SortBy {
+    pub const ALL: [SortBy; 5] = [
+        SortBy::Relevance,
+        SortBy::ModifiedTime,
+        SortBy::FileName,
+        SortBy::FileSize,
+        SortBy::FilePath,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortBy::Relevance => "相关度",
+            SortBy::ModifiedTime => "修改时间",
+            SortBy::FileName => "文件名",
+            SortBy::FileSize => "大小",
+            SortBy::FilePath => "路径",
+        }
+    }
+}
+
+/// 搜索选项: 排序、限定索引目录
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub sort_by: SortBy,
+    pub desc: bool,                              //是否降序(按相关度时忽略)
+    pub index_dir_ids: Vec<i64>,                 //只搜索这些索引目录, 为空时不限
+    pub facet_filters: Vec<(FacetKind, String)>, //分面筛选, 同一分面的值之间为或
+    pub metadata_filters: Vec<MetadataFilter>,   //元数据筛选(如 author:张三), 之间为且
+}
+
 /// @Author: DengLibin
 /// @Date: Create in 2024-04-15 10:34:44
 /// @Description: 查询
@@ -241,72 +613,829 @@ pub fn search_doc(
     page_num: usize,
     page_size: usize,
 ) -> GlobalResult<Vec<IndexDocument>> {
+    search_doc_with_options(
+        index_obj,
+        query_str,
+        &SearchOptions::default(),
+        page_num,
+        page_size,
+    )
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 10:30:18
+/// @Description: 查询(排序、限定索引目录)
+pub fn search_doc_with_options(
+    index_obj: &Index,
+    query_str: &str,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    let schema = index_obj.schema();
+    let index_reader = to_global_result(index_obj.reader())?;
+
+    let searcher = index_reader.searcher();
+    let query = parse_query(index_obj, query_str)?;
+    // 获取命中总数
+    // let hits_total = to_global_result(searcher.search(&query, &tantivy::collector::Count))?;
+    let vec: Vec<IndexDocument> =
+        search_with_options(searcher, schema, query, options, page_num, page_size)?;
+
+    Ok(vec)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 11:02:18
+/// @Description: 解析查询语句(文件名、内容字段)
+pub fn parse_query(index_obj: &Index, query_str: &str) -> GlobalResult<Box<dyn Query>> {
     let schema = index_obj.schema();
     let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
     let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+    let query_parser = QueryParser::for_index(index_obj, vec![title, body]);
+    to_global_result(query_parser.parse_query(query_str))
+}
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 15:02:33
+/// @Description: 分词结果拼成查询语句(所有词都要命中), 忽略空白
+pub fn and_query_str(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .filter(|token| !token.trim().is_empty())
+        .map(|token| token.as_str())
+        .collect::<Vec<&str>>()
+        .join(" AND ")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-10 10:12:44
+/// @Description: 查询, jieba结果太少或要求精确子串时，用二元分词字段做子串搜索
+/// @param query_str: 查询语句(jieba分词后的)
+/// @param text: 用户输入的原始文本
+/// @param exact_substring: 是否只做精确子串搜索
+pub fn search_doc_with_fallback(
+    index_obj: &Index,
+    query_str: &str,
+    text: &str,
+    exact_substring: bool,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    if exact_substring {
+        return search_substring_with_options(index_obj, text, options, page_num, page_size);
+    }
+    //两种结果合并成一个查询, 一起排序、分页
+    let query = fallback_query(index_obj, query_str, text, options)?;
+    let schema = index_obj.schema();
     let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+    search_with_options(searcher, schema, query, options, page_num, page_size)
+}
 
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 18:40:16
+/// @Description: jieba查询命中(按筛选条件)少于FALLBACK_MIN_HITS时, 和子串查询合并(或)
+fn fallback_query(
+    index_obj: &Index,
+    query_str: &str,
+    text: &str,
+    options: &SearchOptions,
+) -> GlobalResult<Box<dyn Query>> {
+    let query = parse_query(index_obj, query_str)?;
+    let substring_query = match substring_query(index_obj, text)? {
+        Some(substring_query) => substring_query,
+        None => return Ok(query),
+    };
+    let schema = index_obj.schema();
+    let index_reader = to_global_result(index_obj.reader())?;
     let searcher = index_reader.searcher();
-    let query_parser = QueryParser::for_index(index_obj, vec![title, body]);
+    let filtered = filter_query(&schema, query.box_clone(), options)?;
+    let count = to_global_result(searcher.search(&filtered, &Count))?;
+    if count >= FALLBACK_MIN_HITS {
+        return Ok(query);
+    }
+    Ok(Box::new(BooleanQuery::new(vec![
+        (Occur::Should, query),
+        (Occur::Should, substring_query),
+    ])))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 11:36:29
+/// @Description: 分面统计, 查询方式和search_doc_with_fallback一致
+/// jieba查询命中少于FALLBACK_MIN_HITS时，子串搜索的结果也参与统计
+pub fn search_facets_with_fallback(
+    index_obj: &Index,
+    query_str: &str,
+    text: &str,
+    exact_substring: bool,
+    options: &SearchOptions,
+) -> GlobalResult<Vec<FacetGroup>> {
+    if exact_substring {
+        return match substring_query(index_obj, text)? {
+            Some(query) => tantivy_facet::search_facets(index_obj, query.as_ref(), options),
+            None => Ok(vec![]),
+        };
+    }
+    let query = fallback_query(index_obj, query_str, text, options)?;
+    tantivy_facet::search_facets(index_obj, query.as_ref(), options)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-10 09:40:12
+/// @Description: 边输入边搜索: 已输入完整的词按普通查询，最后一个词按前缀匹配
+/// @param query_str: 查询语句(最后一个词之前的部分，jieba分词后的)，可以为空
+/// @param prefix: 最后一个词(前缀)
+pub fn search_prefix(
+    index_obj: &Index,
+    query_str: &str,
+    prefix: &str,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    let schema = index_obj.schema();
+    let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+
+    let mut sub_queries: Vec<(Occur, Box<dyn Query>)> = vec![];
+    if !query_str.trim().is_empty() {
+        let query_parser = QueryParser::for_index(index_obj, vec![title, body]);
+        let query = to_global_result(query_parser.parse_query(query_str))?;
+        sub_queries.push((Occur::Must, query));
+    }
+    if !prefix.trim().is_empty() {
+        //只有一个词的短语前缀查询即前缀查询
+        let mut prefix_queries: Vec<(Occur, Box<dyn Query>)> = vec![];
+        for field in [title, body] {
+            let prefix = analyze_prefix(index_obj, field, prefix)?;
+            prefix_queries.push((
+                Occur::Should,
+                Box::new(PhrasePrefixQuery::new(vec![Term::from_field_text(
+                    field, &prefix,
+                )])),
+            ));
+        }
+        sub_queries.push((Occur::Must, Box::new(BooleanQuery::new(prefix_queries))));
+    }
+    if sub_queries.is_empty() {
+        return Ok(vec![]);
+    }
+    let query = BooleanQuery::new(sub_queries);
+
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+    search_with_options(
+        searcher,
+        schema,
+        Box::new(query),
+        options,
+        page_num,
+        page_size,
+    )
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-10 10:15:36
+/// @Description: 自动补全: 从文件名、内容的词典中取以prefix开头的词，按文档频率从高到低排序
+/// @param limit: 最多返回的数量
+pub fn suggest_terms(index_obj: &Index, prefix: &str, limit: usize) -> GlobalResult<Vec<String>> {
+    if prefix.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let schema = index_obj.schema();
+    let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    //和建索引时一样小写、词干化
+    let title_prefix = analyze_prefix(index_obj, title, prefix)?;
+    let body_prefix = analyze_prefix(index_obj, body, prefix)?;
+    //词 -> (文档频率, 所在字段)
+    let mut doc_freqs: HashMap<String, (u64, Field)> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        for (field, prefix) in [(title, &title_prefix), (body, &body_prefix)] {
+            let inverted_index = to_global_result(segment_reader.inverted_index(field))?;
+            let mut stream = to_global_result(
+                inverted_index
+                    .terms()
+                    .range()
+                    .ge(prefix.as_bytes())
+                    .into_stream(),
+            )?;
+            while stream.advance() {
+                if !stream.key().starts_with(prefix.as_bytes()) {
+                    break;
+                }
+                //前缀本身不作为补全
+                if stream.key().len() == prefix.len() {
+                    continue;
+                }
+                if let Ok(term) = std::str::from_utf8(stream.key()) {
+                    doc_freqs.entry(term.to_string()).or_insert((0, field)).0 +=
+                        stream.value().doc_freq as u64;
+                }
+            }
+        }
+    }
+    let mut terms = doc_freqs
+        .into_iter()
+        .collect::<Vec<(String, (u64, Field))>>();
+    terms.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(&b.0)));
+
+    let mut suggestions: Vec<String> = vec![];
+    for (term, (_doc_freq, field)) in terms {
+        if suggestions.len() >= limit {
+            break;
+        }
+        //词干化后的词(databas)换成原文中的词(database)
+        let word = surface_word(&searcher, field, &term)?.unwrap_or(term);
+        if !suggestions.contains(&word) {
+            suggestions.push(word);
+        }
+    }
+    Ok(suggestions)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 17:02:33
+/// @Description: 前缀按字段的分析器处理(小写、词干化), 取最后一个词; 被过滤掉时用小写的原文
+fn analyze_prefix(index_obj: &Index, field: Field, prefix: &str) -> GlobalResult<String> {
+    let prefix = prefix.trim();
+    let mut analyzer = to_global_result(index_obj.tokenizer_for_field(field))?;
+    let mut token_stream = analyzer.token_stream(prefix);
+    let mut last: Option<String> = None;
+    while let Some(token) = token_stream.next() {
+        last = Some(token.text.clone());
+    }
+    Ok(last
+        .filter(|term| !term.is_empty())
+        .unwrap_or_else(|| prefix.to_lowercase()))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-10 11:02:50
+/// @Description: 取索引中的词在原文中的形式: 找一个包含该词的文档，取其中以该词开头的第一个单词
+pub fn surface_word(
+    searcher: &tantivy::Searcher,
+    field: Field,
+    term: &str,
+) -> GlobalResult<Option<String>> {
+    if !term
+        .chars()
+        .all(|c| c.is_alphanumeric() && !tantivy_jieba::is_cjk_char(c))
+    {
+        return Ok(None);
+    }
+    let query = TermQuery::new(Term::from_field_text(field, term), IndexRecordOption::Basic);
+    let top_docs = to_global_result(searcher.search(&query, &TopDocs::with_limit(1)))?;
+    for (_score, doc_address) in top_docs {
+        let tantivy_document: TantivyDocument = to_global_result(searcher.doc(doc_address))?;
+        for value in tantivy_document.get_all(field) {
+            let text = value.as_str().unwrap_or("");
+            let word = text
+                .split(|c: char| !c.is_alphanumeric() || tantivy_jieba::is_cjk_char(c))
+                .find(|word| word.to_lowercase().starts_with(term));
+            if let Some(word) = word {
+                return Ok(Some(word.to_lowercase()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-10 10:31:27
+/// @Description: 子串搜索: 按空白切分，每段在二元分词字段上做短语查询，所有段都要命中
+/// 少于2个字符的段无法用二元分词匹配，忽略
+pub fn search_substring(
+    index_obj: &Index,
+    text: &str,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    search_substring_with_options(
+        index_obj,
+        text,
+        &SearchOptions::default(),
+        page_num,
+        page_size,
+    )
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 10:36:52
+/// @Description: 子串搜索(排序、限定索引目录)
+pub fn search_substring_with_options(
+    index_obj: &Index,
+    text: &str,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    let query = match substring_query(index_obj, text)? {
+        Some(query) => query,
+        None => return Ok(vec![]),
+    };
+    let schema = index_obj.schema();
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+    search_with_options(searcher, schema, query, options, page_num, page_size)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 11:08:45
+/// @Description: 子串查询, 旧索引没有二元分词字段或没有可匹配的段时返回None
+pub fn substring_query(index_obj: &Index, text: &str) -> GlobalResult<Option<Box<dyn Query>>> {
+    let schema = index_obj.schema();
+    let ngram = match schema.get_field(FIELD_FILE_CONTENT_NGRAM) {
+        Ok(field) => field,
+        //旧索引没有该字段
+        Err(_) => return Ok(None),
+    };
+    let mut analyzer = to_global_result(
+        index_obj
+            .tokenizers()
+            .get(CJK_BIGRAM)
+            .ok_or("未注册二元分词器"),
+    )?;
+
+    let mut sub_queries: Vec<(Occur, Box<dyn Query>)> = vec![];
+    for part in text.split_whitespace() {
+        if part.chars().count() < 2 {
+            continue;
+        }
+        let mut terms: Vec<(usize, Term)> = vec![];
+        let mut token_stream = analyzer.token_stream(part);
+        while let Some(token) = token_stream.next() {
+            terms.push((token.position, Term::from_field_text(ngram, &token.text)));
+        }
+        let query: Box<dyn Query> = if terms.len() == 1 {
+            Box::new(TermQuery::new(
+                terms.remove(0).1,
+                IndexRecordOption::WithFreqs,
+            ))
+        } else {
+            Box::new(PhraseQuery::new_with_offset(terms))
+        };
+        sub_queries.push((Occur::Must, query));
+    }
+    if sub_queries.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Box::new(BooleanQuery::new(sub_queries))))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 15:02:18
+/// @Description: 文件中from_line到to_line的行(行号, 内容), 命中行在片段边界时取上下文用
+/// 取起始行号在范围内的片段和范围前的最后一个片段; 旧索引行号不能按范围查询时返回空
+pub fn search_lines(
+    index_obj: &Index,
+    file_path: &str,
+    from_line: u64,
+    to_line: u64,
+) -> GlobalResult<Vec<(u64, String)>> {
+    let schema = index_obj.schema();
+    let path = to_global_result(schema.get_field(FIELD_FILE_PATH))?;
+    match schema.get_field(FIELD_LINE) {
+        Ok(line) if schema.get_field_entry(line).is_fast() => {}
+        _ => return Ok(vec![]),
+    }
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    let path_term = Term::from_field_text(path, &file_path.replace("\\", "/"));
+    let lines_query = |lower: Bound<u64>, upper: Bound<u64>| {
+        BooleanQuery::new(vec![
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(path_term.clone(), IndexRecordOption::Basic)),
+            ),
+            (
+                Occur::Must,
+                Box::new(RangeQuery::new_u64_bounds(FIELD_LINE.into(), lower, upper)),
+            ),
+        ])
+    };
+    //起始行号在范围内的片段
+    let query = lines_query(Bound::Included(from_line), Bound::Included(to_line));
+    let mut doc_addresses = to_global_result(searcher.search(&query, &DocSetCollector))?
+        .into_iter()
+        .collect::<Vec<DocAddress>>();
+    //范围前的最后一个片段, 可能包含范围开头的行
+    let query = lines_query(Bound::Unbounded, Bound::Excluded(from_line));
+    let last = TopDocs::with_limit(1).order_by_fast_field::<u64>(FIELD_LINE, Order::Desc);
+    for (_line, doc_address) in to_global_result(searcher.search(&query, &last))? {
+        doc_addresses.push(doc_address);
+    }
+
+    let mut lines: Vec<(u64, String)> = vec![];
+    for doc_address in doc_addresses {
+        let tantivy_document: TantivyDocument = to_global_result(searcher.doc(doc_address))?;
+        let doc = to_index_doc(&tantivy_document, &schema);
+        for (i, text) in doc.file_content.lines().enumerate() {
+            let line = doc.line + i as u64;
+            if (from_line..=to_line).contains(&line) {
+                lines.push((line, text.to_string()));
+            }
+        }
+    }
+    //超长的行切成了多个片段, 只取第一段
+    lines.sort_by_key(|(line, _)| *line);
+    lines.dedup_by_key(|(line, _)| *line);
+    Ok(lines)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-22 10:05:49
+/// @Description: 查找相似文档(more like this), 用源文件的内容片段组成查询, 结果不包含源文件
+/// 结果按搜索选项筛选
+/// @param file_path: 源文件路径
+pub fn search_similar(
+    index_obj: &Index,
+    file_path: &str,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    let schema = index_obj.schema();
+    let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+    let path = to_global_result(schema.get_field(FIELD_FILE_PATH))?;
+
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+
+    //源文件的内容片段
+    let path_term = Term::from_field_text(path, &file_path.replace("\\", "/"));
+    let path_query = TermQuery::new(path_term.clone(), IndexRecordOption::Basic);
+    let source_docs = to_global_result(
+        searcher.search(&path_query, &TopDocs::with_limit(SIMILAR_SOURCE_MAX_CHUNKS)),
+    )?;
+    if source_docs.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut names: Vec<OwnedValue> = vec![];
+    let mut contents: Vec<OwnedValue> = vec![];
+    for (_score, doc_address) in source_docs {
+        let tantivy_document: TantivyDocument = to_global_result(searcher.doc(doc_address))?;
+        if names.is_empty() {
+            names.extend(tantivy_document.get_all(title).cloned());
+        }
+        contents.extend(tantivy_document.get_all(body).cloned());
+    }
+
+    let mlt_query = MoreLikeThisQuery::builder()
+        .with_min_doc_frequency(1)
+        .with_min_term_frequency(1)
+        .with_max_query_terms(50)
+        .with_min_word_length(2)
+        .with_document_fields(vec![(title, names), (body, contents)]);
+    let query = BooleanQuery::new(vec![
+        (Occur::Must, Box::new(mlt_query)),
+        (
+            Occur::MustNot,
+            Box::new(TermQuery::new(path_term, IndexRecordOption::Basic)),
+        ),
+    ]);
+    //按搜索选项筛选(索引目录、文件类型、元数据)
+    let query = filter_query(&schema, Box::new(query), options)?;
 
-    let query = to_global_result(query_parser.parse_query(query_str.as_str()))?;
     let top_doc = TopDocs::with_limit(page_size).and_offset((page_num - 1) * page_size);
-    // let top_doc = TopDocs::with_limit(10);
-    // 获取命中总数
-    // let hits_total = to_global_result(searcher.search(&query, &tantivy::collector::Count))?;
     let top_docs = to_global_result(searcher.search(&query, &top_doc))?;
+    let doc_addresses = top_docs
+        .into_iter()
+        .map(|(_, doc_address)| doc_address)
+        .collect();
+    to_index_docs(doc_addresses, searcher, schema)
+}
 
-    let vec: Vec<IndexDocument> = to_index_docs(top_docs, searcher, schema)?;
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 10:45:27
+/// @Description: 按选项过滤、排序并分页
+fn search_with_options(
+    searcher: Searcher,
+    schema: Schema,
+    query: Box<dyn Query>,
+    options: &SearchOptions,
+    page_num: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<IndexDocument>> {
+    let offset = (page_num - 1) * page_size;
+    let doc_addresses =
+        search_doc_addresses(&searcher, &schema, query, options, offset, page_size)?;
+    to_index_docs(doc_addresses, searcher, schema)
+}
 
-    Ok(vec)
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-13 10:12:36
+/// @Description: 按选项过滤、排序, 返回从offset开始的page_size个文档地址
+/// 按修改时间、大小、文件名、路径排序时用快速字段，旧索引没有该字段时按相关度
+pub fn search_doc_addresses(
+    searcher: &Searcher,
+    schema: &Schema,
+    query: Box<dyn Query>,
+    options: &SearchOptions,
+    offset: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<DocAddress>> {
+    let query = filter_query(schema, query, options)?;
+
+    let sort_field = match options.sort_by {
+        SortBy::Relevance => None,
+        SortBy::ModifiedTime => Some(FIELD_MODIFIED_TIME),
+        SortBy::FileName => Some(FIELD_FILE_NAME_SORT),
+        SortBy::FileSize => Some(FIELD_FILE_SIZE),
+        SortBy::FilePath => Some(FIELD_FILE_PATH),
+    }
+    .filter(|name| match schema.get_field(name) {
+        Ok(field) => schema.get_field_entry(field).is_fast(),
+        Err(_) => false,
+    });
+    let order = if options.desc {
+        Order::Desc
+    } else {
+        Order::Asc
+    };
+
+    let query = query.as_ref();
+    let doc_addresses = match (options.sort_by, sort_field) {
+        (SortBy::ModifiedTime, Some(name)) => search_capped(
+            searcher,
+            query,
+            |limit| TopDocs::with_limit(limit).order_by_fast_field::<i64>(name, order.clone()),
+            offset,
+            page_size,
+        )?,
+        (SortBy::FileSize, Some(name)) => search_capped(
+            searcher,
+            query,
+            |limit| TopDocs::with_limit(limit).order_by_fast_field::<u64>(name, order.clone()),
+            offset,
+            page_size,
+        )?,
+        //TopDocs取分数最大的, 升序时用Reverse
+        (_, Some(name)) if options.desc => search_capped(
+            searcher,
+            query,
+            |limit| {
+                TopDocs::with_limit(limit).custom_score(move |segment_reader: &SegmentReader| {
+                    let column = str_column(segment_reader, name);
+                    move |doc: DocId| column(doc)
+                })
+            },
+            offset,
+            page_size,
+        )?,
+        (_, Some(name)) => search_capped(
+            searcher,
+            query,
+            |limit| {
+                TopDocs::with_limit(limit).custom_score(move |segment_reader: &SegmentReader| {
+                    let column = str_column(segment_reader, name);
+                    move |doc: DocId| Reverse(column(doc))
+                })
+            },
+            offset,
+            page_size,
+        )?,
+        (_, None) => search_capped(searcher, query, TopDocs::with_limit, offset, page_size)?,
+    };
+    Ok(doc_addresses)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 17:45:20
+/// @Description: 先限制每个文件的表格行命中数, 再分页
+/// 表格每行是一个文档, 一个大表格会占满整页; 限制后不够一页时扩大取数范围重新搜索
+/// @param collector: 按数量创建收集器
+fn search_capped<S, C>(
+    searcher: &Searcher,
+    query: &dyn Query,
+    collector: impl Fn(usize) -> C,
+    offset: usize,
+    page_size: usize,
+) -> GlobalResult<Vec<DocAddress>>
+where
+    C: Collector<Fruit = Vec<(S, DocAddress)>>,
+{
+    let mut limit = (offset + page_size).max(1);
+    loop {
+        let top_docs = to_global_result(searcher.search(query, &collector(limit)))?;
+        let exhausted = top_docs.len() < limit;
+        let top_docs = cap_record_hits(searcher, top_docs);
+        if exhausted || top_docs.len() >= offset + page_size {
+            let top_docs = top_docs.into_iter().skip(offset).take(page_size);
+            return Ok(top_docs.map(|(_, doc_address)| doc_address).collect());
+        }
+        limit *= 2;
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 17:52:04
+/// @Description: 每个文件最多保留MAX_RECORD_HITS_PER_FILE个表格行, 其他文档不限制(保持原顺序)
+fn cap_record_hits<S>(searcher: &Searcher, top_docs: Vec<(S, DocAddress)>) -> Vec<(S, DocAddress)> {
+    //段 -> (是否表格行, 文件路径)
+    type SegmentColumns = (Option<Column<bool>>, Box<dyn Fn(DocId) -> String>);
+    let mut columns: HashMap<u32, SegmentColumns> = HashMap::new();
+    //文件路径 -> 已保留的行数
+    let mut record_hits: HashMap<String, usize> = HashMap::new();
+    top_docs
+        .into_iter()
+        .filter(|(_, doc_address)| {
+            let (record, path) = columns.entry(doc_address.segment_ord).or_insert_with(|| {
+                let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+                let record = segment_reader.fast_fields().bool(FIELD_RECORD).ok();
+                let path = str_column(segment_reader, FIELD_FILE_PATH);
+                (record, Box::new(path))
+            });
+            let is_record = record
+                .as_ref()
+                .and_then(|record| record.first(doc_address.doc_id))
+                .unwrap_or(false);
+            if !is_record {
+                return true;
+            }
+            let hits = record_hits.entry(path(doc_address.doc_id)).or_insert(0);
+            *hits += 1;
+            *hits <= MAX_RECORD_HITS_PER_FILE
+        })
+        .collect()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 11:15:32
+/// @Description: 加上筛选条件: 索引目录、分面、元数据
+/// 索引目录之间、同一分面的值之间为或, 不同条件之间为且; 旧索引没有分面字段时忽略该分面
+pub fn filter_query(
+    schema: &Schema,
+    query: Box<dyn Query>,
+    options: &SearchOptions,
+) -> GlobalResult<Box<dyn Query>> {
+    let mut sub_queries: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, query)];
+    if !options.index_dir_ids.is_empty() {
+        let index_dir_id = to_global_result(schema.get_field(FIELD_INDEX_DIR_ID))?;
+        let terms = options
+            .index_dir_ids
+            .iter()
+            .map(|id| Term::from_field_i64(index_dir_id, *id))
+            .collect::<Vec<Term>>();
+        sub_queries.push((Occur::Must, any_term_query(terms)));
+    }
+    let mut facet_terms: Vec<(FacetKind, Vec<Term>)> = vec![];
+    for (kind, value) in options.facet_filters.iter() {
+        let field = match schema.get_field(kind.field_name()) {
+            Ok(field) => field,
+            Err(_) => continue,
+        };
+        let term = Term::from_field_text(field, value);
+        match facet_terms.iter_mut().find(|(k, _)| k == kind) {
+            Some((_, terms)) => terms.push(term),
+            None => facet_terms.push((*kind, vec![term])),
+        }
+    }
+    for (_kind, terms) in facet_terms {
+        sub_queries.push((Occur::Must, any_term_query(terms)));
+    }
+    for query in tantivy_metadata::filter_queries(schema, &options.metadata_filters) {
+        sub_queries.push((Occur::Must, query));
+    }
+    if sub_queries.len() == 1 {
+        return Ok(sub_queries.remove(0).1);
+    }
+    Ok(Box::new(BooleanQuery::new(sub_queries)))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-20 11:20:07
+/// @Description: 命中任意一个词
+fn any_term_query(terms: Vec<Term>) -> Box<dyn Query> {
+    let sub_queries = terms
+        .into_iter()
+        .map(|term| {
+            (
+                Occur::Should,
+                Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>,
+            )
+        })
+        .collect::<Vec<_>>();
+    Box::new(BooleanQuery::new(sub_queries))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 11:08:40
+/// @Description: 字符串快速字段的取值函数, 没有值时为空字符串
+pub fn str_column(segment_reader: &SegmentReader, name: &str) -> impl Fn(DocId) -> String {
+    let column = segment_reader.fast_fields().str(name).ok().flatten();
+    move |doc: DocId| {
+        let mut value = String::new();
+        if let Some(column) = &column {
+            if let Some(ord) = column.term_ords(doc).next() {
+                let _ = column.ord_to_str(ord, &mut value);
+            }
+        }
+        value
+    }
 }
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-13 11:02:38
 /// @Description: 获取结果
 fn to_index_docs(
-    top_docs: Vec<(f32, tantivy::DocAddress)>,
-    searcher: tantivy::Searcher,
+    doc_addresses: Vec<DocAddress>,
+    searcher: Searcher,
     schema: Schema,
 ) -> Result<Vec<IndexDocument>, crate::sys::global::GlobalError> {
     let mut vec = Vec::new();
-    for (_score, doc_address) in top_docs {
-        let mut index_doc = IndexDocument::new();
-
+    for doc_address in doc_addresses {
         // let id = doc_address.doc_id.to_string();
 
         let tantivy_document: TantivyDocument = to_global_result(searcher.doc(doc_address))?;
-        let field_values = tantivy_document.field_values();
-        for field_value in field_values {
-            let field_name = schema.get_field_name(field_value.field());
+        vec.push(to_index_doc(&tantivy_document, &schema));
+    }
+    Ok(vec)
+}
 
-            match field_name {
-                FIELD_INDEX_DIR_ID => {
-                    index_doc.index_dir_id = field_value.value().as_i64().unwrap();
-                }
-                FIELD_FILE_ANME => {
-                    index_doc
-                        .file_name
-                        .push_str(field_value.value().as_str().unwrap_or(""));
-                }
-                FIELD_FILE_PATH => {
-                    index_doc
-                        .file_path
-                        .push_str(field_value.value().as_str().unwrap_or(""));
-                }
-                FIELD_FILE_CONTENT => {
-                    index_doc
-                        .file_content
-                        .push_str(field_value.value().as_str().unwrap_or(""));
-                }
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-17 10:05:12
+/// @Description: 索引文档转换
+pub fn to_index_doc(tantivy_document: &TantivyDocument, schema: &Schema) -> IndexDocument {
+    let mut index_doc = IndexDocument::new();
+    let field_values = tantivy_document.field_values();
+    for field_value in field_values {
+        let field_name = schema.get_field_name(field_value.field());
 
-                _ => {}
+        match field_name {
+            FIELD_INDEX_DIR_ID => {
+                index_doc.index_dir_id = field_value.value().as_i64().unwrap();
+            }
+            FIELD_FILE_ANME => {
+                index_doc
+                    .file_name
+                    .push_str(field_value.value().as_str().unwrap_or(""));
+            }
+            FIELD_FILE_PATH => {
+                index_doc
+                    .file_path
+                    .push_str(field_value.value().as_str().unwrap_or(""));
             }
+            FIELD_FILE_CONTENT => {
+                index_doc
+                    .file_content
+                    .push_str(field_value.value().as_str().unwrap_or(""));
+            }
+            FIELD_MODIFIED_TIME => {
+                index_doc.modified_time = field_value.value().as_i64().unwrap_or(0);
+            }
+            FIELD_FILE_SIZE => {
+                index_doc.file_size = field_value.value().as_u64().unwrap_or(0);
+            }
+            FIELD_TITLE => {
+                index_doc.metadata.title = field_value.value().as_str().unwrap_or("").into();
+            }
+            FIELD_AUTHOR => {
+                index_doc.metadata.author = field_value.value().as_str().unwrap_or("").into();
+            }
+            FIELD_SUBJECT => {
+                index_doc.metadata.subject = field_value.value().as_str().unwrap_or("").into();
+            }
+            FIELD_KEYWORDS => {
+                index_doc.metadata.keywords = field_value.value().as_str().unwrap_or("").into();
+            }
+            FIELD_APPLICATION => {
+                index_doc.metadata.application = field_value.value().as_str().unwrap_or("").into();
+            }
+            FIELD_CREATED_TIME => {
+                index_doc.metadata.created_time = field_value.value().as_i64().unwrap_or(0);
+            }
+            FIELD_DOC_MODIFIED_TIME => {
+                index_doc.metadata.modified_time = field_value.value().as_i64().unwrap_or(0);
+            }
+            FIELD_PAGE_COUNT => {
+                index_doc.metadata.page_count = field_value.value().as_u64().unwrap_or(0);
+            }
+            FIELD_LOCATION => {
+                index_doc
+                    .location
+                    .push_str(field_value.value().as_str().unwrap_or(""));
+            }
+            FIELD_RECORD => {
+                index_doc.record = field_value.value().as_bool().unwrap_or(false);
+            }
+            FIELD_LINE => {
+                index_doc.line = field_value.value().as_u64().unwrap_or(0);
+            }
+
+            _ => {}
         }
-        vec.push(index_doc);
     }
-    Ok(vec)
+    index_doc
 }
 
 /// @Author: DengLibin
@@ -323,3 +1452,18 @@ pub fn delete_by_index_dir_id(index_obj: &mut Index, index_dir_id: i64) -> Globa
     let _r = to_global_result(index_writer.commit())?; // 必须提交以使删除生效
     Ok(())
 }
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-13 10:05:32
+/// @Description: 索引维护: 合并所有段(清除已删除的文档)，回收不再使用的文件
+/// 只需要共享引用, 合并期间可以继续搜索(同一时间只能有一个写入器)
+pub fn optimize_index(index_obj: &Index) -> GlobalResult<()> {
+    let mut index_writer: tantivy::IndexWriter = to_global_result(index_obj.writer(50_000_000))?;
+    let segment_ids = to_global_result(index_obj.searchable_segment_ids())?;
+    if !segment_ids.is_empty() {
+        to_global_result(index_writer.merge(&segment_ids).wait())?;
+    }
+    to_global_result(index_writer.garbage_collect_files().wait())?;
+    to_global_result(index_writer.wait_merging_threads())?;
+    Ok(())
+}
@@ -0,0 +1,308 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-12 10:05:26
+//! @Description: 拼写纠错(你是不是要找)
+//!
+//! 搜索没有结果时，从索引词典中找相近的词替换没有命中的词:
+//! 拉丁文按编辑距离(长度相近的词)，中文按拼音(字数相同、拼音相同或相差一个字母)。
+//! 只返回替换后确实有结果的查询。
+//! 词典(词、拼音)按索引的段缓存, 索引变化后重新加载, 不用每次查询都遍历词典、转拼音。
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use lazy_static::lazy_static;
+use pinyin::ToPinyin;
+use tantivy::{schema::Field, Index, Opstamp, Searcher, SegmentId, Term};
+
+use crate::{
+    sys::global::{to_global_result, GlobalResult},
+    util::text_utils,
+};
+
+use super::{
+    tantivy_jieba,
+    tantivy_search::{self, FIELD_FILE_ANME, FIELD_FILE_CONTENT},
+};
+
+//每个词最多取几个候选词
+const MAX_CANDIDATES: usize = 3;
+
+lazy_static! {
+    //最近一次使用的纠错词典
+    static ref SPELL_DICT: RwLock<Option<Arc<SpellDict>>> = RwLock::new(None);
+}
+
+/// 纠错词典中的词
+struct DictTerm {
+    term: String,  //索引中的词(小写、词干化)
+    chars: usize,  //字符数
+    doc_freq: u64, //各字段、各段的文档频率之和
+    field: Field,  //所在字段(取原文形式用)
+}
+
+/// 纠错词典, 按索引的段(段id和删除的操作号)缓存
+struct SpellDict {
+    segments: BTreeMap<SegmentId, Option<Opstamp>>, //加载时的段
+    latin: Vec<DictTerm>,                           //拉丁文的词
+    cjk: Vec<(DictTerm, String)>,                   //中文的词和拼音
+    pinyin: HashMap<String, Vec<usize>>, //拼音及去掉一个字母后的拼音 -> 中文的词(cjk中的下标)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 10:12:40
+/// @Description: 纠错建议, 返回替换后有结果的查询文本
+/// @param text: 用户输入的原始文本
+/// @param limit: 最多返回的数量
+pub fn did_you_mean(index_obj: &Index, text: &str, limit: usize) -> GlobalResult<Vec<String>> {
+    let schema = index_obj.schema();
+    let title = to_global_result(schema.get_field(FIELD_FILE_ANME))?;
+    let body = to_global_result(schema.get_field(FIELD_FILE_CONTENT))?;
+    let fields = [title, body];
+
+    let index_reader = to_global_result(index_obj.reader())?;
+    let searcher = index_reader.searcher();
+    let mut analyzer = to_global_result(index_obj.tokenizer_for_field(body))?;
+
+    //(原词, 候选词)
+    let mut corrections: Vec<(String, Vec<String>)> = vec![];
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut dict: Option<Arc<SpellDict>> = None;
+    for token in tantivy_jieba::tokenize(text) {
+        //重复的词只纠错一次
+        if token.trim().is_empty() || !seen.insert(token.clone()) {
+            continue;
+        }
+        //分析后的词(小写、词干化)
+        let mut terms: Vec<String> = vec![];
+        {
+            let mut token_stream = analyzer.token_stream(&token);
+            while let Some(t) = token_stream.next() {
+                terms.push(t.text.clone());
+            }
+        }
+        if terms.len() != 1 {
+            continue;
+        }
+        let term = terms.remove(0);
+        if doc_freq(&searcher, &fields, &term)? > 0 {
+            continue;
+        }
+        //有需要纠错的词时才加载词典
+        let dict = match dict.as_ref() {
+            Some(dict) => dict.clone(),
+            None => dict.insert(spell_dict(&searcher, &fields)?).clone(),
+        };
+        let candidates = if term.chars().all(tantivy_jieba::is_cjk_char) {
+            pinyin_candidates(&searcher, &dict, &term)?
+        } else if term.chars().all(|c| c.is_alphanumeric()) {
+            edit_candidates(&searcher, &dict, &term)?
+        } else {
+            vec![]
+        };
+        if !candidates.is_empty() {
+            corrections.push((token, candidates));
+        }
+    }
+    if corrections.is_empty() {
+        return Ok(vec![]);
+    }
+
+    //第k个建议: 每个词(所有出现的地方)用第k个候选词(不足时用最后一个)
+    let mut suggestions: Vec<String> = vec![];
+    for k in 0..MAX_CANDIDATES {
+        if suggestions.len() >= limit {
+            break;
+        }
+        let mut corrected = text.to_string();
+        for (token, candidates) in corrections.iter() {
+            let candidate = &candidates[k.min(candidates.len() - 1)];
+            corrected = corrected.replace(token.as_str(), candidate);
+        }
+        if suggestions.contains(&corrected) {
+            continue;
+        }
+        let query_str = tantivy_search::and_query_str(&tantivy_jieba::tokenize(&corrected));
+        let docs = tantivy_search::search_doc(index_obj, &query_str, 1, 1)?;
+        if !docs.is_empty() {
+            suggestions.push(corrected);
+        }
+    }
+    Ok(suggestions)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 10:40:03
+/// @Description: 词在各字段中的文档频率之和
+fn doc_freq(searcher: &Searcher, fields: &[Field], term: &str) -> GlobalResult<u64> {
+    let mut count = 0;
+    for field in fields {
+        count += to_global_result(searcher.doc_freq(&Term::from_field_text(*field, term)))?;
+    }
+    Ok(count)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 10:48:55
+/// @Description: 按编辑距离找候选词(长度相差不超过最大距离的词, 首字母也可以不同)
+fn edit_candidates(searcher: &Searcher, dict: &SpellDict, term: &str) -> GlobalResult<Vec<String>> {
+    let term_len = term.chars().count();
+    let max_distance = if term_len <= 4 { 1 } else { 2 };
+    let candidates = dict
+        .latin
+        .iter()
+        .filter(|t| t.chars.abs_diff(term_len) <= max_distance)
+        .filter_map(|t| {
+            let distance = text_utils::edit_distance(term, &t.term);
+            (distance <= max_distance).then_some((t, distance))
+        });
+    surface_words(searcher, candidates)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 11:10:37
+/// @Description: 按拼音找候选词(同音字、拼音相差一个字母)
+/// 拼音相差一个字母的两个词, 去掉一个字母后有相同的拼音, 按拼音及去掉一个字母后的拼音查找
+fn pinyin_candidates(
+    searcher: &Searcher,
+    dict: &SpellDict,
+    term: &str,
+) -> GlobalResult<Vec<String>> {
+    let term_pinyin = match to_pinyin(term) {
+        Some(p) => p,
+        None => return Ok(vec![]),
+    };
+    let term_len = term.chars().count();
+    let mut indexes = deletions(&term_pinyin)
+        .iter()
+        .filter_map(|key| dict.pinyin.get(key))
+        .flatten()
+        .copied()
+        .collect::<Vec<usize>>();
+    indexes.sort_unstable();
+    indexes.dedup();
+    let candidates = indexes.into_iter().filter_map(|i| {
+        let (t, pinyin) = &dict.cjk[i];
+        if t.chars != term_len {
+            return None;
+        }
+        let distance = text_utils::edit_distance(&term_pinyin, pinyin);
+        (distance <= 1).then_some((t, distance))
+    });
+    surface_words(searcher, candidates)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 19:10:25
+/// @Description: 纠错词典, 索引的段没有变化时用缓存, 否则遍历词典重新加载
+fn spell_dict(searcher: &Searcher, fields: &[Field]) -> GlobalResult<Arc<SpellDict>> {
+    let segments = searcher.generation().segments();
+    if let Some(dict) = SPELL_DICT.read().unwrap().as_ref() {
+        if dict.segments == *segments {
+            return Ok(dict.clone());
+        }
+    }
+
+    //词 -> (文档频率, 所在字段)
+    let mut doc_freqs: HashMap<String, (u64, Field)> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        for field in fields {
+            let inverted_index = to_global_result(segment_reader.inverted_index(*field))?;
+            let mut stream = to_global_result(inverted_index.terms().stream())?;
+            while stream.advance() {
+                let key = match std::str::from_utf8(stream.key()) {
+                    Ok(key) => key,
+                    Err(_) => continue,
+                };
+                let is_cjk = key.chars().all(tantivy_jieba::is_cjk_char);
+                if is_cjk || key.chars().all(|c| c.is_alphanumeric()) {
+                    doc_freqs.entry(key.to_string()).or_insert((0, *field)).0 +=
+                        stream.value().doc_freq as u64;
+                }
+            }
+        }
+    }
+
+    let mut dict = SpellDict {
+        segments: segments.clone(),
+        latin: vec![],
+        cjk: vec![],
+        pinyin: HashMap::new(),
+    };
+    for (term, (doc_freq, field)) in doc_freqs {
+        let dict_term = DictTerm {
+            chars: term.chars().count(),
+            term,
+            doc_freq,
+            field,
+        };
+        if !dict_term.term.chars().all(tantivy_jieba::is_cjk_char) {
+            dict.latin.push(dict_term);
+            continue;
+        }
+        let pinyin = match to_pinyin(&dict_term.term) {
+            Some(pinyin) => pinyin,
+            None => continue,
+        };
+        for key in deletions(&pinyin) {
+            dict.pinyin.entry(key).or_default().push(dict.cjk.len());
+        }
+        dict.cjk.push((dict_term, pinyin));
+    }
+    let dict = Arc::new(dict);
+    *SPELL_DICT.write().unwrap() = Some(dict.clone());
+    Ok(dict)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 19:22:48
+/// @Description: 拼音本身及去掉一个字母后的拼音(去重)
+fn deletions(pinyin: &str) -> Vec<String> {
+    let mut keys = vec![pinyin.to_string()];
+    for (i, c) in pinyin.char_indices() {
+        let key = format!("{}{}", &pinyin[..i], &pinyin[i + c.len_utf8()..]);
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 11:40:51
+/// @Description: 候选词按距离从小到大、文档频率从高到低排序, 取原文中的形式
+fn surface_words<'a>(
+    searcher: &Searcher,
+    candidates: impl Iterator<Item = (&'a DictTerm, usize)>,
+) -> GlobalResult<Vec<String>> {
+    let mut candidates = candidates.collect::<Vec<(&DictTerm, usize)>>();
+    candidates.sort_by(|a, b| {
+        a.1.cmp(&b.1)
+            .then(b.0.doc_freq.cmp(&a.0.doc_freq))
+            .then(a.0.term.cmp(&b.0.term))
+    });
+    let mut words: Vec<String> = vec![];
+    for (t, _distance) in candidates {
+        if words.len() >= MAX_CANDIDATES {
+            break;
+        }
+        let word = tantivy_search::surface_word(searcher, t.field, &t.term)?
+            .unwrap_or_else(|| t.term.clone());
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    Ok(words)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 11:52:26
+/// @Description: 拼音(不带声调), 有不是汉字的字符时返回None
+fn to_pinyin(text: &str) -> Option<String> {
+    let mut s = String::new();
+    for c in text.chars() {
+        s.push_str(c.to_pinyin()?.plain());
+    }
+    Some(s)
+}
@@ -0,0 +1,142 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-14 09:18:36
+//! @Description: 同义词扩展(查询时)
+//!
+//! 同义词文件: 数据目录下synonyms.txt, 每行一组, 逗号分隔, 如: 合同,协议 / PO,purchase order, #开头为注释
+//! 查询时把每个词扩展成 (原词^2 OR 同义词1 OR 同义词2)，原词权重高于同义词。
+
+use std::{collections::HashMap, fs, path::Path, sync::RwLock};
+
+use lazy_static::lazy_static;
+use log::info;
+
+use crate::sys::global::{to_global_result, GlobalResult};
+
+lazy_static! {
+    //小写的词 -> 同组的其他词
+    static ref SYNONYMS: RwLock<HashMap<String, Vec<String>>> = RwLock::new(HashMap::new());
+}
+
+//同义词文件(数据目录下)
+pub const SYNONYMS_FILE: &str = "synonyms.txt";
+//原词的权重
+const ORIGINAL_BOOST: &str = "2";
+//多个分词组成的同义词(如 purchase order)最多包含的分词数
+const MAX_PHRASE_TOKENS: usize = 4;
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 09:30:50
+/// @Description: 加载同义词，文件不存在时清空
+pub fn load_synonyms(data_dir: &str) -> GlobalResult<()> {
+    let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+    let synonyms_file = format!("{}/{}", data_dir, SYNONYMS_FILE);
+    if Path::new(&synonyms_file).is_file() {
+        let content = to_global_result(fs::read_to_string(&synonyms_file))?;
+        for line in content.lines() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            //引号会破坏查询语句
+            let group = line
+                .split([',', '，'])
+                .map(|word| word.replace('"', "").trim().to_string())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<String>>();
+            if group.len() < 2 {
+                continue;
+            }
+            for word in group.iter() {
+                let others = synonyms.entry(word.to_lowercase()).or_default();
+                for other in group.iter() {
+                    if other != word && !others.contains(other) {
+                        others.push(other.clone());
+                    }
+                }
+            }
+        }
+        info!("加载同义词数量:{}", synonyms.len());
+    }
+    *SYNONYMS.write().unwrap() = synonyms;
+    Ok(())
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 11:05:44
+/// @Description: 同义词文件路径, 不存在时创建(带说明)
+pub fn synonyms_file(data_dir: &str) -> GlobalResult<String> {
+    let synonyms_file = format!("{}/{}", data_dir, SYNONYMS_FILE);
+    if !Path::new(&synonyms_file).is_file() {
+        to_global_result(fs::write(
+            &synonyms_file,
+            "# 每行一组同义词, 逗号分隔, 如:\n# 合同,协议\n# PO,purchase order\n",
+        ))?;
+    }
+    Ok(synonyms_file)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 09:52:17
+/// @Description: 分词结果拼成查询语句(所有词都要命中), 有同义词的词扩展成OR组
+pub fn expand_query(tokens: &[String]) -> String {
+    match_synonyms(tokens)
+        .into_iter()
+        .map(|(word, others)| {
+            if others.is_empty() {
+                return word;
+            }
+            let mut group = vec![format!("\"{}\"^{}", word, ORIGINAL_BOOST)];
+            for other in others {
+                group.push(format!("\"{}\"", other));
+            }
+            format!("({})", group.join(" OR "))
+        })
+        .collect::<Vec<String>>()
+        .join(" AND ")
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 10:20:05
+/// @Description: 分词结果中的词的同义词(用于高亮)
+pub fn synonyms_of(tokens: &[String]) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    for (_word, others) in match_synonyms(tokens) {
+        for other in others {
+            if !words.contains(&other) {
+                words.push(other);
+            }
+        }
+    }
+    words
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-14 10:02:41
+/// @Description: 查找每个词的同义词, 返回(词, 同义词), 忽略空白
+/// 相邻的几个分词合起来是同义词时(如 purchase order)作为一个词, 取最长的
+fn match_synonyms(tokens: &[String]) -> Vec<(String, Vec<String>)> {
+    let synonyms = SYNONYMS.read().unwrap();
+    let tokens = tokens
+        .iter()
+        .filter(|token| !token.trim().is_empty())
+        .map(|token| token.as_str())
+        .collect::<Vec<&str>>();
+
+    let mut matched: Vec<(String, Vec<String>)> = vec![];
+    let mut i = 0;
+    'outer: while i < tokens.len() {
+        let max_end = (i + MAX_PHRASE_TOKENS).min(tokens.len());
+        for end in (i + 1..=max_end).rev() {
+            let words = &tokens[i..end];
+            for word in [words.join(" "), words.join("")] {
+                if let Some(others) = synonyms.get(&word.to_lowercase()) {
+                    matched.push((word, others.clone()));
+                    i = end;
+                    continue 'outer;
+                }
+            }
+        }
+        matched.push((tokens[i].to_string(), vec![]));
+        i += 1;
+    }
+    matched
+}
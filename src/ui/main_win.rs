@@ -4,7 +4,7 @@
 
 use std::{
     sync::{Arc, RwLock},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use eframe::egui;
@@ -23,26 +23,54 @@ use tantivy::Index;
 use tokio::{sync::mpsc, time::sleep};
 
 use crate::{
-    app::{self, get_data_dir, init_log},
-    component::cmd::open_folder_and_select_file,
+    app::{self, init_log},
+    component::{
+        cmd::open_folder_and_select_file,
+        snapshot::{self, SnapshotManifest},
+    },
     dao::{
         files_dao::{self, IndexFile},
         index_dir_dao, sqlite_dao,
     },
     file_scanner::{
+        doc_metadata::DocMetadata,
+        extract_worker,
         file_extractor::{self, extract_file},
-        file_text_extractor::{self, FileText},
+        file_text_extractor::{self, FileText, TextExtractParam},
+        ocr,
     },
     sys::global::{GlobalError, GlobalResult},
-    tantivy::{tantivy_jieba, tantivy_search},
+    tantivy::{
+        tantivy_explain::{self, QueryDebug},
+        tantivy_facet::{self, FacetKind},
+        tantivy_jieba, tantivy_metadata,
+        tantivy_regex::{self, RegexSearchResult},
+        tantivy_search, tantivy_spell, tantivy_synonym,
+    },
     ui::ui_global::load_global_font,
-    util::text_utils,
+    util::{fingerprint, text_utils},
 };
 //窗口宽高
 const WIN_WIDTH: f32 = 1050.0;
 const WIN_HEIGHT: f32 = 720.0;
 
 const LETF_WIDTH: f32 = 280.0;
+//边输入边搜索的延迟
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+//自动补全的数量
+const SUGGESTION_COUNT: usize = 8;
+//纠错建议的数量
+const SPELLING_SUGGESTION_COUNT: usize = 3;
+//分面栏宽度
+const FACET_WIDTH: f32 = 170.0;
+//结果中最多显示的命中位置数量
+const MAX_SHOWN_LOCATIONS: usize = 5;
+//结果中最多显示的命中表格行数量
+const MAX_SHOWN_ROWS: usize = 5;
+//结果中最多显示的命中行数量(文本文件)
+const MAX_SHOWN_LINE_HITS: usize = 3;
+//命中行前后显示的行数
+const LINE_HIT_CONTEXT: usize = 1;
 
 /// @Author: DengLibin
 /// @Date: Create in 2024-12-16 09:48:39
@@ -102,10 +130,65 @@ pub fn show_win(runtime: tokio::runtime::Runtime) -> Result<(), eframe::Error> {
 }
 
 struct MyFile {
-    path: String,    //路径
-    name: String,    //文件名
-    is_file: bool,   //是否文件
-    content: String, //文件内容
+    path: String,                        //路径
+    name: String,                        //文件名
+    is_file: bool,                       //是否文件
+    content: String,                     //文件内容
+    dup_count: usize,                    //折叠的相似文件数量
+    modified_time: i64,                  //修改时间(毫秒)
+    file_size: u64,                      //文件大小
+    metadata: DocMetadata,               //文档元数据
+    locations: Vec<String>,              //命中的内容片段所在位置(第47页、Sheet2等), 按相关度排序
+    rows: Vec<(String, String)>,         //命中的表格行(位置, 按表头展示的内容), 按相关度排序
+    line_chunks: Vec<(u64, String)>,     //命中的按行切分的片段(起始行号, 内容), 按相关度排序
+    line_hits: Vec<text_utils::LineHit>, //从line_chunks中取的命中行和上下文
+}
+
+impl From<tantivy_search::IndexDocument> for MyFile {
+    /// 搜索结果, 表格的行、按行切分的片段单独显示, 不参与摘要
+    fn from(doc: tantivy_search::IndexDocument) -> Self {
+        let mut my_file = MyFile {
+            is_file: true,
+            name: doc.file_name,
+            path: doc.file_path,
+            content: "".into(),
+            dup_count: 0,
+            modified_time: doc.modified_time,
+            file_size: doc.file_size,
+            metadata: doc.metadata,
+            locations: vec![],
+            rows: vec![],
+            line_chunks: vec![],
+            line_hits: vec![],
+        };
+        if doc.record {
+            my_file.rows.push((doc.location, doc.file_content));
+        } else if doc.line > 0 {
+            my_file.line_chunks.push((doc.line, doc.file_content));
+        } else {
+            my_file.content = doc.file_content;
+            my_file.locations.push(doc.location);
+        }
+        my_file
+    }
+}
+
+/// 边输入边搜索的结果: (补全的词, 搜索结果)
+type PrefixSearchResult = (
+    Vec<String>,
+    GlobalResult<Vec<tantivy_search::IndexDocument>>,
+);
+
+/// 提取失败的文件
+struct FailedFile {
+    path: String,  //文件路径
+    error: String, //失败原因
+}
+
+/// 重复文件组
+struct DuplicateGroup {
+    exact: bool,        //内容完全相同
+    paths: Vec<String>, //文件路径
 }
 
 struct MyApp {
@@ -130,6 +213,34 @@ struct MyApp {
     tantivy_index: Arc<RwLock<Index>>,                    //索引
     scaning_count: i32,                                   //扫描中文件夹数量
     file_count: i64,                                      //文件总数
+    need_rebuild: bool,                                   //词典已变更，需要重建索引
+    exact_substring: bool,                                //精确子串搜索
+    maintaining: bool,                                    //索引维护中
+    index_size: u64,                                      //索引占用磁盘大小
+    db_size: u64,                                         //数据库占用磁盘大小
+    workspace: String,                                    //当前工作区
+    data_dir: String,                                     //当前工作区的数据目录
+    workspaces: Vec<String>,                              //所有工作区
+    show_new_workspace: bool,                             //是否显示新建工作区窗口
+    new_workspace_name: String,                           //新建工作区名称
+    collapse_similar: bool,                               //折叠相似的搜索结果
+    duplicate_groups: Vec<DuplicateGroup>,                //重复文件
+    show_duplicates: bool,                                //是否显示重复文件窗口
+    failed_files: Vec<FailedFile>,                        //提取失败的文件
+    show_failed_files: bool,                              //是否显示提取失败的文件窗口
+    snapshot_import: Option<std::sync::mpsc::Receiver<GlobalResult<SnapshotManifest>>>, //解压中的快照
+    regex_search: Option<std::sync::mpsc::Receiver<GlobalResult<RegexSearchResult>>>, //进行中的正则搜索
+    last_edit_time: Option<Instant>, //最后一次修改搜索框的时间
+    prefix_search: Option<std::sync::mpsc::Receiver<PrefixSearchResult>>, //进行中的边输入边搜索
+    suggestions: Vec<String>,        //自动补全的词
+    spelling_suggestions: Vec<String>, //纠错建议
+    regex_mode: bool,                //正则搜索
+    search_options: tantivy_search::SearchOptions, //排序、限定索引目录
+    facet_groups: Vec<tantivy_facet::FacetGroup>, //分面统计
+    show_debug: bool,                //是否显示查询调试窗口
+    query_debug: QueryDebug,         //查询调试信息
+    explain_path: String,            //打分解释的文件
+    explanation: String,             //打分解释
 }
 
 impl eframe::App for MyApp {
@@ -143,18 +254,31 @@ impl eframe::App for MyApp {
 
 impl MyApp {
     fn new(runtime: tokio::runtime::Runtime) -> Self {
-        let data_dir: String = app::get_data_dir();
+        let data_dir: String = app::get_data_dir().unwrap();
         let db_path = format!("sqlite:{}/mysearch.db", data_dir);
         let sqlite_pool =
             runtime.block_on(async { sqlite_dao::get_conn_pool(&db_path).await.unwrap() });
 
+        //用户词典
+        if let Err(e) = tantivy_jieba::load_user_dict(&data_dir) {
+            error!("加载用户词典异常:{}", e);
+        }
+        if let Err(e) = tantivy_synonym::load_synonyms(&data_dir) {
+            error!("加载同义词异常:{}", e);
+        }
+        if let Err(e) = ocr::load_config(&data_dir) {
+            error!("加载OCR配置异常:{}", e);
+        }
+        if let Err(e) = extract_worker::load_config(&data_dir) {
+            error!("加载提取子进程配置异常:{}", e);
+        }
+
         let (tx, rx) = std::sync::mpsc::channel::<String>();
         let arc_tx: Arc<std::sync::mpsc::Sender<String>> = Arc::new(tx);
         let arc_rx: Arc<std::sync::mpsc::Receiver<String>> = Arc::new(rx);
 
         //索引
-        let index =
-            tantivy_search::open_index(format!("{}/indices", get_data_dir()).as_str()).unwrap();
+        let index = tantivy_search::open_index(format!("{}/indices", data_dir).as_str()).unwrap();
         let arc_index = Arc::new(RwLock::new(index));
 
         let mut my_app: MyApp = Self {
@@ -185,6 +309,34 @@ impl MyApp {
             tantivy_index: arc_index,
             scaning_count: 0,
             file_count: 0_i64,
+            need_rebuild: false,
+            exact_substring: false,
+            maintaining: false,
+            index_size: 0,
+            db_size: 0,
+            workspace: app::current_workspace(),
+            data_dir,
+            workspaces: app::list_workspaces(),
+            show_new_workspace: false,
+            new_workspace_name: "".into(),
+            collapse_similar: false,
+            duplicate_groups: vec![],
+            show_duplicates: false,
+            failed_files: vec![],
+            show_failed_files: false,
+            snapshot_import: None,
+            regex_search: None,
+            last_edit_time: None,
+            prefix_search: None,
+            suggestions: vec![],
+            spelling_suggestions: vec![],
+            regex_mode: false,
+            search_options: tantivy_search::SearchOptions::default(),
+            facet_groups: vec![],
+            show_debug: false,
+            query_debug: QueryDebug::default(),
+            explain_path: "".into(),
+            explanation: "".into(),
         };
         my_app.init().unwrap();
         my_app
@@ -194,7 +346,18 @@ impl MyApp {
      *界面
      */
     fn my_ui(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.snapshot_import.is_some() {
+            self.finish_import_snapshot();
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+        if self.regex_search.is_some() && !self.finish_search_regex() {
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
         self.tip_ui(ctx);
+        self.new_workspace_ui(ctx);
+        self.duplicates_ui(ctx);
+        self.failed_files_ui(ctx);
+        self.debug_ui(ctx);
         //菜单
         self.menu_ui(ctx);
         //界面
@@ -207,7 +370,98 @@ impl MyApp {
     fn menu_ui(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
+                let enabled = self.scaning_count == 0 && !self.maintaining;
+                ui.menu_button(format!("工作区({})", self.workspace), |ui| {
+                    let mut selected: Option<String> = None;
+                    for workspace in self.workspaces.iter() {
+                        let checked = *workspace == self.workspace;
+                        let res = ui.add_enabled(
+                            enabled,
+                            egui::SelectableLabel::new(checked, workspace.as_str()),
+                        );
+                        if res.clicked() {
+                            selected = Some(workspace.clone());
+                        }
+                    }
+                    ui.separator();
+                    if ui.add_enabled(enabled, Button::new("新建工作区")).clicked() {
+                        self.show_new_workspace = true;
+                        ui.close_menu();
+                    }
+                    if let Some(workspace) = selected {
+                        ui.close_menu();
+                        self.switch_workspace(&workspace);
+                    }
+                });
+                ui.menu_button("数据", |ui| {
+                    if ui.add_enabled(enabled, Button::new("导出快照")).clicked() {
+                        ui.close_menu();
+                        self.export_snapshot();
+                    }
+                    if ui.add_enabled(enabled, Button::new("导入快照")).clicked() {
+                        ui.close_menu();
+                        self.import_snapshot();
+                    }
+                    ui.separator();
+                    if ui.button("编辑同义词").clicked() {
+                        ui.close_menu();
+                        match tantivy_synonym::synonyms_file(&self.data_dir) {
+                            Ok(file) => app::open_file(&file),
+                            Err(e) => error!("创建同义词文件异常:{}", e),
+                        }
+                    }
+                    if ui.button("重新加载同义词").clicked() {
+                        ui.close_menu();
+                        match tantivy_synonym::load_synonyms(&self.data_dir) {
+                            Ok(()) => self.msg = "同义词已重新加载".into(),
+                            Err(e) => self.show_tips(&mut vec![format!("加载同义词异常:{}", e)]),
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("编辑OCR配置").clicked() {
+                        ui.close_menu();
+                        match ocr::config_file(&self.data_dir) {
+                            Ok(file) => app::open_file(&file),
+                            Err(e) => error!("创建OCR配置文件异常:{}", e),
+                        }
+                    }
+                    if ui.button("重新加载OCR配置").clicked() {
+                        ui.close_menu();
+                        match ocr::load_config(&self.data_dir) {
+                            Ok(()) => self.msg = "OCR配置已重新加载".into(),
+                            Err(e) => self.show_tips(&mut vec![format!("加载OCR配置异常:{}", e)]),
+                        }
+                    }
+                    if ui.button("编辑提取配置").clicked() {
+                        ui.close_menu();
+                        match extract_worker::config_file(&self.data_dir) {
+                            Ok(file) => app::open_file(&file),
+                            Err(e) => error!("创建提取配置文件异常:{}", e),
+                        }
+                    }
+                    if ui.button("重新加载提取配置").clicked() {
+                        ui.close_menu();
+                        match extract_worker::load_config(&self.data_dir) {
+                            Ok(()) => self.msg = "提取配置已重新加载".into(),
+                            Err(e) => self.show_tips(&mut vec![format!("加载提取配置异常:{}", e)]),
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("重复文件").clicked() {
+                        ui.close_menu();
+                        self.find_duplicates();
+                    }
+                    if ui.button("提取失败的文件").clicked() {
+                        ui.close_menu();
+                        self.find_failed_files();
+                    }
+                });
                 ui.menu_button("帮助", |ui| {
+                    if ui.button("查询调试").clicked() {
+                        ui.close_menu();
+                        self.show_debug = true;
+                        self.refresh_query_debug();
+                    }
                     if ui.button("关于").clicked() {
                         self.show_tips(&mut vec![
                             "使用: 添加文件夹，自动创建索引，完成后即可搜索".to_string(),
@@ -264,9 +518,17 @@ impl MyApp {
                 if msg.contains("创建索引完成") {
                     self.scaning_count -= 1;
                     self.count_files();
+                    self.count_disk_size();
+                }
+                if msg.contains("索引维护完成")
+                    || msg.contains("索引维护异常")
+                    || msg.contains("导出快照完成")
+                    || msg.contains("导出快照异常")
+                {
+                    self.maintaining = false;
+                    self.count_disk_size();
                 }
                 self.msg = msg;
-                
             } else {
                 break;
             }
@@ -308,7 +570,9 @@ impl MyApp {
             self.wrap_label_text(ui, format!("文件总数:{}", self.file_count).as_str());
             ui.add_space(20.0);
 
-            let btn_res = ui.add_enabled(self.scaning_count == 0, Button::new("重新扫描"));
+            //维护索引期间索引写入器被占用, 禁止扫描
+            let enabled = self.scaning_count == 0 && !self.maintaining;
+            let btn_res = ui.add_enabled(enabled, Button::new("重新扫描"));
 
             if btn_res.clicked() {
                 let dirs: Vec<(String, i64)> = self
@@ -322,7 +586,7 @@ impl MyApp {
             }
             ui.add_space(20.0);
             //按钮
-            if ui.button("添加文件夹").clicked() {
+            if ui.add_enabled(enabled, Button::new("添加文件夹")).clicked() {
                 // 弹出文件夹选择对话框
                 if let Some(folder) = FileDialog::new().pick_folder() {
                     let selected_folder = folder.display().to_string();
@@ -339,6 +603,39 @@ impl MyApp {
             }
         });
 
+        ui.horizontal(|ui| {
+            self.wrap_label_text(
+                ui,
+                format!(
+                    "索引:{} 数据库:{}",
+                    text_utils::format_size(self.index_size),
+                    text_utils::format_size(self.db_size)
+                )
+                .as_str(),
+            );
+            ui.add_space(20.0);
+            let btn_res = ui.add_enabled(
+                self.scaning_count == 0 && !self.maintaining,
+                Button::new("维护索引"),
+            );
+            if btn_res.clicked() {
+                self.maintain_index();
+            }
+        });
+
+        if self.need_rebuild {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("词典已变更,需重建索引").color(Color32::RED));
+                let btn_res = ui.add_enabled(
+                    self.scaning_count == 0 && !self.maintaining,
+                    Button::new("重建索引"),
+                );
+                if btn_res.clicked() {
+                    self.rebuild_index();
+                }
+            });
+        }
+
         if self.current_del_index >= 0 {
             self.index_dirs.remove(self.current_del_index as usize);
             self.current_del_index = -1; //重置
@@ -386,7 +683,8 @@ impl MyApp {
 
                 //居右
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-                    if self.scaning_count == 0 {
+                    //维护期间索引写入器被占用
+                    if self.scaning_count == 0 && !self.maintaining {
                         //关闭图标
                         let close_btn = ImageButton::new(self.close_img.clone())
                             // .rounding(45.0)
@@ -400,6 +698,14 @@ impl MyApp {
                         if close_res.clicked() {
                             self.rm_index_dir(i, self.index_dirs[i as usize].id);
                         }
+                        //OCR开关
+                        let mut ocr = self.index_dirs[i as usize].ocr;
+                        let ocr_res = ui.checkbox(&mut ocr, "OCR").on_hover_text(
+                            "识别图片和扫描版PDF中的文字(需安装tesseract), 对之后扫描的文件生效",
+                        );
+                        if ocr_res.changed() {
+                            self.set_dir_ocr(i as usize, ocr);
+                        }
                     } else {
                         let mut rich_text: RichText = "...".into();
                         rich_text = rich_text.size(15.0).color(Color32::BLACK);
@@ -424,14 +730,127 @@ impl MyApp {
     fn serarch_ui(&mut self, ui: &mut Ui) {
         //搜索框
         self.input_box_ui(ui);
-        ui.add_space(25.0);
-
-        //结果列表
-        ScrollArea::vertical().animated(true).show(ui, |ui| {
-            self.files_ui(ui).unwrap();
+        ui.add_space(5.0);
+        self.search_options_ui(ui);
+        ui.add_space(15.0);
+        self.spelling_suggestions_ui(ui);
+
+        ui.horizontal_top(|ui| {
+            let facet_width = if self.facet_groups.is_empty() {
+                0.0
+            } else {
+                FACET_WIDTH
+            };
+            //结果列表
+            let files_width = ui.available_width() - facet_width;
+            ui.vertical(|ui| {
+                ui.set_width(files_width);
+                ScrollArea::vertical().animated(true).show(ui, |ui| {
+                    self.files_ui(ui).unwrap();
+                });
+            });
+            //分面
+            if facet_width > 0.0 {
+                ui.vertical(|ui| {
+                    ui.set_width(facet_width);
+                    ScrollArea::vertical()
+                        .id_salt("facets")
+                        .animated(true)
+                        .show(ui, |ui| {
+                            self.facets_ui(ui);
+                        });
+                });
+            }
         });
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-20 14:10:37
+    /// @Description: 分面栏, 点击分面值筛选(再次点击取消)
+    fn facets_ui(&mut self, ui: &mut Ui) {
+        let mut clicked: Option<(FacetKind, String)> = None;
+        let mut clear = false;
+        let options = &self.search_options;
+        if !options.facet_filters.is_empty() || !options.index_dir_ids.is_empty() {
+            if ui.link("清除筛选").clicked() {
+                clear = true;
+            }
+            ui.add_space(5.0);
+        }
+        for group in self.facet_groups.iter() {
+            if group.counts.is_empty() {
+                continue;
+            }
+            ui.label(RichText::new(group.kind.label()).size(14.0).strong());
+            for (value, count) in group.counts.iter() {
+                let (selected, label, hover) = match group.kind {
+                    FacetKind::SourceDir => {
+                        let id = value.parse::<i64>().unwrap_or_default();
+                        let path = self
+                            .index_dirs
+                            .iter()
+                            .find(|d| d.id == id)
+                            .map(|d| d.path.clone())
+                            .unwrap_or_else(|| value.clone());
+                        //只显示最后一级文件夹名
+                        let name = path
+                            .trim_end_matches(['/', '\\'])
+                            .rsplit(['/', '\\'])
+                            .next()
+                            .unwrap_or("")
+                            .to_string();
+                        (options.index_dir_ids.contains(&id), name, path)
+                    }
+                    _ => {
+                        let selected = options
+                            .facet_filters
+                            .iter()
+                            .any(|(k, v)| *k == group.kind && v == value);
+                        (selected, value.clone(), value.clone())
+                    }
+                };
+                let res = ui
+                    .selectable_label(selected, format!("{} ({})", label, count))
+                    .on_hover_text(hover);
+                if res.clicked() {
+                    clicked = Some((group.kind, value.clone()));
+                }
+            }
+            ui.add_space(10.0);
+        }
+
+        if clear {
+            self.search_options.facet_filters.clear();
+            self.search_options.index_dir_ids.clear();
+        } else if let Some((kind, value)) = clicked {
+            match kind {
+                FacetKind::SourceDir => {
+                    let id = value.parse::<i64>().unwrap_or_default();
+                    let ids = &mut self.search_options.index_dir_ids;
+                    match ids.iter().position(|i| *i == id) {
+                        Some(i) => {
+                            ids.remove(i);
+                        }
+                        None => ids.push(id),
+                    }
+                }
+                _ => {
+                    let filters = &mut self.search_options.facet_filters;
+                    match filters.iter().position(|(k, v)| *k == kind && *v == value) {
+                        Some(i) => {
+                            filters.remove(i);
+                        }
+                        None => filters.push((kind, value)),
+                    }
+                }
+            }
+        } else {
+            return;
+        }
+        self.last_edit_time = None;
+        self.search_res();
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-11-29 11:53:37
     /// @Description: 搜索
@@ -441,13 +860,62 @@ impl MyApp {
         let win_width = ui.ctx().screen_rect().width();
         //水平布局
         ui.horizontal(|ui| {
-            ui.add(
+            let edit_res = ui.add(
                 egui::TextEdit::singleline(&mut self.search_text)
                     .font(FontId::proportional(search_size))
-                    .min_size((win_width - LETF_WIDTH - 120.0, search_size).into())
+                    .min_size((win_width - LETF_WIDTH - 380.0, search_size).into())
                     .desired_rows(1)
                     .hint_text("请输入"),
             );
+            if edit_res.changed() {
+                self.last_edit_time = Some(Instant::now());
+            }
+            //停止输入一段时间后再搜索
+            let popup_id = ui.make_persistent_id("search_suggestions");
+            //正则输入过程中通常是不完整的，不做即时搜索
+            if self.regex_mode {
+                self.last_edit_time = None;
+            }
+            if let Some(last_edit_time) = self.last_edit_time {
+                let elapsed = last_edit_time.elapsed();
+                if elapsed >= SEARCH_DEBOUNCE {
+                    self.last_edit_time = None;
+                    self.search_as_you_type();
+                } else {
+                    ui.ctx().request_repaint_after(SEARCH_DEBOUNCE - elapsed);
+                }
+            }
+            //补全和搜索在阻塞线程中执行, 完成后显示
+            if self.prefix_search.is_some() {
+                if self.finish_search_as_you_type() {
+                    if !self.suggestions.is_empty() && edit_res.has_focus() {
+                        ui.memory_mut(|m| m.open_popup(popup_id));
+                    }
+                } else {
+                    ui.ctx().request_repaint_after(Duration::from_millis(50));
+                }
+            }
+            //自动补全
+            let mut selected_suggestion: Option<String> = None;
+            egui::popup_below_widget(
+                ui,
+                popup_id,
+                &edit_res,
+                egui::PopupCloseBehavior::CloseOnClickOutside,
+                |ui| {
+                    ui.set_min_width(200.0);
+                    for suggestion in self.suggestions.iter() {
+                        if ui.selectable_label(false, suggestion).clicked() {
+                            selected_suggestion = Some(suggestion.clone());
+                        }
+                    }
+                },
+            );
+            if let Some(suggestion) = selected_suggestion {
+                ui.memory_mut(|m| m.close_popup());
+                self.apply_suggestion(&suggestion);
+                edit_res.request_focus();
+            }
 
             // 使用 RichText 设置按钮字体大小
             let button_text = RichText::new("搜索").size(search_size);
@@ -458,23 +926,118 @@ impl MyApp {
                     color: Color32::GRAY, // 边框颜色
                 });
             let btn_res = ui.add(search_btn);
+            ui.checkbox(&mut self.exact_substring, "精确子串");
+            ui.checkbox(&mut self.regex_mode, "正则");
+            ui.checkbox(&mut self.collapse_similar, "折叠相似");
             //搜索按钮点击
             if btn_res.clicked() {
+                self.last_edit_time = None;
+                ui.memory_mut(|m| m.close_popup());
                 self.search_res();
             }
             //键盘事件
             if ui.ctx().input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.last_edit_time = None;
+                ui.memory_mut(|m| m.close_popup());
                 self.search_res();
             }
         });
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-19 14:02:51
+    /// @Description: 排序方式、搜索范围(索引目录), 变更后重新搜索
+    fn search_options_ui(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("排序:");
+            let sort_by = &mut self.search_options.sort_by;
+            egui::ComboBox::from_id_salt("sort_by")
+                .selected_text(sort_by.label())
+                .show_ui(ui, |ui| {
+                    for item in tantivy_search::SortBy::ALL {
+                        changed |= ui.selectable_value(sort_by, item, item.label()).changed();
+                    }
+                });
+            let is_relevance = self.search_options.sort_by == tantivy_search::SortBy::Relevance;
+            changed |= ui
+                .add_enabled(
+                    !is_relevance,
+                    egui::Checkbox::new(&mut self.search_options.desc, "降序"),
+                )
+                .changed();
+
+            ui.add_space(10.0);
+            let dir_ids = &mut self.search_options.index_dir_ids;
+            //已删除的索引目录不再作为搜索范围
+            dir_ids.retain(|id| self.index_dirs.iter().any(|d| d.id == *id));
+            let scope = if dir_ids.is_empty() {
+                "全部文件夹".to_string()
+            } else {
+                format!("{}个文件夹", dir_ids.len())
+            };
+            ui.menu_button(format!("范围: {}", scope), |ui| {
+                if ui
+                    .selectable_label(dir_ids.is_empty(), "全部文件夹")
+                    .clicked()
+                {
+                    dir_ids.clear();
+                    changed = true;
+                }
+                ui.separator();
+                for index_dir in self.index_dirs.iter() {
+                    let mut checked = dir_ids.contains(&index_dir.id);
+                    if ui.checkbox(&mut checked, index_dir.path.as_str()).changed() {
+                        if checked {
+                            dir_ids.push(index_dir.id);
+                        } else {
+                            dir_ids.retain(|id| *id != index_dir.id);
+                        }
+                        changed = true;
+                    }
+                }
+            });
+        });
+        if changed && !self.search_text.trim().is_empty() {
+            self.last_edit_time = None;
+            self.search_res();
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-12 14:26:05
+    /// @Description: 纠错建议(你是不是要找)
+    fn spelling_suggestions_ui(&mut self, ui: &mut Ui) {
+        if self.spelling_suggestions.is_empty() {
+            return;
+        }
+        let mut clicked: Option<String> = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(RichText::new("你是不是要找:").size(14.0));
+            for suggestion in self.spelling_suggestions.iter() {
+                if ui.link(RichText::new(suggestion).size(14.0)).clicked() {
+                    clicked = Some(suggestion.clone());
+                }
+            }
+        });
+        ui.add_space(10.0);
+        if let Some(suggestion) = clicked {
+            self.search_text = suggestion;
+            self.last_edit_time = None;
+            self.search_res();
+        }
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-11-29 16:03:40
     /// @Description: 文件列表
     fn files_ui(&mut self, ui: &mut Ui) -> Result<(), Box<dyn std::error::Error>> {
         //点击的文件
         let mut clicked_file: Option<&MyFile> = None;
+        //查找相似的文件
+        let mut similar_file: Option<String> = None;
+        //打分解释的文件
+        let mut explain_file: Option<String> = None;
         for my_file in self.files.iter() {
             //文件名称
 
@@ -519,6 +1082,13 @@ impl MyApp {
                 if res.clicked() {
                     clicked_file = Some(&my_file);
                 }
+                //查找相似
+                if ui.small_button("相似").clicked() {
+                    similar_file = Some(my_file.path.clone());
+                }
+                if self.show_debug && ui.small_button("解释").clicked() {
+                    explain_file = Some(my_file.path.clone());
+                }
             });
 
             //文件内容
@@ -529,16 +1099,107 @@ impl MyApp {
                 12.0,
                 Color32::GRAY,
             );
+            //命中的行: 文件:行号, 前后各有几行上下文
+            for line_hit in my_file.line_hits.iter() {
+                ui.label(
+                    RichText::new(format!(
+                        "{}:{}",
+                        file_extractor::display_path(&my_file.path),
+                        line_hit.line
+                    ))
+                    .color(Color32::DARK_GRAY),
+                );
+                for (line_no, line) in line_hit.context.iter() {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(format!("{:>6}", line_no))
+                                .monospace()
+                                .color(Color32::GRAY),
+                        );
+                        let color = match *line_no == line_hit.line {
+                            true => Color32::DARK_GRAY,
+                            false => Color32::GRAY,
+                        };
+                        self.highlight_text_fragment(ui, line, &self.tokenize, 12.0, color);
+                    });
+                }
+            }
+            //命中的表格行, 按表头展示
+            for (location, text) in my_file.rows.iter().take(MAX_SHOWN_ROWS) {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(RichText::new(location).color(Color32::DARK_GRAY));
+                    self.highlight_text_fragment(ui, text, &self.tokenize, 12.0, Color32::GRAY);
+                });
+            }
+            if my_file.rows.len() > MAX_SHOWN_ROWS {
+                ui.label(
+                    RichText::new(format!("(共命中{}行)", my_file.rows.len())).color(Color32::GRAY),
+                );
+            }
+            //命中的位置
+            if !my_file.locations.is_empty() {
+                let mut locations = my_file
+                    .locations
+                    .iter()
+                    .take(MAX_SHOWN_LOCATIONS)
+                    .map(|location| location.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                if my_file.locations.len() > MAX_SHOWN_LOCATIONS {
+                    locations.push_str(&format!(" 等{}处", my_file.locations.len()));
+                }
+                ui.label(RichText::new(format!("位置: {}", locations)).color(Color32::GRAY));
+            }
             //文件路径
             ui.add_space(10.0);
-            ui.label(my_file.path.replace(".out/", "/"));
+            ui.horizontal_wrapped(|ui| {
+                ui.label(file_extractor::display_path(&my_file.path));
+                if my_file.modified_time > 0 {
+                    ui.label(
+                        RichText::new(text_utils::format_time(my_file.modified_time))
+                            .color(Color32::GRAY),
+                    );
+                }
+                if my_file.file_size > 0 {
+                    ui.label(
+                        RichText::new(text_utils::format_size(my_file.file_size))
+                            .color(Color32::GRAY),
+                    );
+                }
+                //文档元数据
+                let metadata = &my_file.metadata;
+                let items = [
+                    ("作者", metadata.author.clone()),
+                    ("标题", metadata.title.clone()),
+                    (
+                        "页数",
+                        match metadata.page_count {
+                            0 => "".into(),
+                            n => n.to_string(),
+                        },
+                    ),
+                ];
+                for (label, value) in items {
+                    if !value.is_empty() {
+                        ui.label(
+                            RichText::new(format!("{}:{}", label, value)).color(Color32::GRAY),
+                        );
+                    }
+                }
+            });
+            if my_file.dup_count > 0 {
+                ui.label(
+                    RichText::new(format!("(另有{}个相似文件)", my_file.dup_count))
+                        .color(Color32::GRAY),
+                );
+            }
 
             let y = ui.cursor().min.y;
 
             ui.painter().line_segment(
                 [
                     pos2(LETF_WIDTH + 30.0, y),
-                    pos2(ui.ctx().screen_rect().width() - 10.0, y),
+                    pos2(ui.max_rect().right() - 10.0, y),
                 ], // 起点和终点
                 Stroke::new(0.5, Color32::GRAY), // 线条宽度和颜色
             );
@@ -549,6 +1210,12 @@ impl MyApp {
                 open_folder_and_select_file(&my_file.path);
             }
         }
+        if let Some(file_path) = similar_file {
+            self.search_similar(&file_path);
+        }
+        if let Some(file_path) = explain_file {
+            self.explain(&file_path);
+        }
         Ok(())
     }
 
@@ -671,6 +1338,14 @@ impl MyApp {
                         name: index_file.name,
                         is_file: true,
                         content: "".into(),
+                        dup_count: 0,
+                        modified_time: 0,
+                        file_size: 0,
+                        metadata: DocMetadata::default(),
+                        locations: vec![],
+                        rows: vec![],
+                        line_chunks: vec![],
+                        line_hits: vec![],
                     };
 
                     self.files.push(my_file);
@@ -686,15 +1361,85 @@ impl MyApp {
     /// @Date: Create in 2024-12-03 17:11:29
     /// @Description: 初始化相关
     fn init(&mut self) -> GlobalResult<()> {
+        self.runtime.block_on(async { init_log().await })?;
+        self.init_data()
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-15 14:52:30
+    /// @Description: 初始化数据(建表、统计、加载文件夹列表), 导入快照后也需要调用
+    fn init_data(&mut self) -> GlobalResult<()> {
         self.runtime.block_on(async {
-            init_log().await?;
             index_dir_dao::create_index_dir_table(&self.sqlite_pool).await?;
             files_dao::create_index_file_table(&self.sqlite_pool).await
         })?;
         self.count_files();
+        self.count_disk_size();
+        self.check_dict();
         return self.list_index_dirs();
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-06 14:20:35
+    /// @Description: 检查词典是否变更
+    fn check_dict(&mut self) {
+        let data_dir = self.data_dir.clone();
+        if !tantivy_jieba::dict_changed(&data_dir) {
+            return;
+        }
+        if self.file_count == 0 {
+            //还没有索引，直接记录
+            if let Err(e) = tantivy_jieba::save_dict_signature(&data_dir) {
+                error!("保存词典签名异常:{}", e);
+            }
+        } else {
+            self.need_rebuild = true;
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-06 14:31:02
+    /// @Description: 重建索引: 重新创建索引目录，清空文件记录，重新扫描所有文件夹
+    fn rebuild_index(&mut self) {
+        let dirs: Vec<(String, i64)> = self
+            .index_dirs
+            .iter()
+            .map(|dir| (dir.path.clone(), dir.id))
+            .collect();
+        let arc_tantivy_index = self.tantivy_index.clone();
+        {
+            let mut index = arc_tantivy_index.write().unwrap();
+            //先释放旧索引的文件
+            *index = Index::create_in_ram(tantivy::schema::Schema::builder().build());
+            let index_dir = format!("{}/indices", self.data_dir);
+            match tantivy_search::recreate_index(&index_dir, &Default::default()) {
+                Ok(new_index) => *index = new_index,
+                Err(e) => {
+                    error!("重建索引失败:{}", e);
+                    self.show_tip(&format!("重建索引失败:{}", e));
+                    return;
+                }
+            }
+        }
+        for (_, id) in dirs.iter() {
+            let r = self
+                .runtime
+                .block_on(async { files_dao::delete_by_index_dir(&self.sqlite_pool, *id).await });
+            if let Err(e) = r {
+                error!("删除文件记录失败:{}", e);
+            }
+        }
+        if let Err(e) = tantivy_jieba::save_dict_signature(&self.data_dir) {
+            error!("保存词典签名异常:{}", e);
+        }
+        self.need_rebuild = false;
+        self.files.clear();
+        self.count_files();
+        for (path, id) in dirs {
+            self.scan_files(path, id);
+        }
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-12-03 17:08:26
     /// @Description: 获取索引的文件夹列表
@@ -749,6 +1494,21 @@ impl MyApp {
         self.count_files();
         self.files.clear();
     }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-26 11:02:48
+    /// @Description: 设置文件夹是否OCR
+    fn set_dir_ocr(&mut self, index: usize, ocr: bool) {
+        let id = self.index_dirs[index].id;
+        let r = self
+            .runtime
+            .block_on(async { index_dir_dao::update_ocr(&self.sqlite_pool, id, ocr).await });
+        match r {
+            Ok(()) => self.index_dirs[index].ocr = ocr,
+            Err(e) => self.show_tip(&format!("设置OCR失败:{}", e)),
+        }
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-12-06 11:55:48
     /// @Description: 显示提示窗口
@@ -767,41 +1527,284 @@ impl MyApp {
     /// @Date: Create in 2024-12-19 09:45:23
     /// @Description: 搜索
     fn search_res(&mut self) {
+        //提交搜索时丢弃未完成的边输入边搜索和正则搜索
+        self.prefix_search = None;
+        self.regex_search = None;
+        if self.regex_mode {
+            self.search_regex();
+            return;
+        }
+        let (search_text, options) = self.metadata_search();
         let search_res = {
             let index = self.tantivy_index.read().unwrap();
 
             //搜索
             //分词
-            let mut keywords = tantivy_jieba::tokenize(&self.search_text);
+            let mut keywords = tantivy_jieba::tokenize(&search_text);
 
             self.tokenize.clear();
             self.tokenize.append(&mut keywords);
 
-            let query_str = self.tokenize.join(" AND ");
+            let query_str = Self::query_str_of(&self.tokenize, &options);
+            let mut synonyms = tantivy_synonym::synonyms_of(&self.tokenize);
+            //子串搜索的结果按原文高亮
+            if self.exact_substring {
+                self.tokenize.clear();
+            } else {
+                self.tokenize.append(&mut synonyms);
+            }
+            for part in search_text.split_whitespace() {
+                if !self.tokenize.iter().any(|t| t == part) {
+                    self.tokenize.push(part.to_string());
+                }
+            }
             // println!("搜索:{}", query_str);
             //从长到短排序
             self.tokenize
                 .sort_by(|item1, item2| item2.len().cmp(&item1.len()));
 
-            let search_res = tantivy_search::search_doc(&index, query_str.as_str(), 1, 500);
+            //只有元数据条件时不做子串搜索
+            let exact_substring = self.exact_substring && !search_text.trim().is_empty();
+            let search_res = tantivy_search::search_doc_with_fallback(
+                &index,
+                query_str.as_str(),
+                &search_text,
+                exact_substring,
+                &options,
+                1,
+                500,
+            );
+            self.facet_groups = tantivy_search::search_facets_with_fallback(
+                &index,
+                query_str.as_str(),
+                &search_text,
+                exact_substring,
+                &options,
+            )
+            .unwrap_or_else(|e| {
+                error!("分面统计异常:{}", e);
+                vec![]
+            });
 
             search_res
         };
+        self.show_search_res(search_res);
+        if self.show_debug {
+            self.refresh_query_debug();
+        }
+        self.spelling_suggestions.clear();
+        if self.files.is_empty() && !self.search_text.trim().is_empty() {
+            let index = self.tantivy_index.read().unwrap();
+            let r =
+                tantivy_spell::did_you_mean(&index, &self.search_text, SPELLING_SUGGESTION_COUNT);
+            match r {
+                Ok(suggestions) => self.spelling_suggestions = suggestions,
+                Err(e) => error!("纠错建议异常:{}", e),
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 14:30:16
+    /// @Description: 取出搜索框中的元数据条件(如 author:张三), 返回(其余文本, 带元数据条件的搜索选项)
+    fn metadata_search(&self) -> (String, tantivy_search::SearchOptions) {
+        let (search_text, filters) = tantivy_metadata::parse_filters(&self.search_text);
+        let mut options = self.search_options.clone();
+        options.metadata_filters = filters;
+        (search_text, options)
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 14:36:52
+    /// @Description: 分词结果转成查询语句, 只有元数据条件时查询全部(由条件筛选)
+    fn query_str_of(tokens: &[String], options: &tantivy_search::SearchOptions) -> String {
+        let query_str = tantivy_synonym::expand_query(tokens);
+        if query_str.trim().is_empty() && !options.metadata_filters.is_empty() {
+            return "*".into();
+        }
+        query_str
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-17 14:12:09
+    /// @Description: 正则搜索, 按匹配到的文本高亮
+    /// 不能用词典筛选时要验证所有文档, 在阻塞线程中执行, 完成后显示
+    fn search_regex(&mut self) {
+        self.spelling_suggestions.clear();
+        self.facet_groups.clear();
+        let index = self.tantivy_index.read().unwrap().clone();
+        let pattern = self.search_text.trim().to_string();
+        let options = self.search_options.clone();
+        let (tx, rx) = std::sync::mpsc::channel::<GlobalResult<RegexSearchResult>>();
+        self.regex_search = Some(rx);
+        self.msg = "正则搜索中...".into();
+        self.runtime.spawn_blocking(move || {
+            let _ = tx.send(tantivy_regex::search_regex(
+                &index, &pattern, &options, 1, 500,
+            ));
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-14 10:12:37
+    /// @Description: 正则搜索完成时显示结果, 返回是否已完成
+    fn finish_search_regex(&mut self) -> bool {
+        let Some(rx) = self.regex_search.as_ref() else {
+            return true;
+        };
+        let search_res = match rx.try_recv() {
+            Ok(r) => r,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(e) => Err(GlobalError::new(e.to_string())),
+        };
+        self.regex_search = None;
+        match search_res {
+            Ok(result) => {
+                let mut keywords: Vec<String> = vec![];
+                let docs = result
+                    .hits
+                    .into_iter()
+                    .map(|hit| {
+                        for (start, end) in hit.matches {
+                            let keyword = &hit.doc.file_content[start..end];
+                            if !keywords.iter().any(|k| k == keyword) {
+                                keywords.push(keyword.to_string());
+                            }
+                        }
+                        hit.doc
+                    })
+                    .collect::<Vec<tantivy_search::IndexDocument>>();
+                //从长到短排序
+                keywords.sort_by(|item1, item2| item2.len().cmp(&item1.len()));
+                self.tokenize = keywords;
+                self.show_search_res(Ok(docs));
+                self.msg = if result.truncated {
+                    format!(
+                        "正则搜索已验证{}个文档片段(达到上限), 结果可能不完整",
+                        result.scanned
+                    )
+                } else {
+                    format!("正则搜索已验证{}个文档片段", result.scanned)
+                };
+            }
+            Err(e) => {
+                self.files.clear();
+                self.msg = e.to_string();
+            }
+        }
+        true
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-10 14:05:31
+    /// @Description: 边输入边搜索, 最后一个词按前缀匹配
+    fn search_as_you_type(&mut self) {
+        self.prefix_search = None;
+        self.spelling_suggestions.clear();
+        //分面只在提交搜索时统计
+        self.facet_groups.clear();
+        if self.search_text.trim().is_empty() {
+            self.files.clear();
+            self.suggestions.clear();
+            return;
+        }
+        //有元数据条件时按完整搜索
+        if !self.metadata_search().1.metadata_filters.is_empty() {
+            self.suggestions.clear();
+            self.search_res();
+            return;
+        }
+        let (completed, prefix) = Self::split_prefix(&self.search_text);
+        self.tokenize = tantivy_jieba::tokenize(completed);
+        let query_str = tantivy_synonym::expand_query(&self.tokenize);
+        let mut synonyms = tantivy_synonym::synonyms_of(&self.tokenize);
+        self.tokenize.append(&mut synonyms);
+        if !prefix.is_empty() {
+            self.tokenize.push(prefix.to_string());
+        }
+        //从长到短排序
+        self.tokenize
+            .sort_by(|item1, item2| item2.len().cmp(&item1.len()));
+
+        //遍历词典、搜索耗时, 在阻塞线程中执行; 新的输入会丢弃未完成的结果
+        let index = self.tantivy_index.read().unwrap().clone();
+        let prefix = prefix.to_string();
+        let options = self.search_options.clone();
+        let (tx, rx) = std::sync::mpsc::channel::<PrefixSearchResult>();
+        self.prefix_search = Some(rx);
+        self.runtime.spawn_blocking(move || {
+            let suggestions = tantivy_search::suggest_terms(&index, &prefix, SUGGESTION_COUNT)
+                .unwrap_or_else(|e| {
+                    error!("自动补全异常:{}", e);
+                    vec![]
+                });
+            let search_res =
+                tantivy_search::search_prefix(&index, &query_str, &prefix, &options, 1, 500);
+            let _ = tx.send((suggestions, search_res));
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 17:20:45
+    /// @Description: 边输入边搜索完成时显示结果, 返回是否已完成
+    fn finish_search_as_you_type(&mut self) -> bool {
+        let Some(rx) = self.prefix_search.as_ref() else {
+            return true;
+        };
+        let (suggestions, search_res) = match rx.try_recv() {
+            Ok(r) => r,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(e) => (vec![], Err(GlobalError::new(e.to_string()))),
+        };
+        self.prefix_search = None;
+        self.suggestions = suggestions;
+        self.show_search_res(search_res);
+        true
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-10 14:20:48
+    /// @Description: 拆分出最后一个正在输入的词(前缀)
+    /// 以空白结尾时最后一个词已输入完整，前缀为空
+    fn split_prefix(text: &str) -> (&str, &str) {
+        if text.ends_with(char::is_whitespace) {
+            return (text, "");
+        }
+        let last_part = text.split_whitespace().last().unwrap_or("");
+        //最后一段的分词中，作为结尾的最长的词
+        let prefix_len = tantivy_jieba::tokenize(last_part)
+            .into_iter()
+            .filter(|token| last_part.ends_with(token.as_str()))
+            .map(|token| token.len())
+            .max()
+            .unwrap_or(0);
+        text.split_at(text.len() - prefix_len)
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-10 14:32:09
+    /// @Description: 选中补全的词，替换正在输入的词
+    fn apply_suggestion(&mut self, suggestion: &str) {
+        let (completed, _prefix) = Self::split_prefix(&self.search_text);
+        self.search_text = format!("{}{}", completed, suggestion);
+        self.suggestions.clear();
+        self.last_edit_time = None;
+        self.search_res();
+    }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-10 14:40:15
+    /// @Description: 显示搜索结果
+    fn show_search_res(&mut self, search_res: GlobalResult<Vec<tantivy_search::IndexDocument>>) {
         self.files.clear();
         if let Ok(docs) = search_res {
             // println!("搜索结果数量:{}", docs.len());
-            let mut files = docs
-                .into_iter()
-                .map(|doc| MyFile {
-                    is_file: true,
-                    name: doc.file_name,
-                    path: doc.file_path,
-                    content: doc.file_content,
-                })
-                .collect::<Vec<MyFile>>();
+            let mut files = docs.into_iter().map(MyFile::from).collect::<Vec<MyFile>>();
             //分组
             files = Self::group_by_file_path(files);
+            if self.collapse_similar {
+                files = self.collapse_similar_files(files);
+            }
             self.files.append(&mut files);
         } else {
             error!("搜索异常:{}", search_res.unwrap_err());
@@ -809,10 +1812,51 @@ impl MyApp {
         self.get_text_snippets();
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-22 11:20:34
+    /// @Description: 查找相似文档
+    fn search_similar(&mut self, file_path: &str) {
+        self.facet_groups.clear();
+        let search_res = {
+            let index = self.tantivy_index.read().unwrap();
+            //与当前搜索的筛选条件一致
+            let (_, options) = self.metadata_search();
+            tantivy_search::search_similar(&index, file_path, &options, 1, 500)
+        };
+
+        self.files.clear();
+        self.tokenize.clear();
+        match search_res {
+            Ok(docs) => {
+                let files = docs.into_iter().map(MyFile::from).collect::<Vec<MyFile>>();
+                self.files = Self::group_by_file_path(files);
+                //没有关键词，取开头作为摘要
+                let index = self.tantivy_index.read().unwrap();
+                for my_file in self.files.iter_mut() {
+                    my_file.content = my_file.content.chars().take(150).collect();
+                    my_file.line_hits = Self::line_hits(&index, my_file, &vec![]);
+                }
+                drop(index);
+                self.msg = format!(
+                    "与{}相似的文档:{}个",
+                    file_extractor::display_path(file_path),
+                    self.files.len()
+                );
+            }
+            Err(e) => {
+                error!("查找相似文档异常:{}", e);
+            }
+        }
+    }
+
     /// @Author: DengLibin
     /// @Date: Create in 2024-12-06 14:36:55
     /// @Description: 扫描文件
     fn scan_files(&mut self, dir: String, index_dir_id: i64) {
+        if self.maintaining {
+            self.msg = "索引维护中,请稍后再扫描".into();
+            return;
+        }
         let dir_c = dir.clone();
         let dir_c2 = dir.clone();
         let dir_c3 = dir.clone();
@@ -820,6 +1864,11 @@ impl MyApp {
         let msg_sender2: Arc<std::sync::mpsc::Sender<String>> = self.msg_sender.clone();
         let arc_tantivy_index = self.tantivy_index.clone();
         let arc_sqlite_pool = self.sqlite_pool.clone();
+        let arc_sqlite_pool2 = self.sqlite_pool.clone();
+        let ocr = self
+            .index_dirs
+            .iter()
+            .any(|index_dir| index_dir.id == index_dir_id && index_dir.ocr);
         self.scaning_count += 1;
         self.runtime.spawn(async move {
             let (tx, mut rx) = mpsc::channel::<String>(1000); // 创建通道，设置缓冲区大小
@@ -831,6 +1880,12 @@ impl MyApp {
             tokio::spawn(async move {
                 let mut count = 0;
                 let mut all_docs: Vec<tantivy_search::IndexDocument> = vec![];
+                //内容指纹 (文件路径, 指纹, 内容哈希)
+                let mut fingerprints: Vec<(String, i64, i64)> = vec![];
+                //文本文件的编码 (文件路径, 编码)
+                let mut encodings: Vec<(String, String)> = vec![];
+                //提取失败的文件 (文件路径, 失败原因)
+                let mut failures: Vec<(String, String)> = vec![];
                 while let Some(file_text) = text_receiver.recv().await {
                     let _r = msg_sender2.send(format!(
                         "创建索引:{},已完成数量:{}",
@@ -838,18 +1893,39 @@ impl MyApp {
                     ));
                     if !file_text.success {
                         error!("提取内容错误:{}:{}", file_text.file_path, file_text.err);
+                        failures.push((file_text.file_path, file_text.err));
                         continue;
                     }
                     //添加到索引
                     let file_path = file_text.file_path;
                     let content = file_text.text;
+                    if !file_text.encoding.is_empty() {
+                        encodings.push((file_path.clone(), file_text.encoding));
+                    }
+                    if let (Some(fp), Some(hash)) = (
+                        fingerprint::simhash(&content),
+                        fingerprint::content_hash(&content),
+                    ) {
+                        fingerprints.push((file_path.clone(), fp as i64, hash as i64));
+                    }
 
+                    //有结构单元时按单元切分, 片段记录所在位置
                     let mut docs: Vec<tantivy_search::IndexDocument> =
-                        tantivy_search::IndexDocument::split_to_list(
-                            file_path,
-                            content,
-                            index_dir_id,
-                        );
+                        if file_text.sections.is_empty() {
+                            tantivy_search::IndexDocument::split_to_list_with_metadata(
+                                file_path,
+                                content,
+                                index_dir_id,
+                                file_text.metadata,
+                            )
+                        } else {
+                            tantivy_search::IndexDocument::split_sections_to_list(
+                                file_path,
+                                file_text.sections,
+                                index_dir_id,
+                                file_text.metadata,
+                            )
+                        };
                     all_docs.append(&mut docs);
                     count += 1;
 
@@ -874,8 +1950,46 @@ impl MyApp {
                     }
                     all_docs.clear();
                 }
+                //文件记录在发送者全部关闭前已经入库
+                if !fingerprints.is_empty() {
+                    let r = files_dao::update_fingerprints(
+                        arc_sqlite_pool2.as_ref(),
+                        index_dir_id,
+                        fingerprints,
+                    )
+                    .await;
+                    if let Err(e) = r {
+                        error!("更新内容指纹异常:{}", e);
+                    }
+                }
+                if !encodings.is_empty() {
+                    let r = files_dao::update_encodings(
+                        arc_sqlite_pool2.as_ref(),
+                        index_dir_id,
+                        encodings,
+                    )
+                    .await;
+                    if let Err(e) = r {
+                        error!("更新文件编码异常:{}", e);
+                    }
+                }
+                let failed_count = failures.len();
+                if !failures.is_empty() {
+                    let r = files_dao::update_failures(
+                        arc_sqlite_pool2.as_ref(),
+                        index_dir_id,
+                        failures,
+                    )
+                    .await;
+                    if let Err(e) = r {
+                        error!("标记提取失败的文件异常:{}", e);
+                    }
+                }
 
-                let _r = msg_sender2.send(format!("{}:创建索引完成,文件数量:{}", dir_c3, count));
+                let _r = msg_sender2.send(format!(
+                    "{}:创建索引完成,文件数量:{},提取失败:{}",
+                    dir_c3, count, failed_count
+                ));
                 info!("提取文件内容完成:{}", count);
 
                 sleep(Duration::from_secs(3)).await;
@@ -893,6 +2007,11 @@ impl MyApp {
             tokio::spawn(async move {
                 //接收提取的文件
                 let mut count = 0;
+                //之前提取失败的重新提取
+                let r = files_dao::delete_failed(arc_sqlite_pool.as_ref(), index_dir_id).await;
+                if let Err(e) = r {
+                    error!("删除提取失败的文件记录异常:{}", e);
+                }
                 //已存在的
                 let exits_files = files_dao::file_paths(arc_sqlite_pool.as_ref(), index_dir_id)
                     .await
@@ -918,8 +2037,12 @@ impl MyApp {
                     index_files.push(index_file);
 
                     //提取文件内容
-                    file_text_extractor::spawn_extract_text(file_path, text_sender_arc.clone())
-                        .await;
+                    file_text_extractor::spawn_extract(TextExtractParam {
+                        file_path,
+                        text_sender: text_sender_arc.clone(),
+                        ocr,
+                    })
+                    .await;
                 }
                 if !index_files.is_empty() {
                     let r = files_dao::insert_batch(arc_sqlite_pool.as_ref(), index_files).await;
@@ -946,6 +2069,7 @@ impl MyApp {
     /// @Date: Create in 2024-12-11 18:13:51
     /// @Description: 取关键词片段
     fn get_text_snippets(&mut self) {
+        let index = self.tantivy_index.read().unwrap();
         for my_file in self.files.iter_mut() {
             let snippets = text_utils::find_text_snippets(&my_file.content, &self.tokenize, 20, 3);
 
@@ -955,7 +2079,54 @@ impl MyApp {
             }
             my_file.content.clear();
             my_file.content.push_str(&fragments);
+            my_file.line_hits = Self::line_hits(&index, my_file, &self.tokenize);
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-07 11:02:38
+    /// @Description: 按行切分的片段中命中的行和上下文(按相关度)
+    /// 命中行在片段的开头或结尾时, 上下文从索引中相邻的片段取
+    fn line_hits(
+        index: &Index,
+        my_file: &MyFile,
+        keywords: &Vec<String>,
+    ) -> Vec<text_utils::LineHit> {
+        let mut line_hits = my_file
+            .line_chunks
+            .iter()
+            .flat_map(|(start_line, chunk)| {
+                text_utils::line_hits(
+                    chunk,
+                    *start_line,
+                    keywords,
+                    LINE_HIT_CONTEXT,
+                    MAX_SHOWN_LINE_HITS,
+                )
+            })
+            .take(MAX_SHOWN_LINE_HITS)
+            .collect::<Vec<text_utils::LineHit>>();
+        let context = LINE_HIT_CONTEXT as u64;
+        for line_hit in line_hits.iter_mut() {
+            let from_line = line_hit.line.saturating_sub(context).max(1);
+            let to_line = line_hit.line + context;
+            if line_hit.context.len() as u64 == to_line - from_line + 1 {
+                continue;
+            }
+            let lines = tantivy_search::search_lines(index, &my_file.path, from_line, to_line);
+            match lines {
+                Ok(lines) => {
+                    for line in lines {
+                        if !line_hit.context.iter().any(|(no, _)| *no == line.0) {
+                            line_hit.context.push(line);
+                        }
+                    }
+                    line_hit.context.sort_by_key(|(no, _)| *no);
+                }
+                Err(e) => error!("查询上下文行异常:{}, {}", my_file.path, e),
+            }
         }
+        line_hits
     }
 
     /// @Author: DengLibin
@@ -968,12 +2139,575 @@ impl MyApp {
             let o = map.get_mut(path.as_str());
             if let Some(file) = o {
                 file.content.push_str(&my_file.content); //追加
+                for location in my_file.locations {
+                    if !file.locations.contains(&location) {
+                        file.locations.push(location);
+                    }
+                }
+                for row in my_file.rows {
+                    if !file.rows.contains(&row) {
+                        file.rows.push(row);
+                    }
+                }
+                for chunk in my_file.line_chunks {
+                    if !file.line_chunks.contains(&chunk) {
+                        file.line_chunks.push(chunk);
+                    }
+                }
             } else {
                 map.insert(path, my_file);
             }
         }
 
-        map.into_iter().map(|(_key, value)| value).collect()
+        map.into_iter()
+            .map(|(_key, mut value)| {
+                value.locations.retain(|location| !location.is_empty());
+                value
+            })
+            .collect()
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-05 14:10:26
+    /// @Description: 折叠内容相似的文件，保留排在前面的
+    fn collapse_similar_files(&self, files: Vec<MyFile>) -> Vec<MyFile> {
+        let paths = files
+            .iter()
+            .map(|f| f.path.clone())
+            .collect::<Vec<String>>();
+        let r = self
+            .runtime
+            .block_on(async { files_dao::fingerprints_by_paths(&self.sqlite_pool, &paths).await });
+        let fingerprint_map = match r {
+            Ok(map) => map,
+            Err(e) => {
+                error!("查询内容指纹异常:{}", e);
+                return files;
+            }
+        };
+        let fingerprints = paths
+            .iter()
+            .map(|path| fingerprint_map.get(path).map(|fp| *fp as u64))
+            .collect::<Vec<Option<u64>>>();
+        let kept_by = fingerprint::collapse(&fingerprints, fingerprint::NEAR_DUPLICATE_DISTANCE);
+
+        let mut dup_counts = vec![0_usize; files.len()];
+        for k in kept_by.iter().flatten() {
+            dup_counts[*k] += 1;
+        }
+        files
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| kept_by[*i].is_none())
+            .map(|(i, mut my_file)| {
+                my_file.dup_count = dup_counts[i];
+                my_file
+            })
+            .collect()
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-05 14:36:08
+    /// @Description: 查找重复文件(内容相同或近似)
+    fn find_duplicates(&mut self) {
+        let r = self
+            .runtime
+            .block_on(async { files_dao::select_with_fingerprint(&self.sqlite_pool).await });
+        let files = match r {
+            Ok(files) => files,
+            Err(e) => {
+                error!("查询内容指纹异常:{}", e);
+                self.show_tips(&mut vec![format!("查找重复文件异常:{}", e)]);
+                return;
+            }
+        };
+        let fingerprints = files
+            .iter()
+            .map(|f| f.fingerprint.unwrap_or_default() as u64)
+            .collect::<Vec<u64>>();
+        let clusters = fingerprint::cluster(&fingerprints, fingerprint::NEAR_DUPLICATE_DISTANCE);
+        self.duplicate_groups = clusters
+            .into_iter()
+            .map(|members| {
+                let first_hash = files[members[0]].content_hash;
+                DuplicateGroup {
+                    exact: members.iter().all(|i| files[*i].content_hash == first_hash),
+                    paths: members.iter().map(|i| files[*i].path.clone()).collect(),
+                }
+            })
+            .collect();
+        self.show_duplicates = true;
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-05 15:02:47
+    /// @Description: 重复文件窗口
+    fn duplicates_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_duplicates {
+            return;
+        }
+        let mut open = self.show_duplicates;
+        let mut clicked_path: Option<String> = None;
+        egui::Window::new(format!("重复文件({}组)", self.duplicate_groups.len()))
+            .collapsible(false)
+            .default_size(vec2(600.0, 400.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.duplicate_groups.is_empty() {
+                    ui.label("没有重复文件");
+                    return;
+                }
+                ScrollArea::vertical().animated(true).show(ui, |ui| {
+                    for (i, group) in self.duplicate_groups.iter().enumerate() {
+                        let title = if group.exact {
+                            format!("第{}组: 内容相同({}个)", i + 1, group.paths.len())
+                        } else {
+                            format!("第{}组: 内容相似({}个)", i + 1, group.paths.len())
+                        };
+                        ui.label(RichText::new(title).strong());
+                        for path in group.paths.iter() {
+                            let res = ui.add(
+                                Label::new(file_extractor::display_path(path))
+                                    .sense(egui::Sense::click()),
+                            );
+                            if res.hovered() {
+                                ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+                            }
+                            if res.clicked() {
+                                clicked_path = Some(path.clone());
+                            }
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+        self.show_duplicates = open;
+        if let Some(path) = clicked_path {
+            if file_util::exist(&path) {
+                open_folder_and_select_file(&path);
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 09:10:35
+    /// @Description: 查询提取失败的文件(超时、内存超出上限、提取进程崩溃等)
+    fn find_failed_files(&mut self) {
+        let r = self
+            .runtime
+            .block_on(async { files_dao::select_failed(&self.sqlite_pool).await });
+        match r {
+            Ok(files) => {
+                self.failed_files = files
+                    .into_iter()
+                    .map(|f| FailedFile {
+                        path: f.path,
+                        error: f.error.unwrap_or_default(),
+                    })
+                    .collect();
+                self.show_failed_files = true;
+            }
+            Err(e) => {
+                error!("查询提取失败的文件异常:{}", e);
+                self.show_tips(&mut vec![format!("查询提取失败的文件异常:{}", e)]);
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 09:18:52
+    /// @Description: 提取失败的文件窗口
+    fn failed_files_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_failed_files {
+            return;
+        }
+        let mut open = self.show_failed_files;
+        let mut clicked_path: Option<String> = None;
+        egui::Window::new(format!("提取失败的文件({}个)", self.failed_files.len()))
+            .collapsible(false)
+            .default_size(vec2(600.0, 400.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.failed_files.is_empty() {
+                    ui.label("没有提取失败的文件");
+                    return;
+                }
+                ui.label("重新扫描时会再次提取这些文件");
+                ui.separator();
+                ScrollArea::vertical().animated(true).show(ui, |ui| {
+                    for file in self.failed_files.iter() {
+                        let res = ui.add(
+                            Label::new(file_extractor::display_path(&file.path))
+                                .sense(egui::Sense::click()),
+                        );
+                        if res.hovered() {
+                            ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+                        }
+                        if res.clicked() {
+                            clicked_path = Some(file.path.clone());
+                        }
+                        ui.label(RichText::new(&file.error).color(Color32::RED));
+                        ui.separator();
+                    }
+                });
+            });
+        self.show_failed_files = open;
+        if let Some(path) = clicked_path {
+            if file_util::exist(&path) {
+                open_folder_and_select_file(&path);
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-21 10:20:16
+    /// @Description: 查询调试窗口: 分词结果、查询树、选中结果的打分解释
+    fn debug_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_debug {
+            return;
+        }
+        let mut open = self.show_debug;
+        egui::Window::new("查询调试")
+            .collapsible(false)
+            .default_size(vec2(600.0, 500.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.regex_mode {
+                    ui.label("正则搜索不经过分词和查询解析");
+                    return;
+                }
+                ScrollArea::vertical().animated(true).show(ui, |ui| {
+                    let debug = &self.query_debug;
+                    ui.label(RichText::new("分词").strong());
+                    let tokens = debug
+                        .tokens
+                        .iter()
+                        .map(|token| format!("[{}]", token))
+                        .collect::<Vec<String>>()
+                        .join(" ");
+                    self.wrap_label_text(ui, tokens);
+                    ui.separator();
+                    ui.label(RichText::new("查询语句").strong());
+                    self.wrap_label_text(ui, debug.query_str.as_str());
+                    ui.separator();
+                    ui.label(RichText::new("查询树").strong());
+                    ui.label(RichText::new(debug.query_tree.as_str()).monospace());
+                    ui.separator();
+                    ui.label(RichText::new("打分解释").strong());
+                    if self.explain_path.is_empty() {
+                        ui.label("点击结果中的\"解释\"查看");
+                    } else {
+                        self.wrap_label_text(ui, file_extractor::display_path(&self.explain_path));
+                        ui.label(RichText::new(self.explanation.as_str()).monospace());
+                    }
+                });
+            });
+        self.show_debug = open;
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-21 10:35:42
+    /// @Description: 按当前搜索框内容刷新查询调试信息
+    fn refresh_query_debug(&mut self) {
+        self.explain_path.clear();
+        self.explanation.clear();
+        let (search_text, options) = self.metadata_search();
+        let query_str = Self::query_str_of(&tantivy_jieba::tokenize(&search_text), &options);
+        let index = self.tantivy_index.read().unwrap();
+        let r = tantivy_explain::debug_query(
+            &index,
+            &search_text,
+            &query_str,
+            self.exact_substring && !search_text.trim().is_empty(),
+            &options,
+        );
+        match r {
+            Ok(debug) => self.query_debug = debug,
+            Err(e) => {
+                self.query_debug = QueryDebug {
+                    tokens: tantivy_jieba::tokenize(&search_text),
+                    query_str,
+                    query_tree: format!("解析查询异常:{}", e),
+                }
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-21 10:48:09
+    /// @Description: 选中结果的打分解释
+    fn explain(&mut self, file_path: &str) {
+        let (search_text, options) = self.metadata_search();
+        let r = {
+            let index = self.tantivy_index.read().unwrap();
+            tantivy_explain::explain_file(
+                &index,
+                &search_text,
+                &self.query_debug.query_str,
+                self.exact_substring && !search_text.trim().is_empty(),
+                &options,
+                file_path,
+            )
+        };
+        self.explain_path = file_path.to_string();
+        self.explanation = match r {
+            Ok(Some(explanation)) => explanation,
+            //子串补充的结果不是jieba查询命中的
+            Ok(None) => "该文件没有命中当前查询(可能来自子串补充搜索)".into(),
+            Err(e) => format!("打分解释异常:{}", e),
+        };
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-13 11:02:55
+    /// @Description: 统计索引和数据库占用的磁盘大小
+    fn count_disk_size(&mut self) {
+        let data_dir = self.data_dir.clone();
+        self.index_size = app::disk_size(&format!("{}/indices", data_dir));
+        //WAL模式下未合并的写入在-wal文件中
+        self.db_size = ["", "-wal", "-shm"]
+            .iter()
+            .map(|suffix| app::disk_size(&format!("{}/mysearch.db{}", data_dir, suffix)))
+            .sum();
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-13 11:10:38
+    /// @Description: 维护索引: 合并段、回收文件、整理数据库
+    fn maintain_index(&mut self) {
+        //合并段需要独占索引写入器, 扫描中不能维护
+        if self.scaning_count > 0 || self.maintaining {
+            self.msg = "扫描中,请稍后再维护索引".into();
+            return;
+        }
+        self.maintaining = true;
+        let msg_sender = self.msg_sender.clone();
+        let arc_tantivy_index = self.tantivy_index.clone();
+        let arc_sqlite_pool = self.sqlite_pool.clone();
+        let _ = msg_sender.send("索引维护中...".into());
+        self.runtime.spawn(async move {
+            //合并段 耗时任务 交给阻塞线程池; 维护期间禁止扫描, 不持有锁, 合并时可以继续搜索
+            let r = tokio::task::spawn_blocking(move || {
+                let index = arc_tantivy_index.read().unwrap().clone();
+                tantivy_search::optimize_index(&index)
+            })
+            .await;
+            let r = match r {
+                Ok(r) => r,
+                Err(e) => Err(GlobalError::new(e.to_string())),
+            };
+            let r = match r {
+                Ok(()) => sqlite_dao::vacuum(arc_sqlite_pool.as_ref()).await,
+                Err(e) => Err(e),
+            };
+            match r {
+                Ok(()) => {
+                    info!("索引维护完成");
+                    let _ = msg_sender.send("索引维护完成".into());
+                }
+                Err(e) => {
+                    error!("索引维护异常:{}", e);
+                    let _ = msg_sender.send(format!("索引维护异常:{}", e));
+                }
+            }
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-15 14:02:11
+    /// @Description: 导出快照
+    fn export_snapshot(&mut self) {
+        let file = FileDialog::new()
+            .add_filter("快照", &["zip"])
+            .set_file_name("my_search_snapshot.zip")
+            .save_file();
+        let out_file = match file {
+            Some(f) => f.display().to_string(),
+            None => return,
+        };
+        //借用维护状态，导出期间禁止扫描和维护
+        self.maintaining = true;
+        let msg_sender = self.msg_sender.clone();
+        let arc_tantivy_index = self.tantivy_index.clone();
+        let arc_sqlite_pool = self.sqlite_pool.clone();
+        let data_dir = self.data_dir.clone();
+        let _ = msg_sender.send("导出快照中...".into());
+        self.runtime.spawn(async move {
+            let r = snapshot::export_snapshot(
+                &data_dir,
+                arc_tantivy_index,
+                arc_sqlite_pool.as_ref(),
+                &out_file,
+            )
+            .await;
+            match r {
+                Ok(()) => {
+                    let _ = msg_sender.send(format!("导出快照完成:{}", out_file));
+                }
+                Err(e) => {
+                    error!("导出快照异常:{}", e);
+                    let _ = msg_sender.send(format!("导出快照异常:{}", e));
+                }
+            }
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-15 14:30:47
+    /// @Description: 导入快照, 关闭数据库和索引后替换数据目录中的文件，再重新打开
+    fn import_snapshot(&mut self) {
+        let file = FileDialog::new().add_filter("快照", &["zip"]).pick_file();
+        let snapshot_file = match file {
+            Some(f) => f.display().to_string(),
+            None => return,
+        };
+        //借用维护状态，导入期间禁止扫描和维护; 解压耗时, 在阻塞线程中执行
+        self.maintaining = true;
+        let _ = self.msg_sender.send("导入快照中...".into());
+        let (tx, rx) = std::sync::mpsc::channel::<GlobalResult<SnapshotManifest>>();
+        self.snapshot_import = Some(rx);
+        let data_dir = self.data_dir.clone();
+        self.runtime.spawn_blocking(move || {
+            let _ = tx.send(snapshot::prepare_import(&snapshot_file, &data_dir));
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 10:52:09
+    /// @Description: 快照解压完成后, 关闭数据库和索引, 替换数据目录中的文件，再重新打开
+    fn finish_import_snapshot(&mut self) {
+        let Some(rx) = self.snapshot_import.as_ref() else {
+            return;
+        };
+        let r = match rx.try_recv() {
+            Ok(r) => r,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(e) => Err(GlobalError::new(e.to_string())),
+        };
+        self.snapshot_import = None;
+        self.maintaining = false;
+        let r = match r {
+            Ok(manifest) => {
+                self.close_data();
+                let r = snapshot::apply_import(&self.data_dir).map(|_| manifest);
+                if let Err(e) = self.reopen_data() {
+                    error!("重新打开数据异常:{}", e);
+                    self.show_tip(&format!("重新打开数据异常:{}", e));
+                    return;
+                }
+                r
+            }
+            Err(e) => Err(e),
+        };
+
+        match r {
+            Ok(manifest) => {
+                self.msg = "导入快照完成".into();
+                self.show_tip(&format!("导入快照完成,程序版本:{}", manifest.app_version));
+            }
+            Err(e) => {
+                error!("导入快照异常:{}", e);
+                self.msg = "导入快照异常".into();
+                self.show_tip(&format!("导入快照异常:{}", e));
+            }
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-20 14:05:38
+    /// @Description: 关闭数据库和索引(释放数据目录中的文件)
+    fn close_data(&mut self) {
+        self.runtime
+            .block_on(async { self.sqlite_pool.close().await });
+        let mut index = self.tantivy_index.write().unwrap();
+        *index = Index::create_in_ram(tantivy::schema::Schema::builder().build());
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-20 14:12:06
+    /// @Description: 重新打开当前数据目录的词典、数据库和索引
+    fn reopen_data(&mut self) -> GlobalResult<()> {
+        let data_dir = app::get_data_dir()?;
+        if let Err(e) = tantivy_jieba::load_user_dict(&data_dir) {
+            error!("加载用户词典异常:{}", e);
+        }
+        if let Err(e) = tantivy_synonym::load_synonyms(&data_dir) {
+            error!("加载同义词异常:{}", e);
+        }
+        if let Err(e) = ocr::load_config(&data_dir) {
+            error!("加载OCR配置异常:{}", e);
+        }
+        if let Err(e) = extract_worker::load_config(&data_dir) {
+            error!("加载提取子进程配置异常:{}", e);
+        }
+        let db_path = format!("sqlite:{}/mysearch.db", data_dir);
+        let sqlite_pool = self
+            .runtime
+            .block_on(async { sqlite_dao::get_conn_pool(&db_path).await })?;
+        let index = tantivy_search::open_index(format!("{}/indices", data_dir).as_str())?;
+        self.sqlite_pool = Arc::new(sqlite_pool);
+        *self.tantivy_index.write().unwrap() = index;
+        self.data_dir = data_dir;
+
+        self.files.clear();
+        self.need_rebuild = false;
+        self.init_data()
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-20 14:20:51
+    /// @Description: 切换工作区
+    fn switch_workspace(&mut self, workspace: &str) {
+        if workspace == self.workspace {
+            return;
+        }
+        //工作区不可用时不切换, 继续使用原工作区的数据
+        if let Err(e) = app::set_current_workspace(workspace) {
+            self.show_tip(&e.msg);
+            return;
+        }
+        self.close_data();
+        if let Err(e) = self.reopen_data() {
+            error!("重新打开数据异常:{}", e);
+            self.show_tip(&format!("重新打开数据异常:{}", e));
+        }
+        self.workspace = app::current_workspace();
+        self.workspaces = app::list_workspaces();
+        self.search_text.clear();
+        self.tokenize.clear();
+        self.msg = format!("当前工作区:{}", self.workspace);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-20 14:35:17
+    /// @Description: 新建工作区窗口
+    fn new_workspace_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_new_workspace {
+            return;
+        }
+        let mut open = self.show_new_workspace;
+        let mut create = false;
+        egui::Window::new("新建工作区")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("名称:");
+                    ui.text_edit_singleline(&mut self.new_workspace_name);
+                });
+                ui.add_space(5.0);
+                if ui.button("创建并切换").clicked() {
+                    create = true;
+                }
+            });
+        self.show_new_workspace = open;
+        if create {
+            let name = self.new_workspace_name.trim().to_string();
+            self.show_new_workspace = false;
+            self.new_workspace_name.clear();
+            self.switch_workspace(&name);
+        }
     }
 
     /// @Author: DengLibin
@@ -0,0 +1,144 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-25 09:12:50
+//! @Description: 文本文件编码识别与解码
+//!
+//! 顺序: BOM -> 没有BOM的UTF-16(按0字节的位置、两侧字节值的分布判断) -> UTF-8 -> chardetng统计识别
+//! (Big5、Shift_JIS、EUC-KR、GBK/GB18030、Windows-1252等)。
+//! 解码有错误时用替换字符，不丢弃整个文件。
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, GB18030, GBK, UTF_16BE, UTF_16LE, UTF_8};
+
+//统计识别最多使用的字节数
+const DETECT_MAX_LEN: usize = 1024 * 1024;
+//判断没有BOM的UTF-16时最多使用的字节数
+const UTF16_SAMPLE_LEN: usize = 4096;
+
+/// 解码结果
+#[derive(Debug)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static str, //使用的编码
+    pub lossy: bool,            //是否有无法解码的字节(已替换为U+FFFD)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-25 09:20:31
+/// @Description: 识别编码并解码
+pub fn decode(bytes: &[u8]) -> DecodedText {
+    let (encoding, bom_len) = detect(bytes);
+    let (text, lossy) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    DecodedText {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        lossy,
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-25 09:28:46
+/// @Description: 识别编码, 返回(编码, BOM长度)
+pub fn detect(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return (encoding, bom_len);
+    }
+    if let Some(encoding) = utf16_without_bom(bytes) {
+        return (encoding, 0);
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return (UTF_8, 0);
+    }
+    let mut detector = EncodingDetector::new();
+    let sample = &bytes[..bytes.len().min(DETECT_MAX_LEN)];
+    detector.feed(sample, sample.len() == bytes.len());
+    let encoding = detector.guess(None, true);
+    //GBK解码器本身支持GB18030的四字节编码
+    if encoding == GBK {
+        return (GB18030, 0);
+    }
+    (encoding, 0)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-25 09:40:12
+/// @Description: 没有BOM的UTF-16: 以ASCII字符为主时，每两个字节中固定一个位置是0;
+/// 以中日韩文字为主时，固定一个位置的字节集中在少数区段
+pub fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE_LEN)];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+    //一侧多数为0，另一侧几乎没有0
+    if odd_zeros * 10 > pairs * 3 && even_zeros * 20 < pairs {
+        return Some(UTF_16LE);
+    }
+    if even_zeros * 10 > pairs * 3 && odd_zeros * 20 < pairs {
+        return Some(UTF_16BE);
+    }
+    //中日韩文字为主时0字节很少, 按两侧字节值的分布判断
+    if is_utf8(sample) {
+        return None;
+    }
+    if cjk_utf16(sample, 1) {
+        return Some(UTF_16LE);
+    }
+    if cjk_utf16(sample, 0) {
+        return Some(UTF_16BE);
+    }
+    None
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-12 16:05:27
+/// @Description: 以中日韩文字为主的UTF-16: 高位字节集中在汉字、谚文等区段, 低位字节分布在整个0-255
+/// GBK、Big5、Shift_JIS、EUC-KR的第二个字节都不小于0x40, 按低位字节中小于0x40的比例区分
+/// @param high: 高位字节在每两个字节中的位置(UTF-16LE为1, UTF-16BE为0)
+fn cjk_utf16(sample: &[u8], high: usize) -> bool {
+    let mut pairs = 0;
+    let mut cjk = 0; //高位是汉字、谚文的
+    let mut cjk_low = 0; //其中低位字节小于0x40的
+    let mut kana = 0; //日文假名、中日韩标点
+    let mut other = 0; //不是常见字符的
+    for pair in sample.chunks_exact(2) {
+        pairs += 1;
+        match pair[high] {
+            0x4e..=0x9f | 0xac..=0xd7 => {
+                cjk += 1;
+                if pair[1 - high] < 0x40 {
+                    cjk_low += 1;
+                }
+            }
+            0x30 => kana += 1,
+            //ASCII、通用标点、全角字符
+            0x00 | 0x20 | 0xff => {}
+            _ => other += 1,
+        }
+    }
+    (cjk + kana) * 2 >= pairs && other * 10 < pairs && cjk_low * 10 > cjk
+}
+
+//是否是UTF-8(末尾截断的字符不算错误)
+fn is_utf8(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-25 09:52:07
+/// @Description: 是否是UTF-16文本(有BOM或按0字节判断), 这类文本有大量0字节, 不能按非打印字符判断
+pub fn is_utf16(bytes: &[u8]) -> bool {
+    match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding == UTF_16LE || encoding == UTF_16BE,
+        None => utf16_without_bom(bytes).is_some(),
+    }
+}
@@ -0,0 +1,172 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-05 09:12:40
+//! @Description: 文本指纹(SimHash)，用于查找重复、近似重复的文件
+//!
+//! 指纹要存到数据库，哈希用固定算法(FNV-1a)，不用std的DefaultHasher(不同版本结果可能不同)
+
+use std::collections::HashMap;
+
+//近似重复的最大汉明距离
+pub const NEAR_DUPLICATE_DISTANCE: u32 = 3;
+//shingle长度(字符数)
+const SHINGLE_LEN: usize = 4;
+//分段数，距离不超过3的两个指纹至少有一段完全相同
+const BANDS: u32 = 4;
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 09:20:11
+/// @Description: FNV-1a 64位哈希
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 09:26:48
+/// @Description: 内容哈希(忽略空白)，相同表示内容完全相同
+pub fn content_hash(text: &str) -> Option<u64> {
+    let normalized = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if normalized.is_empty() {
+        return None;
+    }
+    Some(fnv1a(normalized.as_bytes()))
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 09:35:02
+/// @Description: SimHash指纹, 以连续4个字符(忽略空白, 小写)为特征
+pub fn simhash(text: &str) -> Option<u64> {
+    let chars = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(|c| c.to_lowercase())
+        .collect::<Vec<char>>();
+    if chars.is_empty() {
+        return None;
+    }
+    let mut weights = [0_i64; 64];
+    let mut shingle = String::new();
+    let shingle_count = if chars.len() > SHINGLE_LEN {
+        chars.len() - SHINGLE_LEN + 1
+    } else {
+        1
+    };
+    for i in 0..shingle_count {
+        shingle.clear();
+        shingle.extend(chars[i..(i + SHINGLE_LEN).min(chars.len())].iter());
+        let hash = fnv1a(shingle.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+    let mut fingerprint: u64 = 0;
+    for (bit, weight) in weights.iter().enumerate() {
+        if *weight > 0 {
+            fingerprint |= 1 << bit;
+        }
+    }
+    Some(fingerprint)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 09:48:30
+/// @Description: 汉明距离
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 10:02:17
+/// @Description: 按指纹聚类, 返回成员数>=2的类(元素为下标)
+/// 先按分段分桶，只比较同一个桶中的指纹
+pub fn cluster(fingerprints: &[u64], max_distance: u32) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..fingerprints.len()).collect();
+    let band_bits = 64 / BANDS;
+    for band in 0..BANDS {
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, fp) in fingerprints.iter().enumerate() {
+            let key = (fp >> (band * band_bits)) & ((1_u64 << band_bits) - 1);
+            buckets.entry(key).or_default().push(i);
+        }
+        for members in buckets.values() {
+            for x in 0..members.len() {
+                for y in x + 1..members.len() {
+                    let (i, j) = (members[x], members[y]);
+                    if hamming_distance(fingerprints[i], fingerprints[j]) <= max_distance {
+                        union(&mut parents, i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..fingerprints.len() {
+        let root = find(&mut parents, i);
+        groups.entry(root).or_default().push(i);
+    }
+    let mut clusters = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .collect::<Vec<Vec<usize>>>();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    clusters
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-05 10:30:44
+/// @Description: 折叠近似重复(按顺序，排在前面的保留)
+/// 返回每个元素归属的保留元素下标，自身保留或没有指纹的为None
+pub fn collapse(fingerprints: &[Option<u64>], max_distance: u32) -> Vec<Option<usize>> {
+    let mut kept: Vec<usize> = vec![];
+    let mut result = vec![None; fingerprints.len()];
+    for (i, fp) in fingerprints.iter().enumerate() {
+        let fp = match fp {
+            Some(fp) => *fp,
+            None => continue,
+        };
+        let similar = kept.iter().find(|k| {
+            let kept_fp = fingerprints[**k].unwrap_or_default();
+            hamming_distance(kept_fp, fp) <= max_distance
+        });
+        match similar {
+            Some(k) => result[i] = Some(*k),
+            None => kept.push(i),
+        }
+    }
+    result
+}
+
+fn find(parents: &mut Vec<usize>, i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    //路径压缩
+    let mut node = i;
+    while parents[node] != root {
+        let next = parents[node];
+        parents[node] = root;
+        node = next;
+    }
+    root
+}
+
+fn union(parents: &mut Vec<usize>, i: usize, j: usize) {
+    let root_i = find(parents, i);
+    let root_j = find(parents, j);
+    if root_i != root_j {
+        parents[root_j.max(root_i)] = root_i.min(root_j);
+    }
+}
@@ -1 +1,4 @@
-pub mod text_utils;
\ No newline at end of file
+pub mod charset;
+pub mod fingerprint;
+pub mod text_utils;
+pub mod xml_utils;
@@ -166,3 +166,110 @@ fn in_which_tag(pos: usize, tags: &Vec<(usize, usize)>) -> Option<(usize, usize)
     }
     return None;
 }
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-01-13 10:40:09
+/// @Description: 字节数格式化为可读大小
+pub fn format_size(size: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-19 14:20:36
+/// @Description: 毫秒时间戳格式化为本地时间, 为0时返回空
+pub fn format_time(millis: i64) -> String {
+    if millis == 0 {
+        return "".into();
+    }
+    match chrono::DateTime::from_timestamp_millis(millis) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "".into(),
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-02-12 09:30:17
+/// @Description: 编辑距离(按字符)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    let mut cur = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// 命中的行
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineHit {
+    pub line: u64,                   //命中的行号(从1开始)
+    pub context: Vec<(u64, String)>, //该行和前后的行: (行号, 内容)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-07 10:12:45
+/// @Description: 按行切分的内容片段中包含关键词的行和上下文
+/// start_line: 片段的起始行号, context_lines: 前后各取多少行, 在前一个命中的上下文中的行不再单独列出
+/// 没有命中的行时(如词干、拼音匹配)取片段开头
+pub fn line_hits(
+    chunk: &str,
+    start_line: u64,
+    keywords: &Vec<String>,
+    context_lines: usize,
+    max_count: usize,
+) -> Vec<LineHit> {
+    let lines = chunk.lines().collect::<Vec<&str>>();
+    let mut hit_indexes: Vec<usize> = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if hit_indexes.len() >= max_count {
+            break;
+        }
+        if keywords_pos(line, keywords).is_empty() {
+            continue;
+        }
+        if hit_indexes
+            .last()
+            .is_some_and(|last| i <= last + context_lines)
+        {
+            continue;
+        }
+        hit_indexes.push(i);
+    }
+    if hit_indexes.is_empty() && !lines.is_empty() {
+        hit_indexes.push(0);
+    }
+    hit_indexes
+        .into_iter()
+        .map(|i| {
+            let from = i.saturating_sub(context_lines);
+            let to = (i + context_lines + 1).min(lines.len());
+            LineHit {
+                line: start_line + i as u64,
+                context: (from..to)
+                    .map(|j| (start_line + j as u64, lines[j].to_string()))
+                    .collect(),
+            }
+        })
+        .collect()
+}
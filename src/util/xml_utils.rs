@@ -0,0 +1,122 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-03 09:12:26
+//! @Description: 简单的xml扫描(office文档、tika输出的xhtml), 只按顺序给出开始标签、结束标签和文本, 不校验格式
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    //注释 | CDATA | 声明、处理指令 | 标签 | 文本
+    static ref TOKEN_RE: Regex = Regex::new(
+        r"(?s)<!--.*?-->|<!\[CDATA\[(.*?)\]\]>|<[?!][^>]*>|<(/?)([A-Za-z_][\w:.\-]*)([^>]*?)(/?)>|([^<]+)"
+    )
+    .unwrap();
+    static ref ENTITY_RE: Regex = Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|[a-z]+);").unwrap();
+}
+
+/// xml事件
+#[derive(Debug, PartialEq)]
+pub enum XmlEvent<'a> {
+    Start { name: &'a str, attrs: &'a str }, //开始标签 <a>
+    Empty { name: &'a str, attrs: &'a str }, //空标签 <a/>
+    End { name: &'a str },                   //结束标签 </a>
+    Text(String),                            //文本(已反转义)
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 09:20:48
+/// @Description: 按顺序扫描xml, 跳过注释、声明
+pub fn events(xml: &str) -> impl Iterator<Item = XmlEvent<'_>> {
+    TOKEN_RE.captures_iter(xml).filter_map(to_event)
+}
+
+fn to_event<'a>(caps: Captures<'a>) -> Option<XmlEvent<'a>> {
+    if let Some(cdata) = caps.get(1) {
+        return Some(XmlEvent::Text(cdata.as_str().to_string()));
+    }
+    if let Some(text) = caps.get(6) {
+        return Some(XmlEvent::Text(unescape(text.as_str())));
+    }
+    let name = caps.get(3)?.as_str();
+    let attrs = caps.get(4).map(|m| m.as_str()).unwrap_or("");
+    if caps.get(2).is_some_and(|m| !m.as_str().is_empty()) {
+        return Some(XmlEvent::End { name });
+    }
+    if caps.get(5).is_some_and(|m| !m.as_str().is_empty()) {
+        return Some(XmlEvent::Empty { name, attrs });
+    }
+    Some(XmlEvent::Start { name, attrs })
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 09:31:15
+/// @Description: 取属性值(已反转义)
+pub fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    loop {
+        rest = rest.trim_start();
+        //属性名
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            return None;
+        }
+        let attr_name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        //没有值的属性
+        let Some(value) = rest.strip_prefix('=') else {
+            continue;
+        };
+        rest = value.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value_end = rest[1..].find(quote)? + 1;
+        if attr_name == name {
+            return Some(unescape(&rest[1..value_end]));
+        }
+        rest = &rest[value_end + 1..];
+    }
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 09:36:40
+/// @Description: 反转义: 预定义实体和数字字符引用, 不认识的实体保留原样
+pub fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    ENTITY_RE
+        .replace_all(text, |caps: &Captures| {
+            let entity = &caps[1];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|dec| dec.parse::<u32>().ok())
+                        .and_then(char::from_u32),
+                },
+            };
+            match c {
+                Some(c) => c.to_string(),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// @Author: DengLibin
+/// @Date: Create in 2025-03-03 09:42:03
+/// @Description: 去掉命名空间前缀: w:p -> p
+pub fn local_name(name: &str) -> &str {
+    match name.rfind(':') {
+        Some(i) => &name[i + 1..],
+        None => name,
+    }
+}
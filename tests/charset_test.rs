@@ -0,0 +1,110 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-25 11:05:18
+//! @Description:
+
+mod test {
+    use encoding_rs::{BIG5, EUC_KR, GBK, SHIFT_JIS, WINDOWS_1252};
+    use rust_my_doc_search::util::charset;
+
+    fn utf16(text: &str, little_endian: bool, bom: bool) -> Vec<u8> {
+        let mut bytes = vec![];
+        if bom {
+            bytes.extend(if little_endian {
+                [0xff, 0xfe]
+            } else {
+                [0xfe, 0xff]
+            });
+        }
+        for unit in text.encode_utf16() {
+            if little_endian {
+                bytes.extend(unit.to_le_bytes());
+            } else {
+                bytes.extend(unit.to_be_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-25 11:10:42
+    /// @Description: 识别各种编码
+    #[test]
+    pub fn test_decode() {
+        let english = "The quick brown fox jumps over the lazy dog. Error code 1234.";
+        for (little_endian, bom) in [(true, true), (false, true), (true, false), (false, false)] {
+            let decoded = charset::decode(&utf16(english, little_endian, bom));
+            assert_eq!(decoded.text, english);
+            assert_eq!(
+                decoded.encoding,
+                if little_endian {
+                    "UTF-16LE"
+                } else {
+                    "UTF-16BE"
+                }
+            );
+        }
+
+        let samples = [
+            (
+                GBK,
+                "gb18030",
+                "租赁华为云资源情况说明，甲方应当在合同签订后十日内支付首期租金。",
+            ),
+            (
+                BIG5,
+                "Big5",
+                "租賃華為雲資源情況說明，甲方應當在合同簽訂後十日內支付首期租金。",
+            ),
+            (
+                SHIFT_JIS,
+                "Shift_JIS",
+                "本日は晴天なり。東京都の天気予報をお知らせします。",
+            ),
+            (
+                EUC_KR,
+                "EUC-KR",
+                "오늘은 날씨가 맑습니다. 서울의 일기 예보를 알려드립니다.",
+            ),
+            (
+                WINDOWS_1252,
+                "windows-1252",
+                "Café crème brûlée à la carte, naïve façade.",
+            ),
+        ];
+        for (encoding, name, text) in samples {
+            let (bytes, _, _) = encoding.encode(text);
+            let decoded = charset::decode(&bytes);
+            assert_eq!(decoded.encoding, name);
+            assert_eq!(decoded.text, text);
+            assert!(!decoded.lossy);
+        }
+
+        //中日韩文字为主的UTF-16没有BOM, 0字节很少
+        let cjk = [
+            "租赁华为云资源情况说明，甲方应当在合同签订后十日内支付首期租金。",
+            "本日は晴天なり。東京都の天気予報をお知らせします。",
+            "오늘은 날씨가 맑습니다. 서울의 일기 예보를 알려드립니다.",
+        ];
+        for text in cjk {
+            for little_endian in [true, false] {
+                let decoded = charset::decode(&utf16(text, little_endian, false));
+                assert_eq!(decoded.text, text);
+                assert_eq!(
+                    decoded.encoding,
+                    if little_endian {
+                        "UTF-16LE"
+                    } else {
+                        "UTF-16BE"
+                    }
+                );
+            }
+        }
+
+        //有无法解码的字节, 仍然返回文本
+        let mut bytes = utf16("合同编号", true, true);
+        bytes.extend([0x00, 0xd8]); //单独的代理项
+        let decoded = charset::decode(&bytes);
+        assert!(decoded.lossy);
+        assert_eq!(decoded.text, "合同编号\u{fffd}");
+    }
+}
@@ -0,0 +1,147 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-27 11:02:15
+//! @Description:
+
+mod test {
+    use std::fs;
+
+    use rust_my_doc_search::file_scanner::{
+        email_extractor,
+        extractor_registry::{self, MIME_EML, MIME_MBOX},
+    };
+
+    const EML: &str = "From: =?UTF-8?B?5byg5LiJ?= <zhangsan@example.com>\r
+To: lisi@example.com, =?UTF-8?Q?=E7=8E=8B=E4=BA=94?= <wangwu@example.com>\r
+Subject: =?UTF-8?B?5ZCI5ZCM6K+E5a6h?=\r
+Date: Wed, 26 Feb 2025 10:30:00 +0800\r
+Message-ID: <1@example.com>\r
+MIME-Version: 1.0\r
+Content-Type: multipart/mixed; boundary=\"b1\"\r
+\r
+--b1\r
+Content-Type: text/plain; charset=utf-8\r
+Content-Transfer-Encoding: quoted-printable\r
+\r
+=E8=AF=B7=E6=9F=A5=E6=94=B6=E9=99=84=E4=BB=B6\r
+--b1\r
+Content-Type: text/plain; charset=utf-8; name=\"note.txt\"\r
+Content-Disposition: attachment; filename=\"note.txt\"\r
+Content-Transfer-Encoding: base64\r
+\r
+6ZmE5Lu25YaF5a65\r
+--b1\r
+Content-Type: message/rfc822\r
+Content-Disposition: attachment; filename=\"fwd.eml\"\r
+\r
+From: wangwu@example.com\r
+Subject: forwarded\r
+\r
+inner body\r
+--b1--\r
+";
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-27 11:10:40
+    /// @Description: eml: 邮件头、解码后的正文、附件
+    #[test]
+    pub fn test_eml() {
+        let dir = "./test_data/email";
+        fs::create_dir_all(dir).unwrap();
+        let file_path = format!("{}/合同.eml", dir);
+        fs::write(&file_path, EML).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(&file_path).unwrap(),
+            MIME_EML
+        );
+
+        let text = extractor_registry::extract_text(&file_path).unwrap().text;
+        assert!(text.contains("主题: 合同评审"));
+        assert!(text.contains("发件人: 张三 <zhangsan@example.com>"));
+        assert!(text.contains("收件人: lisi@example.com, 王五 <wangwu@example.com>"));
+        assert!(text.contains("日期: 2025-"));
+        assert!(text.contains("请查收附件"));
+        assert!(text.contains("附件: note.txt\n附件内容"));
+        assert!(text.contains("主题: forwarded"));
+        assert!(text.contains("inner body"));
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 15:10:42
+    /// @Description: 邮件头超过识别长度(From:在8KB之后)时按扩展名识别
+    #[test]
+    pub fn test_eml_long_headers() {
+        let dir = "./test_data/email";
+        fs::create_dir_all(dir).unwrap();
+        let file_path = format!("{}/长邮件头.eml", dir);
+        let received = "Received: from mx.example.com; Wed, 26 Feb 2025 10:30:00 +0800\r\n";
+        fs::write(&file_path, received.repeat(200) + EML).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(&file_path).unwrap(),
+            MIME_EML
+        );
+        let text = extractor_registry::extract_text(&file_path).unwrap().text;
+        assert!(text.contains("主题: 合同评审"));
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-27 11:25:03
+    /// @Description: mbox拆成单个邮件, Maildir中的邮件按邮件头识别
+    #[test]
+    pub fn test_mbox_maildir() {
+        let dir = "./test_data/mbox";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(format!("{}/Maildir/cur", dir)).unwrap();
+
+        let mbox = "From zhangsan@example.com Wed Feb 26 10:30:00 2025\n\
+From: zhangsan@example.com\n\
+Subject: 周报/第1周\n\
+Date: Wed, 26 Feb 2025 10:30:00 +0000\n\
+\n\
+first body\n\
+>From here\n\
+\n\
+From lisi@example.com Thu Feb 27 09:00:00 2025\n\
+From: lisi@example.com\n\
+To: zhangsan@example.com\n\
+\n\
+second body\n";
+        let mbox_file = format!("{}/Inbox", dir);
+        fs::write(&mbox_file, mbox).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(&mbox_file).unwrap(),
+            MIME_MBOX
+        );
+        assert!(email_extractor::is_mbox_file(&mbox_file));
+
+        let out_dir = format!("{}.out", mbox_file);
+        assert_eq!(
+            email_extractor::split_mbox(&mbox_file, &out_dir).unwrap(),
+            2
+        );
+        let first = format!("{}/00001_周报第1周.eml", out_dir);
+        let text = extractor_registry::extract_text(&first).unwrap().text;
+        assert!(text.contains("first body\nFrom here"));
+        let modified = fs::metadata(&first).unwrap().modified().unwrap();
+        let secs = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(secs, 1740565800);
+        let text = extractor_registry::extract_text(&format!("{}/00002_无主题.eml", out_dir))
+            .unwrap()
+            .text;
+        assert!(text.contains("second body"));
+
+        let message_file = format!("{}/Maildir/cur/1740565800.M1P2.host!2,S", dir);
+        fs::write(&message_file, EML).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(&message_file).unwrap(),
+            MIME_EML
+        );
+        assert!(!email_extractor::is_mbox_file(&message_file));
+        let text = extractor_registry::extract_text(&message_file)
+            .unwrap()
+            .text;
+        assert!(text.contains("请查收附件"));
+    }
+}
@@ -0,0 +1,94 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-11 11:02:40
+//! @Description:
+
+mod test {
+    use rust_my_doc_search::file_scanner::extract_worker::{
+        ExtractWorkerConfig, ExtractWorkerPool,
+    };
+
+    //主程序作为子进程
+    const WORKER_PROGRAM: &str = env!("CARGO_BIN_EXE_rust_my_doc_search");
+
+    fn cre_runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-11 11:05:18
+    /// @Description: 在子进程中提取, 子进程复用, 提取错误不结束子进程
+    #[test]
+    pub fn test_extract_in_worker() {
+        std::fs::create_dir_all("./test_data/extract_worker").unwrap();
+        let file_path = "./test_data/extract_worker/说明.txt";
+        std::fs::write(file_path, "第一行\n第二行\n").unwrap();
+        let pool = ExtractWorkerPool::new(
+            WORKER_PROGRAM,
+            "./test_data/extract_worker",
+            ExtractWorkerConfig::default(),
+        );
+        cre_runtime().block_on(async {
+            let extracted = pool.extract(file_path, false).await.unwrap();
+            assert_eq!(extracted.text, "第一行\n第二行\n");
+            assert_eq!(extracted.sections.len(), 1);
+            assert_eq!(pool.idle_count().await, 1);
+
+            let r = pool
+                .extract("./test_data/extract_worker/不存在.txt", false)
+                .await;
+            assert!(r.is_err());
+            assert_eq!(pool.idle_count().await, 1);
+        });
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-11 11:18:33
+    /// @Description: 卡住的文件超时或超过内存上限时结束子进程, 下一个文件重新启动子进程
+    #[cfg(target_os = "linux")]
+    #[test]
+    pub fn test_worker_timeout_and_memory_limit() {
+        std::fs::create_dir_all("./test_data/extract_worker").unwrap();
+        let file_path = "./test_data/extract_worker/说明.txt";
+        std::fs::write(file_path, "内容").unwrap();
+        //打开命名管道时没有写入方会一直阻塞
+        let fifo = "./test_data/extract_worker/卡住.fifo";
+        let _ = std::fs::remove_file(fifo);
+        let status = std::process::Command::new("mkfifo")
+            .arg(fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let config = ExtractWorkerConfig {
+            workers: 1,
+            timeout_secs: 1,
+            memory_limit_mb: 0,
+            ..Default::default()
+        };
+        let pool = ExtractWorkerPool::new(WORKER_PROGRAM, "./test_data/extract_worker", config);
+        cre_runtime().block_on(async {
+            let e = pool.extract(fifo, false).await.unwrap_err();
+            assert!(e.msg.contains("超时"), "{}", e.msg);
+            assert_eq!(pool.idle_count().await, 0);
+            let extracted = pool.extract(file_path, false).await.unwrap();
+            assert_eq!(extracted.text, "内容");
+            assert_eq!(pool.idle_count().await, 1);
+        });
+
+        let config = ExtractWorkerConfig {
+            workers: 1,
+            timeout_secs: 30,
+            memory_limit_mb: 1,
+            ..Default::default()
+        };
+        let pool = ExtractWorkerPool::new(WORKER_PROGRAM, "./test_data/extract_worker", config);
+        cre_runtime().block_on(async {
+            let e = pool.extract(fifo, false).await.unwrap_err();
+            assert!(e.msg.contains("内存"), "{}", e.msg);
+            assert_eq!(pool.idle_count().await, 0);
+        });
+    }
+}
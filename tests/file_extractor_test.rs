@@ -12,9 +12,13 @@ mod test {
     use rust_my_doc_search::{
         app::init_log,
         file_scanner::{
-            file_extractor::{extract_file, remove_out_dir_all},
-            file_text_extractor::{self, FileText}, is_compress_file,
+            extractor_registry::{self, ExtractedText, TextExtractor},
+            file_extractor::{display_path, extract_file, remove_out_dir_all},
+            file_text_extractor::{self, FileText},
+            is_compress_file, ocr,
         },
+        sys::global::GlobalResult,
+        util::fingerprint,
     };
     use tokio::{runtime::Builder, sync::mpsc};
     fn cre_runtime() -> tokio::runtime::Runtime {
@@ -137,12 +141,143 @@ mod test {
         println!("耗时:{} ms", e - s);
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-13 14:35:10
+    /// @Description: 显示路径只去掉压缩包、mbox解压文件夹的.out后缀
+    #[test]
+    pub fn test_display_path() {
+        assert_eq!(
+            display_path("D:/docs/a.zip.out/b.txt"),
+            "D:/docs/a.zip/b.txt"
+        );
+        assert_eq!(
+            display_path("D:/docs/a.zip.out/c.7z.out/mail.mbox.out/00001_x.eml"),
+            "D:/docs/a.zip/c.7z/mail.mbox/00001_x.eml"
+        );
+        //普通的以.out结尾的文件夹不变
+        assert_eq!(display_path("D:/build.out/a.txt"), "D:/build.out/a.txt");
+        assert_eq!(
+            display_path("D:/a.zip.out.bak/b.txt"),
+            "D:/a.zip.out.bak/b.txt"
+        );
+    }
+
     #[test]
     pub fn test_is_compress_file() {
         let b = is_compress_file("c:/test/测试.tar.gz");
         println!("is_compress_file:{}", b)
     }
 
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-24 11:02:17
+    /// @Description: 按文件头识别类型, 扩展名只作为补充
+    #[test]
+    pub fn test_sniff_mime() {
+        let dir = "./test_data/sniff";
+        fs::create_dir_all(dir).unwrap();
+        let files: [(&str, &[u8], &str); 7] = [
+            ("报告.txt", b"%PDF-1.7\n", extractor_registry::MIME_PDF),
+            ("截图.dat", b"\x89PNG\r\n\x1a\n\x00\x00", "image/png"),
+            (
+                "说明",
+                "没有扩展名的文本".as_bytes(),
+                extractor_registry::MIME_TEXT,
+            ),
+            //不足10字节的短文件
+            ("短", b"ok\n", extractor_registry::MIME_TEXT),
+            (
+                "短.bin",
+                b"\x01\x02ok",
+                extractor_registry::MIME_OCTET_STREAM,
+            ),
+            (
+                "合同.bin",
+                b"PK\x03\x04....word/document.xml",
+                extractor_registry::MIME_DOCX,
+            ),
+            (
+                "数据.tar.gz",
+                b"\x1f\x8b\x08\x00\x00\x00",
+                extractor_registry::MIME_ARCHIVE,
+            ),
+        ];
+        for (name, content, mime) in files {
+            let file_path = format!("{}/{}", dir, name);
+            fs::write(&file_path, content).unwrap();
+            assert_eq!(extractor_registry::sniff_mime(&file_path).unwrap(), mime);
+        }
+        let extracted = extractor_registry::extract_text(&format!("{}/说明", dir)).unwrap();
+        assert_eq!(extracted.text, "没有扩展名的文本");
+        assert_eq!(extracted.encoding.as_deref(), Some("UTF-8"));
+        let extracted = extractor_registry::extract_text(&format!("{}/截图.dat", dir)).unwrap();
+        assert!(extracted.text.is_empty());
+    }
+
+    struct UpperExtractor;
+
+    impl TextExtractor for UpperExtractor {
+        fn extract(&self, file_path: &str, _mime: &str) -> GlobalResult<ExtractedText> {
+            Ok(fs::read_to_string(file_path).unwrap().to_uppercase().into())
+        }
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-24 11:20:40
+    /// @Description: 注册提取器覆盖默认的
+    #[test]
+    pub fn test_register_extractor() {
+        let dir = "./test_data/register";
+        fs::create_dir_all(dir).unwrap();
+        let file_path = format!("{}/a.log", dir);
+        fs::write(&file_path, "{\\rtf1 hello}").unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(&file_path).unwrap(),
+            "application/rtf"
+        );
+        extractor_registry::register_extractor("application/rtf", Arc::new(UpperExtractor));
+        let extracted = extractor_registry::extract_text(&file_path).unwrap();
+        assert_eq!(extracted.text, "{\\RTF1 HELLO}");
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-26 11:30:25
+    /// @Description: OCR: 没有安装识别程序时保留原结果, 文件内容没变时用缓存
+    #[test]
+    pub fn test_ocr() {
+        let dir = "./test_data/ocr";
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            format!("{}/{}", dir, ocr::OCR_CONFIG_FILE),
+            "tesseract: ./not-exists/tesseract\ntimeout_secs: 5\n",
+        )
+        .unwrap();
+        ocr::load_config(dir).unwrap();
+        let config = ocr::config();
+        assert_eq!(config.tesseract, "./not-exists/tesseract");
+        assert_eq!(config.timeout_secs, 5);
+        assert_eq!(config.lang, "chi_sim+eng");
+
+        let image: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+        let file_path = format!("{}/扫描件.png", dir);
+        fs::write(&file_path, image).unwrap();
+        let cache_file = format!(
+            "{}/ocr_cache/{:016x}_chi_sim_eng_300_100.txt",
+            dir,
+            fingerprint::fnv1a(image)
+        );
+        let _ = fs::remove_file(&cache_file);
+        let extracted = extractor_registry::extract_text_with_ocr(&file_path, true).unwrap();
+        assert!(extracted.text.is_empty());
+
+        fs::create_dir_all(format!("{}/ocr_cache", dir)).unwrap();
+        fs::write(&cache_file, "发票号码 12345678").unwrap();
+        let extracted = extractor_registry::extract_text_with_ocr(&file_path, true).unwrap();
+        assert_eq!(extracted.text, "发票号码 12345678");
+        //没有开启OCR
+        let extracted = extractor_registry::extract_text_with_ocr(&file_path, false).unwrap();
+        assert!(extracted.text.is_empty());
+    }
+
     #[test]
     pub fn count_file() {
         let count = do_count(r#"D:\yiscn\测试文件"#);
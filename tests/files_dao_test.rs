@@ -0,0 +1,55 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-12 09:30:18
+//! @Description:
+
+mod test {
+    use rust_my_doc_search::dao::{
+        files_dao::{self, IndexFile},
+        sqlite_dao,
+    };
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 09:32:41
+    /// @Description: 提取失败的文件不算已存在, 重新扫描时删除失败记录后按新文件插入
+    #[test]
+    pub fn test_failed_files() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            std::fs::create_dir_all("./test_data/files_dao").unwrap();
+            let _ = std::fs::remove_file("./test_data/files_dao/files.db");
+            let pool = sqlite_dao::get_conn_pool("sqlite:./test_data/files_dao/files.db")
+                .await
+                .unwrap();
+            files_dao::create_index_file_table(&pool).await.unwrap();
+            let files = vec![
+                IndexFile::new("D:/doc/a.pdf".into(), 1),
+                IndexFile::new("D:/doc/b.pdf".into(), 1),
+            ];
+            files_dao::insert_batch(&pool, files).await.unwrap();
+            files_dao::update_failures(&pool, 1, vec![("D:/doc/b.pdf".into(), "提取超时".into())])
+                .await
+                .unwrap();
+
+            let paths = files_dao::file_paths(&pool, 1).await.unwrap();
+            assert!(paths.contains("D:/doc/a.pdf"));
+            assert!(!paths.contains("D:/doc/b.pdf"));
+            let failed = files_dao::select_failed(&pool).await.unwrap();
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].path, "D:/doc/b.pdf");
+            assert_eq!(failed[0].error.as_deref(), Some("提取超时"));
+
+            //重新扫描: 删除失败记录, 再次插入的记录是已建索引状态
+            files_dao::delete_failed(&pool, 1).await.unwrap();
+            files_dao::insert_batch(&pool, vec![IndexFile::new("D:/doc/b.pdf".into(), 1)])
+                .await
+                .unwrap();
+            assert!(files_dao::select_failed(&pool).await.unwrap().is_empty());
+            let all = files_dao::select_all(&pool).await.unwrap();
+            assert_eq!(all.len(), 2);
+            assert!(all
+                .iter()
+                .all(|f| f.status == files_dao::STATUS_INDEXED && f.error.is_none()));
+            pool.close().await;
+        });
+    }
+}
@@ -0,0 +1,43 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-05 16:20:13
+//! @Description:
+
+mod test {
+    use rust_my_doc_search::util::fingerprint::{
+        cluster, collapse, content_hash, hamming_distance, simhash, NEAR_DUPLICATE_DISTANCE,
+    };
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-05 16:22:40
+    /// @Description: 重复、近似重复聚类
+    #[test]
+    pub fn test_cluster() {
+        let base = "甲方将位于北京市海淀区的办公用房出租给乙方使用，租赁期限为三年，自二零二五年一月一日起至二零二七年十二月三十一日止，租金每月人民币两万元整，按季度支付，乙方应在每季度开始前十日内支付当季租金。".repeat(3);
+        let changed = base.replacen("两万元", "两万一千元", 1);
+        let texts = vec![
+            base.clone(),
+            "完全不相关的一段文字，讲的是如何用Rust写一个桌面搜索工具。".repeat(5),
+            format!("  {}\n", base),
+            changed,
+        ];
+        //忽略空白
+        assert_eq!(content_hash(&texts[0]), content_hash(&texts[2]));
+        assert_ne!(content_hash(&texts[0]), content_hash(&texts[3]));
+        assert!(simhash("  ").is_none());
+
+        let fingerprints = texts
+            .iter()
+            .map(|t| simhash(t).unwrap())
+            .collect::<Vec<u64>>();
+        println!(
+            "距离:{}",
+            hamming_distance(fingerprints[0], fingerprints[3])
+        );
+        let clusters = cluster(&fingerprints, NEAR_DUPLICATE_DISTANCE);
+        assert_eq!(clusters, vec![vec![0, 2, 3]]);
+
+        let options = fingerprints.into_iter().map(Some).collect::<Vec<_>>();
+        let kept_by = collapse(&options, NEAR_DUPLICATE_DISTANCE);
+        assert_eq!(kept_by, vec![None, None, Some(0), Some(0)]);
+    }
+}
@@ -0,0 +1,225 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-02-28 15:20:37
+//! @Description:
+
+mod test {
+    use std::{collections::HashMap, fs::File, io::Write};
+
+    use rust_my_doc_search::file_scanner::{
+        doc_metadata::{self, DocMetadata},
+        email_extractor,
+    };
+    use zip::write::SimpleFileOptions;
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 15:22:10
+    /// @Description: tika元数据: 按顺序取第一个有值的键, 照片没有应用程序时用相机型号
+    #[test]
+    pub fn test_from_tika() {
+        let mut metadata: HashMap<String, Vec<String>> = HashMap::new();
+        let mut put = |key: &str, value: &str| {
+            metadata.insert(key.to_string(), vec![value.to_string()]);
+        };
+        put("dc:title", " ");
+        put("pdf:docinfo:title", "年度报告");
+        put("dc:creator", "张三");
+        put("xmpTPg:NPages", "12");
+        put("dcterms:created", "2024-03-05T02:00:00Z");
+        put("exif:DateTimeOriginal", "2020:01:01 00:00:00");
+        put("tiff:Make", "Canon");
+        put("tiff:Model", "EOS R5");
+        let doc_metadata = DocMetadata::from_tika(&metadata);
+        assert_eq!(doc_metadata.title, "年度报告");
+        assert_eq!(doc_metadata.author, "张三");
+        assert_eq!(doc_metadata.page_count, 12);
+        assert_eq!(doc_metadata.created_time, 1709604000000);
+        assert_eq!(doc_metadata.application, "Canon EOS R5");
+        assert!(!doc_metadata.is_empty());
+        assert!(DocMetadata::from_tika(&HashMap::new()).is_empty());
+
+        assert_eq!(
+            doc_metadata::parse_time("2024-03-05T10:00:00+08:00"),
+            1709604000000
+        );
+        assert_eq!(
+            doc_metadata::parse_time("2024:03:05 10:00:00"),
+            doc_metadata::parse_time("2024-03-05T10:00:00")
+        );
+        assert!(doc_metadata::parse_time("2024-03-05") > 0);
+        assert_eq!(doc_metadata::parse_time("未知"), 0);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 15:35:48
+    /// @Description: office 2007格式的docProps/core.xml、app.xml
+    #[test]
+    pub fn test_officex_metadata() {
+        std::fs::create_dir_all("./test_data/metadata").unwrap();
+        let file_path = "./test_data/metadata/报告.docx";
+        let mut zip = zip::ZipWriter::new(File::create(file_path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file("docProps/core.xml", options).unwrap();
+        zip.write_all(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<dc:title>研发 &amp; 测试</dc:title><dc:subject>季度总结</dc:subject><dc:creator>张三</dc:creator>
+<cp:keywords>研发,测试</cp:keywords>
+<dcterms:created xsi:type="dcterms:W3CDTF">2024-03-05T02:00:00Z</dcterms:created>
+<dcterms:modified xsi:type="dcterms:W3CDTF">2024-03-06T02:00:00Z</dcterms:modified>
+</cp:coreProperties>"#
+                .as_bytes(),
+        )
+        .unwrap();
+        zip.start_file("docProps/app.xml", options).unwrap();
+        zip.write_all(
+            b"<Properties><Application>Microsoft Office Word</Application><Pages>7</Pages></Properties>",
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        let metadata = doc_metadata::officex_metadata(file_path).unwrap();
+        assert_eq!(metadata.title, "研发 & 测试");
+        assert_eq!(metadata.subject, "季度总结");
+        assert_eq!(metadata.author, "张三");
+        assert_eq!(metadata.keywords, "研发,测试");
+        assert_eq!(metadata.application, "Microsoft Office Word");
+        assert_eq!(metadata.page_count, 7);
+        assert_eq!(metadata.created_time, 1709604000000);
+        assert_eq!(metadata.modified_time, 1709690400000);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-13 16:40:25
+    /// @Description: 图片的EXIF直接解析: JPEG的APP1段、PNG的eXIf块, 大端、小端字节序
+    #[test]
+    pub fn test_exif_metadata() {
+        std::fs::create_dir_all("./test_data/metadata").unwrap();
+        let xp_title = "年度合影\0"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        let tiff = |little_endian: bool| -> Vec<u8> {
+            let u16_bytes = |v: u16| match little_endian {
+                true => v.to_le_bytes(),
+                false => v.to_be_bytes(),
+            };
+            let u32_bytes = |v: u32| match little_endian {
+                true => v.to_le_bytes(),
+                false => v.to_be_bytes(),
+            };
+            //IFD: 条目(标签, 类型, 值), 超过4字节的值放在IFD后面
+            let ifd = |start: usize, entries: &[(u16, u16, Vec<u8>)]| -> Vec<u8> {
+                let mut data_offset = start + 2 + entries.len() * 12 + 4;
+                let mut bytes = u16_bytes(entries.len() as u16).to_vec();
+                let mut data: Vec<u8> = vec![];
+                for (tag, value_type, value) in entries {
+                    bytes.extend(u16_bytes(*tag));
+                    bytes.extend(u16_bytes(*value_type));
+                    let count = if *value_type == 4 { 1 } else { value.len() };
+                    bytes.extend(u32_bytes(count as u32));
+                    if value.len() <= 4 {
+                        bytes.extend(value);
+                        bytes.extend(vec![0; 4 - value.len()]);
+                    } else {
+                        bytes.extend(u32_bytes(data_offset as u32));
+                        data.extend(value);
+                        data_offset += value.len();
+                    }
+                }
+                bytes.extend([0; 4]);
+                bytes.extend(data);
+                bytes
+            };
+            let mut ifd0 = vec![
+                (0x010f, 2, b"Canon\0".to_vec()),
+                (0x0110, 2, b"EOS R5\0".to_vec()),
+                (0x013b, 2, b"Zhang San\0".to_vec()),
+                (0x9c9b, 1, xp_title.clone()),
+                (0x8769, 4, u32_bytes(0).to_vec()),
+            ];
+            let exif_start = 8 + ifd(8, &ifd0).len();
+            ifd0[4].2 = u32_bytes(exif_start as u32).to_vec();
+            let exif_ifd = [(0x9003, 2, b"2020:01:01 00:00:00\0".to_vec())];
+
+            let mut bytes = match little_endian {
+                true => b"II".to_vec(),
+                false => b"MM".to_vec(),
+            };
+            bytes.extend(u16_bytes(42));
+            bytes.extend(u32_bytes(8));
+            bytes.extend(ifd(8, &ifd0));
+            bytes.extend(ifd(exif_start, &exif_ifd));
+            bytes
+        };
+
+        //JPEG: APP0(JFIF)在APP1(EXIF)前面, 之后是图像数据
+        let exif = [b"Exif\0\0".to_vec(), tiff(false)].concat();
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+        jpeg.extend(b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        jpeg.extend([0xff, 0xe1]);
+        jpeg.extend(((exif.len() + 2) as u16).to_be_bytes());
+        jpeg.extend(exif);
+        jpeg.extend([0xff, 0xda, 0x00, 0x02, 0xff, 0xd9]);
+        //PNG: IHDR之后是eXIf
+        let exif = tiff(true);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(13u32.to_be_bytes());
+        png.extend(b"IHDR");
+        png.extend([0; 13 + 4]);
+        png.extend((exif.len() as u32).to_be_bytes());
+        png.extend(b"eXIf");
+        png.extend(exif);
+        png.extend([0; 4]);
+        png.extend(0u32.to_be_bytes());
+        png.extend(b"IEND");
+        png.extend([0; 4]);
+
+        for (name, bytes) in [("照片.jpg", jpeg), ("照片.png", png)] {
+            let file_path = format!("./test_data/metadata/{}", name);
+            std::fs::write(&file_path, bytes).unwrap();
+            let metadata = doc_metadata::exif_metadata(&file_path).unwrap();
+            assert_eq!(metadata.title, "年度合影", "{}", name);
+            assert_eq!(metadata.author, "Zhang San", "{}", name);
+            assert_eq!(metadata.application, "Canon EOS R5", "{}", name);
+            assert_eq!(
+                metadata.created_time,
+                doc_metadata::parse_time("2020:01:01 00:00:00"),
+                "{}",
+                name
+            );
+            //图片只取元数据, 不用tika
+            let extracted =
+                rust_my_doc_search::file_scanner::extractor_registry::extract_text(&file_path)
+                    .unwrap();
+            assert_eq!(extracted.metadata, metadata);
+            assert!(extracted.text.is_empty());
+        }
+        //没有EXIF
+        let file_path = "./test_data/metadata/空白.png";
+        std::fs::write(file_path, b"\x89PNG\r\n\x1a\n\0\0\0\0IEND\0\0\0\0").unwrap();
+        assert!(doc_metadata::exif_metadata(file_path).unwrap().is_empty());
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 15:48:02
+    /// @Description: 邮件: 标题为主题, 作者为发件人
+    #[test]
+    pub fn test_email_metadata() {
+        std::fs::create_dir_all("./test_data/metadata").unwrap();
+        let file_path = "./test_data/metadata/a.eml";
+        std::fs::write(
+            file_path,
+            "From: zhangsan@example.com\r\nSubject: hello\r\nDate: Wed, 26 Feb 2025 10:30:00 +0000\r\n\r\nbody\r\n",
+        )
+        .unwrap();
+        let extracted =
+            rust_my_doc_search::file_scanner::extractor_registry::extract_text(file_path).unwrap();
+        assert!(email_extractor::looks_like_email(
+            b"From: a@b.c\r\nSubject: x\r\n\r\n"
+        ));
+        assert_eq!(extracted.metadata.title, "hello");
+        assert_eq!(extracted.metadata.author, "zhangsan@example.com");
+        assert_eq!(extracted.metadata.created_time, 1740565800000);
+    }
+}
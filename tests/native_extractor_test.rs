@@ -0,0 +1,217 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-10 17:05:48
+//! @Description:
+
+mod test {
+    use std::{fs::File, io::Write};
+
+    use rust_my_doc_search::file_scanner::{
+        doc_metadata, doc_sections::TextSection, epub_extractor, extractor_registry, rtf_extractor,
+    };
+    use zip::{write::SimpleFileOptions, CompressionMethod};
+
+    //写一个zip文件, 第一个条目是不压缩的mimetype
+    fn write_package(file_path: &str, mimetype: &str, entries: &[(&str, &str)]) {
+        std::fs::create_dir_all("./test_data/native").unwrap();
+        let mut zip = zip::ZipWriter::new(File::create(file_path).unwrap());
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file("mimetype", stored).unwrap();
+        zip.write_all(mimetype.as_bytes()).unwrap();
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-10 17:10:22
+    /// @Description: odt按标题拆分, 元数据来自meta.xml, 扩展名不对也能按mimetype识别
+    #[test]
+    pub fn test_odt() {
+        let file_path = "./test_data/native/方案.bin";
+        let content = r#"<office:document-content><office:body><office:text>
+<text:tracked-changes><text:changed-region><text:deletion><text:p>已删除</text:p></text:deletion></text:changed-region></text:tracked-changes>
+<text:p text:style-name="P1">前言<text:s text:c="2"/>内容</text:p>
+<text:h text:outline-level="1">第一章 <text:span>概述</text:span></text:h>
+<text:p>项目<text:tab/>背景<text:line-break/>第二行<text:note><text:note-body><text:p>脚注</text:p></text:note-body></text:note></text:p>
+<text:list><text:list-item><text:p>要点</text:p></text:list-item></text:list>
+<text:p/>
+</office:text></office:body></office:document-content>"#;
+        let meta = r#"<office:document-meta><office:meta>
+<meta:generator>LibreOffice/7.6</meta:generator><dc:title>年度方案</dc:title>
+<meta:initial-creator>张三</meta:initial-creator><meta:keyword>预算</meta:keyword>
+<meta:creation-date>2025-03-01T10:00:00</meta:creation-date>
+<meta:document-statistic meta:table-count="0" meta:page-count="4"/>
+</office:meta></office:document-meta>"#;
+        write_package(
+            file_path,
+            extractor_registry::MIME_ODT,
+            &[("content.xml", content), ("meta.xml", meta)],
+        );
+        assert_eq!(
+            extractor_registry::sniff_mime(file_path).unwrap(),
+            extractor_registry::MIME_ODT
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(
+            extracted.sections,
+            vec![
+                TextSection::new("", "前言  内容\n"),
+                TextSection::new(
+                    "第一章 概述",
+                    "第一章 概述\n项目\t背景\n第二行\n脚注\n要点\n\n"
+                ),
+            ]
+        );
+        let metadata = extracted.metadata;
+        assert_eq!(metadata.title, "年度方案");
+        assert_eq!(metadata.author, "张三");
+        assert_eq!(metadata.keywords, "预算");
+        assert_eq!(metadata.application, "LibreOffice/7.6");
+        assert_eq!(metadata.page_count, 4);
+        assert_eq!(
+            metadata.created_time,
+            doc_metadata::parse_time("2025-03-01T10:00:00")
+        );
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-10 17:28:40
+    /// @Description: ods按行, 展开重复的单元格和行号; odp按幻灯片
+    #[test]
+    pub fn test_ods_odp() {
+        let file_path = "./test_data/native/台账.ods";
+        let content = r#"<office:document-content><office:body><office:spreadsheet>
+<table:table table:name="明细">
+<table:table-row><table:table-cell><text:p>项目</text:p></table:table-cell><table:table-cell table:number-columns-repeated="2"/><table:table-cell><text:p>金额</text:p></table:table-cell></table:table-row>
+<table:table-row table:number-rows-repeated="3"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+<table:table-row><table:table-cell office:value-type="string"><text:p>差旅</text:p></table:table-cell><table:table-cell table:number-columns-repeated="2"><text:p>x</text:p></table:table-cell><table:table-cell office:value-type="float" office:value="300"><text:p>300.00</text:p></table:table-cell><table:table-cell table:number-columns-repeated="1020"/></table:table-row>
+<table:table-row table:number-rows-repeated="1048570"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+</table:table>
+</office:spreadsheet></office:body></office:document-content>"#;
+        write_package(
+            file_path,
+            extractor_registry::MIME_ODS,
+            &[("content.xml", content)],
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(
+            extracted.sections,
+            vec![TextSection::record(
+                "明细 第5行",
+                "项目: 差旅; 第2列: x; 第3列: x; 金额: 300.00"
+            )]
+        );
+
+        let file_path = "./test_data/native/汇报.odp";
+        let content = r#"<office:document-content><office:body><office:presentation>
+<draw:page draw:name="page1"><draw:frame><draw:text-box><text:p>封面</text:p></draw:text-box></draw:frame></draw:page>
+<draw:page draw:name="page2"><draw:frame><draw:text-box><text:p>进度</text:p></draw:text-box></draw:frame><presentation:notes><draw:frame><draw:text-box><text:p>备注</text:p></draw:text-box></draw:frame></presentation:notes></draw:page>
+</office:presentation></office:body></office:document-content>"#;
+        write_package(
+            file_path,
+            extractor_registry::MIME_ODP,
+            &[("content.xml", content)],
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(
+            extracted.sections,
+            vec![
+                TextSection::new("第1张幻灯片", "封面\n"),
+                TextSection::new("第2张幻灯片", "进度\n备注\n"),
+            ]
+        );
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-10 17:45:16
+    /// @Description: RTF: 代码页、字体字符集、unicode和替代字符、跳过的目标、信息组
+    #[test]
+    pub fn test_rtf() {
+        let rtf = concat!(
+            r"{\rtf1\ansi\ansicpg936\deff0",
+            r"{\fonttbl{\f0\fnil\fcharset134 \'cb\'ce\'cc\'e5;}{\f1\fswiss\fcharset0 Arial;}{\f2\fnil\fcharset128 MS Mincho;}}",
+            r"{\colortbl;\red255\green0\blue0;}",
+            r"{\*\generator Riched20 10.0;}",
+            r"{\info{\title \'c4\'ea\'b6\'c8\'b1\'a8\'b8\'e6}{\author Zhang San}{\creatim\yr2024\mo3\dy5\hr10\min30}}",
+            "\\viewkind4\\uc1\\pard\\f0 \\'d6\\'d0\\'ce\\'c4\\f1  caf\\'e9\\par\n",
+            r"\u27979?\u-29739?\uc2\u25991\'ce\'c4 ok\tab{\f2 \'83\'65\'83\'58\'83\'67}\line ",
+            r"{\field{\*\fldinst HYPERLINK http://a}{\fldrslt\f0 \'c1\'b4\'bd\'d3}}\par ",
+            r"\{\}\\\~end}"
+        );
+        let (text, metadata) = rtf_extractor::rtf_text(rtf.as_bytes());
+        assert_eq!(text, "中文 café\n测试文 ok\tテスト\n链接\n{}\\ end");
+        assert_eq!(metadata.title, "年度报告");
+        assert_eq!(metadata.author, "Zhang San");
+        assert_eq!(
+            metadata.created_time,
+            doc_metadata::parse_time("2024-03-05 10:30:00")
+        );
+        //\binN之后的二进制数据里的括号、反斜杠不影响解析
+        let bin_rtf = [&br"{\rtf1 before{\pict\bin5 "[..], b"}xy\\{", br"}after}"].concat();
+        assert_eq!(rtf_extractor::rtf_text(&bin_rtf).0, "beforeafter");
+
+        std::fs::create_dir_all("./test_data/native").unwrap();
+        let file_path = "./test_data/native/说明.rtf";
+        std::fs::write(file_path, rtf).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(file_path).unwrap(),
+            extractor_registry::MIME_RTF
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert!(extracted.text.starts_with("中文"));
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-10 18:02:33
+    /// @Description: EPUB按spine顺序拆分章节, 位置是章节标题
+    #[test]
+    pub fn test_epub() {
+        let file_path = "./test_data/native/小说.epub";
+        let container = r#"<?xml version="1.0"?><container><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#;
+        let opf = r#"<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title id="t">三体</dc:title><dc:creator opf:role="aut">刘慈欣</dc:creator><dc:subject>科幻</dc:subject><dc:date>2008-01-01</dc:date>
+</metadata><manifest>
+<item id="cover" href="Text/cover.xhtml" media-type="application/xhtml+xml"/>
+<item id="c1" href="Text/%E7%AC%AC1%E7%AB%A0.xhtml#top" media-type="application/xhtml+xml"/>
+<item id="c2" href="Text/c2.xhtml" media-type="application/xhtml+xml"/>
+<item id="css" href="Styles/a.css" media-type="text/css"/>
+</manifest><spine toc="ncx"><itemref idref="c2"/><itemref idref="cover"/><itemref idref="c1"/></spine></package>"#;
+        let chapter1 = r#"<html><head><title>第一章</title><style>p{}</style></head><body><h1>科学边界</h1><p>汪淼觉得</p><script>x()</script></body></html>"#;
+        let chapter2 = r#"<html><head><title>第二章 射手</title></head><body><div><p>射手<br/>农场主</p></div></body></html>"#;
+        let cover =
+            r#"<html><head><title>封面</title></head><body><img src="c.jpg"/></body></html>"#;
+        write_package(
+            file_path,
+            extractor_registry::MIME_EPUB,
+            &[
+                ("META-INF/container.xml", container),
+                ("OEBPS/content.opf", opf),
+                ("OEBPS/Text/cover.xhtml", cover),
+                ("OEBPS/Text/第1章.xhtml", chapter1),
+                ("OEBPS/Text/c2.xhtml", chapter2),
+            ],
+        );
+        assert_eq!(
+            epub_extractor::spine_chapters(opf),
+            vec!["Text/c2.xhtml", "Text/cover.xhtml", "Text/第1章.xhtml"]
+        );
+        assert_eq!(
+            extractor_registry::sniff_mime(file_path).unwrap(),
+            extractor_registry::MIME_EPUB
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(
+            extracted.sections,
+            vec![
+                TextSection::new("第二章 射手", "射手\n农场主\n\n"),
+                TextSection::new("科学边界", "科学边界\n汪淼觉得\n"),
+            ]
+        );
+        assert_eq!(extracted.metadata.title, "三体");
+        assert_eq!(extracted.metadata.author, "刘慈欣");
+        assert_eq!(extracted.metadata.keywords, "科幻");
+        assert!(extracted.metadata.created_time != 0);
+    }
+}
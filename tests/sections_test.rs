@@ -0,0 +1,276 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-03 15:10:24
+//! @Description:
+
+mod test {
+    use std::{fs::File, io::Write};
+
+    use rust_my_doc_search::{
+        file_scanner::{
+            doc_metadata::DocMetadata,
+            doc_sections::{self, TextSection},
+            extractor_registry,
+        },
+        tantivy::tantivy_search::{self, IndexDocument},
+        util::xml_utils::{self, XmlEvent},
+    };
+    use zip::write::SimpleFileOptions;
+
+    const RELS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+    //写一个zip文件, entries: (路径, 内容)
+    fn write_zip(file_path: &str, entries: &[(&str, String)]) {
+        std::fs::create_dir_all("./test_data/sections").unwrap();
+        let mut zip = zip::ZipWriter::new(File::create(file_path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 15:12:40
+    /// @Description: xml扫描
+    #[test]
+    pub fn test_xml_utils() {
+        let events = xml_utils::events(
+            r#"<?xml version="1.0"?><!-- c --><a x='1 &amp; 2'>A&lt;B&#x4e2d;&#25991;<b/><![CDATA[<c>]]></a>"#,
+        )
+        .collect::<Vec<XmlEvent>>();
+        assert_eq!(
+            events,
+            vec![
+                XmlEvent::Start {
+                    name: "a",
+                    attrs: " x='1 &amp; 2'"
+                },
+                XmlEvent::Text("A<B中文".into()),
+                XmlEvent::Empty {
+                    name: "b",
+                    attrs: ""
+                },
+                XmlEvent::Text("<c>".into()),
+                XmlEvent::End { name: "a" },
+            ]
+        );
+        assert_eq!(xml_utils::attr(" x='1 &amp; 2'", "x").unwrap(), "1 & 2");
+        assert_eq!(
+            xml_utils::attr(r#" r:id="rId1" id="2""#, "id").unwrap(),
+            "2"
+        );
+        //值里的空格、等号不算属性, 没有值的属性跳过
+        assert_eq!(
+            xml_utils::attr(r#" title="id = 3" hidden id ='4'"#, "id").unwrap(),
+            "4"
+        );
+        assert!(xml_utils::attr(r#" name="id""#, "id").is_none());
+        assert_eq!(xml_utils::unescape("&unknown; &#39;"), "&unknown; '");
+        assert_eq!(xml_utils::local_name("w:p"), "p");
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 15:25:18
+    /// @Description: docx按标题(中文版Word的标题样式id是数字)拆分
+    #[test]
+    pub fn test_docx_sections() {
+        let file_path = "./test_data/sections/方案.docx";
+        let styles = r#"<w:styles>
+<w:style w:type="paragraph" w:styleId="1"><w:name w:val="heading 1"/></w:style>
+<w:style w:type="paragraph" w:styleId="a3"><w:name w:val="Normal Indent"/></w:style>
+</w:styles>"#;
+        let document = r#"<w:document><w:body>
+<w:p><w:r><w:t>前言内容</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="1"/></w:pPr><w:r><w:t>第一章</w:t></w:r><w:r><w:t xml:space="preserve"> 概述</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="a3"/></w:pPr><w:r><w:t>项目</w:t><w:tab/><w:t>背景</w:t></w:r><w:r><w:instrText>PAGE</w:instrText></w:r></w:p>
+<w:p><w:pPr><w:outlineLvl w:val="1"/></w:pPr><w:r><w:t>1.1 目标</w:t></w:r></w:p>
+<w:p><w:r><w:t>降低成本</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        write_zip(
+            file_path,
+            &[
+                ("word/styles.xml", styles.into()),
+                ("word/document.xml", document.into()),
+            ],
+        );
+        let sections =
+            doc_sections::officex_sections(file_path, extractor_registry::MIME_DOCX).unwrap();
+        assert_eq!(
+            sections,
+            vec![
+                TextSection::new("", "前言内容\n"),
+                TextSection::new("第一章 概述", "第一章 概述\n项目\t背景\n"),
+                TextSection::new("1.1 目标", "1.1 目标\n降低成本\n"),
+            ]
+        );
+
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(extracted.sections.len(), 3);
+        assert!(extracted.text.contains("项目\t背景"));
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 15:40:51
+    /// @Description: pptx按presentation.xml中的顺序拆分幻灯片
+    #[test]
+    pub fn test_pptx_sections() {
+        let file_path = "./test_data/sections/汇报.pptx";
+        let presentation = format!(
+            r#"<p:presentation xmlns:r="{}"><p:sldIdLst><p:sldId id="256" r:id="rId3"/><p:sldId id="257" r:id="rId2"/></p:sldIdLst></p:presentation>"#,
+            RELS
+        );
+        let rels = r#"<Relationships>
+<Relationship Id="rId2" Type="slide" Target="slides/slide1.xml"/>
+<Relationship Id="rId3" Type="slide" Target="/ppt/slides/slide2.xml"/>
+</Relationships>"#;
+        let slide = |text: &str| {
+            format!(
+                "<p:sld><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p><a:p><a:r><a:t>正文</a:t></a:r></a:p></p:txBody></p:sld>",
+                text
+            )
+        };
+        write_zip(
+            file_path,
+            &[
+                ("ppt/presentation.xml", presentation),
+                ("ppt/_rels/presentation.xml.rels", rels.into()),
+                ("ppt/slides/slide1.xml", slide("第二页标题")),
+                ("ppt/slides/slide2.xml", slide("封面")),
+            ],
+        );
+        let sections =
+            doc_sections::officex_sections(file_path, extractor_registry::MIME_PPTX).unwrap();
+        assert_eq!(
+            sections,
+            vec![
+                TextSection::new("第1张幻灯片", "封面\n正文\n"),
+                TextSection::new("第2张幻灯片", "第二页标题\n正文\n"),
+            ]
+        );
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 15:55:06
+    /// @Description: xlsx按工作表拆分: 共享字符串、内联字符串、空单元格
+    #[test]
+    pub fn test_xlsx_sections() {
+        let file_path = "./test_data/sections/台账.xlsx";
+        let workbook = format!(
+            r#"<workbook xmlns:r="{}"><sheets><sheet name="汇总" sheetId="2" r:id="rId2"/><sheet name="Sheet2" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            RELS
+        );
+        let rels = r#"<Relationships>
+<Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+<Relationship Id="rId2" Target="worksheets/../worksheets/sheet2.xml"/>
+</Relationships>"#;
+        let shared = "<sst><si><t>名称</t></si><si><r><t>金</t></r><r><t>额</t></r><rPh><t>jin</t></rPh></si></sst>";
+        let sheet1 = r#"<worksheet><sheetData>
+<row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>
+<row r="2"><c r="A2" t="inlineStr"><is><t>合同</t></is></c><c r="C2"><f>1+1</f><v>2</v></c><c r="D2" t="b"><v>1</v></c></row>
+<row r="3"><c r="A3"/></row>
+</sheetData></worksheet>"#;
+        let sheet2 = r#"<worksheet><sheetData><row><c t="str"><v>合计</v></c></row></sheetData></worksheet>"#;
+        write_zip(
+            file_path,
+            &[
+                ("xl/workbook.xml", workbook),
+                ("xl/_rels/workbook.xml.rels", rels.into()),
+                ("xl/sharedStrings.xml", shared.into()),
+                ("xl/worksheets/sheet1.xml", sheet1.into()),
+                ("xl/worksheets/sheet2.xml", sheet2.into()),
+            ],
+        );
+        let sheets = doc_sections::xlsx_sheets(file_path).unwrap();
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].name, "汇总");
+        assert_eq!(
+            sheets[1].rows,
+            vec![vec!["名称", "", "金额"], vec!["合同", "", "2", "TRUE"]]
+        );
+        let sections =
+            doc_sections::officex_sections(file_path, extractor_registry::MIME_XLSX).unwrap();
+        assert_eq!(
+            sections,
+            vec![
+                TextSection::new("汇总", "合计"),
+                TextSection::new("Sheet2", "名称\t\t金额\n合同\t\t2\tTRUE"),
+            ]
+        );
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 16:08:33
+    /// @Description: PDF的xhtml按页拆分, OCR结果按\f拆分
+    #[test]
+    pub fn test_pdf_sections() {
+        let xhtml = r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>标题</title></head>
+<body><div class="page"><p>第一页内容</p><div class="annotation"><p>批注</p></div></div>
+<div class="page"><p>第二页</p><table><tr><td>A</td><td>B</td></tr></table></div></body></html>"#;
+        let sections = doc_sections::pdf_sections(xhtml);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].location, "第1页");
+        assert!(sections[0].text.contains("第一页内容\n"));
+        assert!(sections[0].text.contains("批注"));
+        assert!(!sections[0].text.contains("标题"));
+        assert_eq!(sections[1].location, "第2页");
+        assert!(sections[1].text.contains("A\tB\t\n"));
+
+        let sections = doc_sections::pdf_sections("<html><body><p>没有分页</p></body></html>");
+        assert_eq!(sections, vec![TextSection::new("", "没有分页\n")]);
+
+        let sections = doc_sections::page_sections("一\u{c}二\u{c}三");
+        assert_eq!(sections[2], TextSection::new("第3页", "三"));
+        assert!(doc_sections::page_sections("只有一页").is_empty());
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-03 16:20:15
+    /// @Description: 按结构单元切分内容片段, 搜索结果带位置
+    #[test]
+    pub fn test_split_sections() {
+        let sections = vec![
+            TextSection::new("第1页", "第一页\n说明"),
+            TextSection::new("第2页", "  \n"),
+            TextSection::new("第3页", "预算审批"),
+        ];
+        let docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/预算.pdf".into(),
+            sections,
+            1,
+            DocMetadata::default(),
+        );
+        let locations = docs
+            .iter()
+            .map(|doc| (doc.location.as_str(), doc.file_content.as_str()))
+            .collect::<Vec<(&str, &str)>>();
+        assert_eq!(
+            locations,
+            vec![("第1页", "第一页 说明"), ("第3页", "预算审批")]
+        );
+        let docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/空.pdf".into(),
+            vec![],
+            1,
+            Default::default(),
+        );
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].file_name, "空.pdf");
+
+        let mut index =
+            tantivy_search::recreate_index("./test_data/sections_indices", &Default::default())
+                .unwrap();
+        let docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/预算.pdf".into(),
+            vec![
+                TextSection::new("第1页", "项目说明"),
+                TextSection::new("第47页", "预算审批流程"),
+            ],
+            1,
+            DocMetadata::default(),
+        );
+        tantivy_search::insert_doc_list(&mut index, &docs).unwrap();
+        let r = tantivy_search::search_doc(&index, "审批", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].location, "第47页");
+    }
+}
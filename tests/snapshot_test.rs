@@ -0,0 +1,76 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-01-15 15:10:22
+//! @Description:
+
+mod test {
+    use std::sync::{Arc, RwLock};
+
+    use rust_my_doc_search::{
+        component::snapshot,
+        dao::{index_dir_dao, sqlite_dao},
+        tantivy::tantivy_search::{self, IndexDocument},
+    };
+    use tokio::runtime::Builder;
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-01-15 15:12:40
+    /// @Description: 导出快照再导入到另一个数据目录
+    #[test]
+    pub fn test_export_import_snapshot() {
+        let data_dir = "./test_data/snapshot_src";
+        let restore_dir = "./test_data/snapshot_dest";
+        let snapshot_file = "./test_data/my_search_snapshot.zip";
+        std::fs::create_dir_all(restore_dir).unwrap();
+
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let mut index = tantivy_search::recreate_index(
+                &format!("{}/indices", data_dir),
+                &Default::default(),
+            )
+            .unwrap();
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: "D:/yiscn/合同.docx".to_string(),
+                file_name: "合同.docx".to_string(),
+                file_content: "租赁华为云资源情况".to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+
+            let pool = sqlite_dao::get_conn_pool(&format!("sqlite:{}/mysearch.db", data_dir))
+                .await
+                .unwrap();
+            index_dir_dao::create_index_dir_table(&pool).await.unwrap();
+            index_dir_dao::add_index_dir(&pool, "D:/yiscn".into())
+                .await
+                .unwrap();
+
+            snapshot::export_snapshot(data_dir, Arc::new(RwLock::new(index)), &pool, snapshot_file)
+                .await
+                .unwrap();
+        });
+
+        //旧数据库留下的WAL文件不能留在新数据库旁边
+        let wal_file = format!("{}/mysearch.db-wal", restore_dir);
+        std::fs::write(&wal_file, "旧的WAL").unwrap();
+        let manifest = snapshot::import_snapshot(snapshot_file, restore_dir).unwrap();
+        assert_eq!(manifest.version, snapshot::SNAPSHOT_VERSION);
+        assert!(!std::path::Path::new(&wal_file).exists());
+        assert!(!std::path::Path::new(&format!("{}/snapshot_tmp", restore_dir)).exists());
+        assert!(!std::path::Path::new(&format!("{}/snapshot_backup", restore_dir)).exists());
+
+        let index = tantivy_search::open_index(&format!("{}/indices", restore_dir)).unwrap();
+        let r = tantivy_search::search_doc(&index, "华为", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+
+        //不是快照文件时不动数据目录
+        let bad_file = "./test_data/not_snapshot.zip";
+        std::fs::write(bad_file, "不是zip").unwrap();
+        assert!(snapshot::prepare_import(bad_file, restore_dir).is_err());
+        assert!(snapshot::apply_import(restore_dir).is_err());
+        let index = tantivy_search::open_index(&format!("{}/indices", restore_dir)).unwrap();
+        let r = tantivy_search::search_doc(&index, "华为", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+    }
+}
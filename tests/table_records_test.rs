@@ -0,0 +1,200 @@
+//! @Author: DengLibin
+//! @Date: Create in 2025-03-05 14:02:37
+//! @Description:
+
+mod test {
+    use std::{fs::File, io::Write};
+
+    use rust_my_doc_search::{
+        file_scanner::{
+            doc_metadata::DocMetadata, doc_sections::TextSection, extractor_registry, table_records,
+        },
+        tantivy::tantivy_search::{self, IndexDocument},
+    };
+    use zip::write::SimpleFileOptions;
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-05 14:05:11
+    /// @Description: 按表头展示一行, 识别分隔符
+    #[test]
+    pub fn test_record_text() {
+        let headers = vec!["姓名".to_string(), "".to_string(), "金额".to_string()];
+        let row = vec!["张三".to_string(), "备注".to_string(), " ".to_string()];
+        assert_eq!(
+            table_records::record_text(&headers, &row),
+            "姓名: 张三; 第2列: 备注"
+        );
+        let row = vec![
+            "李四".to_string(),
+            "".to_string(),
+            "100".to_string(),
+            "多出".to_string(),
+        ];
+        assert_eq!(
+            table_records::record_text(&headers, &row),
+            "姓名: 李四; 金额: 100; 第4列: 多出"
+        );
+
+        assert_eq!(
+            table_records::sniff_delimiter("a;b;c\n1;2,5;3", "csv"),
+            b';'
+        );
+        assert_eq!(table_records::sniff_delimiter("a,b\n1,2", "csv"), b',');
+        assert_eq!(table_records::sniff_delimiter("a\tb\n1\t2", "csv"), b'\t');
+        assert_eq!(table_records::sniff_delimiter("a,b", "tsv"), b'\t');
+        assert_eq!(table_records::sniff_delimiter("单列", "csv"), b',');
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-05 14:18:40
+    /// @Description: CSV记录: 引号中的分隔符和换行、空行、只有表头
+    #[test]
+    pub fn test_csv_records() {
+        let text = "\u{feff}合同号,客户,说明\n\nHT-001,\"甲公司, 北京\",\"第一行\n第二行\"\nHT-002,乙公司\n";
+        let records = table_records::csv_records(text, b',');
+        assert_eq!(
+            records,
+            vec![
+                TextSection::record(
+                    "第3行",
+                    "合同号: HT-001; 客户: 甲公司, 北京; 说明: 第一行\n第二行"
+                ),
+                TextSection::record("第5行", "合同号: HT-002; 客户: 乙公司"),
+            ]
+        );
+        assert!(table_records::csv_records("合同号,客户\n", b',').is_empty());
+
+        //按扩展名和内容识别为CSV, GBK编码
+        std::fs::create_dir_all("./test_data/table_records").unwrap();
+        let file_path = "./test_data/table_records/客户.csv";
+        let (gbk, _, _) = encoding_rs::GBK.encode("编号;名称\n1;华东分公司\n");
+        std::fs::write(file_path, gbk).unwrap();
+        assert_eq!(
+            extractor_registry::sniff_mime(file_path).unwrap(),
+            extractor_registry::MIME_CSV
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(
+            extracted.sections,
+            vec![TextSection::record("第2行", "编号: 1; 名称: 华东分公司")]
+        );
+        assert_eq!(extracted.encoding.as_deref(), Some("gb18030"));
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-05 14:36:52
+    /// @Description: xlsx按行, 位置带工作表名和行号, 只有表头的工作表整个作为一个单元
+    #[test]
+    pub fn test_xlsx_records() {
+        std::fs::create_dir_all("./test_data/table_records").unwrap();
+        let file_path = "./test_data/table_records/台账.xlsx";
+        let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="明细" sheetId="1" r:id="rId1"/><sheet name="说明" sheetId="2" r:id="rId2"/></sheets></workbook>"#;
+        let rels = r#"<Relationships>
+<Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+<Relationship Id="rId2" Target="worksheets/sheet2.xml"/>
+</Relationships>"#;
+        let sheet1 = r#"<worksheet><sheetData>
+<row r="2"><c r="B2" t="inlineStr"><is><t>项目</t></is></c><c r="C2" t="inlineStr"><is><t>金额</t></is></c></row>
+<row r="5"><c r="B5" t="inlineStr"><is><t>预算审批</t></is></c><c r="C5"><v>1800</v></c></row>
+<row r="18000"><c r="C18000"><v>3</v></c></row>
+</sheetData></worksheet>"#;
+        let sheet2 = r#"<worksheet><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>只有一行</t></is></c></row></sheetData></worksheet>"#;
+        let mut zip = zip::ZipWriter::new(File::create(file_path).unwrap());
+        for (name, content) in [
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/worksheets/sheet1.xml", sheet1),
+            ("xl/worksheets/sheet2.xml", sheet2),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let records = table_records::xlsx_records(file_path).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                TextSection::record("明细 第5行", "项目: 预算审批; 金额: 1800"),
+                TextSection::record("明细 第18000行", "金额: 3"),
+                TextSection::new("说明", "只有一行"),
+            ]
+        );
+        let extracted = extractor_registry::extract_text(file_path).unwrap();
+        assert_eq!(extracted.sections, records);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-05 14:50:08
+    /// @Description: 每行一个内容片段, 搜索结果带行号和按表头展示的内容
+    #[test]
+    pub fn test_search_records() {
+        let mut index = tantivy_search::recreate_index(
+            "./test_data/table_records_indices",
+            &Default::default(),
+        )
+        .unwrap();
+        let docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/台账.xlsx".into(),
+            vec![
+                TextSection::record("明细 第5行", "项目: 预算审批; 金额: 1800"),
+                TextSection::record("明细 第6行", "项目: 差旅报销; 金额: 300"),
+                TextSection::new("说明", "填表说明"),
+            ],
+            1,
+            DocMetadata::default(),
+        );
+        assert_eq!(docs.len(), 3);
+        assert!(docs[0].record);
+        assert!(!docs[2].record);
+        tantivy_search::insert_doc_list(&mut index, &docs).unwrap();
+
+        let r = tantivy_search::search_doc(&index, "报销", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        assert!(r[0].record);
+        assert_eq!(r[0].location, "明细 第6行");
+        assert_eq!(r[0].file_content, "项目: 差旅报销; 金额: 300");
+
+        let r = tantivy_search::search_doc(&index, "填表", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        assert!(!r[0].record);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 18:05:36
+    /// @Description: 一个表格的行命中很多时, 每个文件最多返回20行, 不占满整页
+    #[test]
+    pub fn test_cap_record_hits() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/record_hits_indices", &Default::default())
+                .unwrap();
+        let rows = (1..=50)
+            .map(|i| {
+                TextSection::record(
+                    format!("明细 第{}行", i),
+                    format!("项目: 差旅报销; 金额: {}", i),
+                )
+            })
+            .collect::<Vec<TextSection>>();
+        let mut docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/台账.xlsx".into(),
+            rows,
+            1,
+            DocMetadata::default(),
+        );
+        docs.extend(IndexDocument::split_sections_to_list(
+            "D:/yiscn/制度.docx".into(),
+            vec![TextSection::new("", "差旅报销制度")],
+            1,
+            DocMetadata::default(),
+        ));
+        tantivy_search::insert_doc_list(&mut index, &docs).unwrap();
+
+        let r = tantivy_search::search_doc(&index, "报销", 1, 30).unwrap();
+        assert_eq!(r.len(), 21);
+        assert_eq!(r.iter().filter(|doc| doc.record).count(), 20);
+        assert!(r.iter().any(|doc| doc.file_path == "D:/yiscn/制度.docx"));
+        let r = tantivy_search::search_doc(&index, "报销", 2, 30).unwrap();
+        assert!(r.is_empty());
+    }
+}
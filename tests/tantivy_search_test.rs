@@ -1,6 +1,17 @@
 mod test {
     use extractous::Extractor;
-    use rust_my_doc_search::tantivy::{tantivy_jieba, tantivy_search::{self, IndexDocument}};
+    use rust_my_doc_search::file_scanner::doc_metadata::{self, DocMetadata};
+    use rust_my_doc_search::file_scanner::doc_sections::TextSection;
+    use rust_my_doc_search::tantivy::{
+        tantivy_explain,
+        tantivy_facet::{self, FacetKind},
+        tantivy_jieba,
+        tantivy_metadata::{self, MetadataField},
+        tantivy_regex,
+        tantivy_search::{self, IndexDocument, SearchOptions, SortBy},
+        tantivy_spell, tantivy_synonym,
+    };
+    use rust_my_doc_search::util::text_utils;
 
     #[test]
     pub fn test_oepen_index() {
@@ -15,6 +26,7 @@ mod test {
                 .replace("\\", "/"),
             file_name: "20231102四川省环境政策研究与规划院华为云资源汇总.docx".to_string(),
             file_content: "租赁华为云资源情况".to_string(),
+            ..Default::default()
         };
         tantivy_search::insert_doc(&mut index, &doc).unwrap();
     }
@@ -23,15 +35,10 @@ mod test {
         println!("查询");
         let index = tantivy_search::open_index("./indices").unwrap();
         // let r =tantivy_search::search_doc(&index, "file_name:四川 AND file_content:情况".into(), 1, 10).unwrap();
-        let r = tantivy_search::search_doc(
-            &index,
-            "file_name:大文件成都亿橙".into(),
-            1,
-            10,
-        )
-        .unwrap();
+        let r =
+            tantivy_search::search_doc(&index, "file_name:大文件成都亿橙".into(), 1, 10).unwrap();
         for line in r {
-           println!("{:?}", line);
+            println!("{:?}", line);
         }
     }
     #[test]
@@ -42,7 +49,8 @@ mod test {
 
         let r = extractor.extract_file_to_string(file_path);
         if let Ok(content) = r {
-            let docs: Vec<IndexDocument> = tantivy_search::IndexDocument::split_to_list(file_path.to_string(), content.0, 1);
+            let docs: Vec<IndexDocument> =
+                tantivy_search::IndexDocument::split_to_list(file_path.to_string(), content.0, 1);
             // let docs: Vec<IndexDocument> = tantivy_search::IndexDocument::split_to_list(file_path.to_string(), "".into());
             for doc in docs {
                 println!("{}", doc.file_content);
@@ -51,6 +59,720 @@ mod test {
         }
     }
 
+    //结束时(包括断言失败)恢复默认词典和停用词, 不影响其他测试
+    struct DefaultDict;
+
+    impl Drop for DefaultDict {
+        fn drop(&mut self) {
+            let _ = tantivy_jieba::load_user_dict("./test_data/default_dict");
+        }
+    }
+
+    #[test]
+    pub fn test_load_user_dict() {
+        let _default_dict = DefaultDict;
+        let data_dir = "./test_data/user_dict";
+        std::fs::create_dir_all(format!("{}/dict", data_dir)).unwrap();
+        std::fs::write(format!("{}/dict/user.txt", data_dir), "亿橙云盘 100 nz\n").unwrap();
+        std::fs::write(format!("{}/stop_words.txt", data_dir), "的\nthe\n").unwrap();
+
+        tantivy_jieba::load_user_dict(data_dir).unwrap();
+        let tokens = tantivy_jieba::tokenize("亿橙云盘的文件");
+        assert!(tokens.contains(&"亿橙云盘".to_string()));
+        assert!(!tokens.contains(&"的".to_string()));
+        //停用词不区分大小写
+        let tokens = tantivy_jieba::tokenize("The Report");
+        assert!(!tokens.contains(&"The".to_string()));
+        assert!(tokens.contains(&"Report".to_string()));
+
+        tantivy_jieba::save_dict_signature(data_dir).unwrap();
+        assert!(!tantivy_jieba::dict_changed(data_dir));
+        std::fs::write(format!("{}/dict/user.txt", data_dir), "亿橙云盘 200 nz\n").unwrap();
+        assert!(tantivy_jieba::dict_changed(data_dir));
+
+        //没有签名文件、没有用户词典的数据目录不需要重建
+        let data_dir = "./test_data/no_dict_sig";
+        let _ = std::fs::remove_dir_all(data_dir);
+        std::fs::create_dir_all(data_dir).unwrap();
+        assert!(!tantivy_jieba::dict_changed(data_dir));
+        std::fs::write(format!("{}/stop_words.txt", data_dir), "的\n").unwrap();
+        assert!(tantivy_jieba::dict_changed(data_dir));
+    }
+
+    #[test]
+    pub fn test_jieba_analyzer() {
+        let index = tantivy::Index::create_in_ram(tantivy::schema::Schema::builder().build());
+        tantivy_search::register_analyzers(&index);
+        let mut analyzer = index.tokenizers().get("jieba").unwrap();
+        let mut token_stream = analyzer.token_stream("Reports 北京大学 Café");
+        let mut tokens = vec![];
+        while let Some(token) = token_stream.next() {
+            tokens.push(token.text.clone());
+        }
+        assert!(tokens.contains(&"report".to_string()));
+        assert!(tokens.contains(&"北京大学".to_string()));
+        assert!(tokens.contains(&"cafe".to_string()));
+    }
+
+    #[test]
+    pub fn test_search_substring() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/ngram_indices", &Default::default())
+                .unwrap();
+        let doc = IndexDocument {
+            index_dir_id: 1,
+            file_path: "D:/yiscn/合同.docx".to_string(),
+            file_name: "合同.docx".to_string(),
+            file_content: "甲方代表张华考上了北京大学,项目编号XM2024001".to_string(),
+            ..Default::default()
+        };
+        tantivy_search::insert_doc(&mut index, &doc).unwrap();
+
+        let r = tantivy_search::search_substring(&index, "华考", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        let r = tantivy_search::search_substring(&index, "xm2024", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        let r = tantivy_search::search_substring(&index, "华北", 1, 10).unwrap();
+        assert!(r.is_empty());
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-12 18:52:40
+    /// @Description: jieba结果太少时, 子串搜索的结果和jieba结果一起排序、分页
+    #[test]
+    pub fn test_search_fallback_sort_page() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/fallback_indices", &Default::default())
+                .unwrap();
+        //b、c是jieba结果, a只有子串命中
+        let contents = [
+            ("b.docx", "华考"),
+            ("c.docx", "华考 成绩"),
+            ("a.docx", "甲方代表张华考上了北京大学"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let text = "华考";
+        let query_str = tantivy_search::and_query_str(&tantivy_jieba::tokenize(text));
+        let options = tantivy_search::SearchOptions {
+            sort_by: tantivy_search::SortBy::FileName,
+            ..Default::default()
+        };
+        let r = tantivy_search::search_doc_with_fallback(
+            &index, &query_str, text, false, &options, 1, 2,
+        )
+        .unwrap();
+        let paths = r
+            .iter()
+            .map(|doc| doc.file_path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["D:/yiscn/a.docx", "D:/yiscn/b.docx"]);
+        let r = tantivy_search::search_doc_with_fallback(
+            &index, &query_str, text, false, &options, 2, 2,
+        )
+        .unwrap();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].file_path, "D:/yiscn/c.docx");
+    }
+
+    #[test]
+    pub fn test_optimize_index() {
+        let index_dir = "./test_data/optimize_indices";
+        let mut index = tantivy_search::recreate_index(index_dir, &Default::default()).unwrap();
+        for i in 0..3 {
+            let doc = IndexDocument {
+                index_dir_id: i,
+                file_path: format!("D:/yiscn/{}.txt", i),
+                file_name: format!("{}.txt", i),
+                file_content: "租赁华为云资源情况".to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        tantivy_search::delete_by_index_dir_id(&mut index, 1).unwrap();
+        tantivy_search::optimize_index(&index).unwrap();
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 1);
+        let r = tantivy_search::search_doc(&index, "华为", 1, 10).unwrap();
+        assert_eq!(r.len(), 2);
+        let size = rust_my_doc_search::app::disk_size(index_dir);
+        println!(
+            "索引大小:{}",
+            rust_my_doc_search::util::text_utils::format_size(size)
+        );
+    }
+
+    #[test]
+    pub fn test_search_similar() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/similar_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            (
+                "合同v1.docx",
+                "甲方租赁乙方华为云服务器资源，租赁期限一年，费用按月结算",
+            ),
+            (
+                "合同v2.docx",
+                "甲方租赁乙方华为云服务器资源，租赁期限两年，费用按季度结算",
+            ),
+            ("周报.docx", "本周完成了数据库迁移和接口联调"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let options = SearchOptions::default();
+        let r = tantivy_search::search_similar(&index, "D:/yiscn/合同v1.docx", &options, 1, 10)
+            .unwrap();
+        assert!(!r.is_empty());
+        assert_eq!(r[0].file_path, "D:/yiscn/合同v2.docx");
+        assert!(r.iter().all(|doc| doc.file_path != "D:/yiscn/合同v1.docx"));
+        //按搜索选项筛选
+        let options = SearchOptions {
+            index_dir_ids: vec![2],
+            ..Default::default()
+        };
+        let r = tantivy_search::search_similar(&index, "D:/yiscn/合同v1.docx", &options, 1, 10)
+            .unwrap();
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    pub fn test_search_prefix() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/prefix_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            ("报告.docx", "database migration report, database schema"),
+            ("说明.docx", "datasource configuration"),
+            ("周报.docx", "weekly summary"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let r =
+            tantivy_search::search_prefix(&index, "", "Data", &Default::default(), 1, 10).unwrap();
+        assert_eq!(r.len(), 2);
+        let r =
+            tantivy_search::search_prefix(&index, "migration", "data", &Default::default(), 1, 10)
+                .unwrap();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].file_path, "D:/yiscn/报告.docx");
+        //前缀和建索引时一样词干化: migrations -> migrat
+        let r = tantivy_search::search_prefix(&index, "", "Migrations", &Default::default(), 1, 10)
+            .unwrap();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].file_path, "D:/yiscn/报告.docx");
+
+        let suggestions = tantivy_search::suggest_terms(&index, "dat", 10).unwrap();
+        assert_eq!(
+            suggestions,
+            vec!["database".to_string(), "datasource".to_string()]
+        );
+    }
+
+    #[test]
+    pub fn test_did_you_mean() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/spell_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            ("合同.docx", "租赁华为云服务器"),
+            ("说明.docx", "database migration"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let r = tantivy_spell::did_you_mean(&index, "databse migration", 3).unwrap();
+        assert_eq!(r, vec!["database migration".to_string()]);
+        //首字母错误也能纠正, 重复的词只纠错一次
+        let r = tantivy_spell::did_you_mean(&index, "fatabase migration fatabase", 3).unwrap();
+        assert_eq!(r, vec!["database migration database".to_string()]);
+        let r = tantivy_spell::did_you_mean(&index, "花为云", 3).unwrap();
+        assert_eq!(r, vec!["华为云".to_string()]);
+        //有结果的词不纠错
+        let r = tantivy_spell::did_you_mean(&index, "migration", 3).unwrap();
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    pub fn test_synonym_expansion() {
+        let data_dir = "./test_data/synonym_data";
+        std::fs::create_dir_all(data_dir).unwrap();
+        std::fs::write(
+            format!("{}/{}", data_dir, tantivy_synonym::SYNONYMS_FILE),
+            "# 注释\n合同,协议\nPO,purchase order\n",
+        )
+        .unwrap();
+        tantivy_synonym::load_synonyms(data_dir).unwrap();
+
+        let mut index =
+            tantivy_search::recreate_index(&format!("{}/indices", data_dir), &Default::default())
+                .unwrap();
+        let contents = [
+            ("a.docx", "本协议由双方签订"),
+            ("b.docx", "本合同由双方签订"),
+            ("c.docx", "the purchase order was approved"),
+            ("d.docx", "order history"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let query_str = tantivy_synonym::expand_query(&tantivy_jieba::tokenize("合同"));
+        assert_eq!(query_str, r#"("合同"^2 OR "协议")"#);
+        let r = tantivy_search::search_doc(&index, &query_str, 1, 10).unwrap();
+        assert_eq!(r.len(), 2);
+        //原词排在同义词前面
+        assert_eq!(r[0].file_path, "D:/yiscn/b.docx");
+
+        let query_str = tantivy_synonym::expand_query(&tantivy_jieba::tokenize("po"));
+        let r = tantivy_search::search_doc(&index, &query_str, 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].file_path, "D:/yiscn/c.docx");
+
+        let query_str = tantivy_synonym::expand_query(&tantivy_jieba::tokenize("purchase order"));
+        assert_eq!(query_str, r#"("purchase order"^2 OR "PO")"#);
+    }
+
+    #[test]
+    pub fn test_search_regex() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/regex_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            ("a.log", "启动失败，错误码E1234，请重试"),
+            ("b.log", "错误码e999"),
+            ("c.txt", "联系电话 010-8888 转 E5678"),
+            ("d.log", "版本v1.2E1234"),
+        ];
+        for (name, content) in contents {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let options = SearchOptions::default();
+        //词典过滤
+        let r = tantivy_regex::search_regex(&index, r"E\d{4}", &options, 1, 10).unwrap();
+        assert_eq!(r.hits.len(), 3);
+        let hit = r
+            .hits
+            .iter()
+            .find(|hit| hit.doc.file_path == "D:/yiscn/a.log")
+            .unwrap();
+        let (start, end) = hit.matches[0];
+        assert_eq!(&hit.doc.file_content[start..end], "E1234");
+        //跨词的正则, 扫描所有文档
+        let r = tantivy_regex::search_regex(&index, r"\d{3}-\d{4}", &options, 1, 10).unwrap();
+        assert_eq!(r.hits.len(), 1);
+        assert_eq!(r.hits[0].doc.file_path, "D:/yiscn/c.txt");
+        assert_eq!(r.scanned, 4);
+        assert!(!r.truncated);
+        assert!(tantivy_regex::search_regex(&index, r"E\d{", &options, 1, 10).is_err());
+        //字符类中有'-', 扫描所有文档
+        let r = tantivy_regex::search_regex(&index, r"0[0-9-]{4}", &options, 1, 10).unwrap();
+        assert_eq!(r.hits.len(), 1);
+        assert_eq!(r.hits[0].doc.file_path, "D:/yiscn/c.txt");
+        //用词典筛选时只算分词内部的匹配: v1.2E1234 分成 v1.2、E1234
+        let r = tantivy_regex::search_regex(&index, r"2E\d{4}", &options, 1, 10).unwrap();
+        assert!(r.hits.is_empty());
+        //排序、筛选
+        let options = SearchOptions {
+            sort_by: SortBy::FileName,
+            desc: true,
+            ..Default::default()
+        };
+        let r = tantivy_regex::search_regex(&index, r"E\d{4}", &options, 1, 10).unwrap();
+        let paths = r
+            .hits
+            .iter()
+            .map(|hit| hit.doc.file_path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            paths,
+            vec!["D:/yiscn/d.log", "D:/yiscn/c.txt", "D:/yiscn/a.log"]
+        );
+        let r = tantivy_regex::search_regex(&index, r"E\d{4}", &options, 2, 2).unwrap();
+        assert_eq!(r.hits.len(), 1);
+        assert_eq!(r.hits[0].doc.file_path, "D:/yiscn/a.log");
+        let options = SearchOptions {
+            index_dir_ids: vec![2],
+            ..Default::default()
+        };
+        let r = tantivy_regex::search_regex(&index, r"E\d{4}", &options, 1, 10).unwrap();
+        assert!(r.hits.is_empty());
+    }
+
+    #[test]
+    pub fn test_search_sort_filter() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/sort_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            (1, "b.docx", 300, 1_700_000_000_000),
+            (1, "C.docx", 100, 1_600_000_000_000),
+            (2, "a.docx", 200, 1_800_000_000_000),
+        ];
+        for (index_dir_id, name, file_size, modified_time) in contents {
+            let doc = IndexDocument {
+                index_dir_id,
+                file_path: format!("D:/yiscn/{}/{}", index_dir_id, name),
+                file_name: name.to_string(),
+                file_content: "项目周报".to_string(),
+                modified_time,
+                file_size,
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let names = |options: &SearchOptions| {
+            tantivy_search::search_doc_with_options(&index, "周报", options, 1, 10)
+                .unwrap()
+                .into_iter()
+                .map(|doc| doc.file_name)
+                .collect::<Vec<String>>()
+        };
+        let mut options = SearchOptions {
+            sort_by: SortBy::ModifiedTime,
+            desc: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&options), vec!["a.docx", "b.docx", "C.docx"]);
+        options.sort_by = SortBy::FileSize;
+        options.desc = false;
+        assert_eq!(names(&options), vec!["C.docx", "a.docx", "b.docx"]);
+        //文件名忽略大小写
+        options.sort_by = SortBy::FileName;
+        assert_eq!(names(&options), vec!["a.docx", "b.docx", "C.docx"]);
+        options.desc = true;
+        assert_eq!(names(&options), vec!["C.docx", "b.docx", "a.docx"]);
+        //路径区分大小写
+        options.sort_by = SortBy::FilePath;
+        assert_eq!(names(&options), vec!["a.docx", "b.docx", "C.docx"]);
+        //限定索引目录
+        options.index_dir_ids = vec![1];
+        assert_eq!(names(&options), vec!["b.docx", "C.docx"]);
+        let r = tantivy_search::search_doc(&index, "周报", 1, 10).unwrap();
+        assert_eq!(r.len(), 3);
+        assert_eq!(r[0].file_size + r[1].file_size + r[2].file_size, 600);
+    }
+
+    #[test]
+    pub fn test_search_facets() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/facet_indices", &Default::default())
+                .unwrap();
+        let contents = [
+            (1, "a.docx", 50 * 1024),
+            (1, "b.PDF", 2 * 1024 * 1024),
+            (2, "c.docx", 3 * 1024 * 1024),
+        ];
+        for (index_dir_id, name, file_size) in contents {
+            //同一个文件的多个片段只统计一次
+            for content in ["项目周报 第一部分", "项目周报 第二部分"] {
+                let doc = IndexDocument {
+                    index_dir_id,
+                    file_path: format!("D:/yiscn/{}/{}", index_dir_id, name),
+                    file_name: name.to_string(),
+                    file_content: content.to_string(),
+                    modified_time: 1_738_368_000_000, //2025-02-01
+                    file_size,
+                    ..Default::default()
+                };
+                tantivy_search::insert_doc(&mut index, &doc).unwrap();
+            }
+        }
+        assert_eq!(tantivy_facet::file_type("b.PDF"), "pdf");
+        assert_eq!(tantivy_facet::size_bucket(50 * 1024), "<100KB");
+
+        let counts = |options: &SearchOptions, kind: FacetKind| {
+            tantivy_search::search_facets_with_fallback(&index, "周报", "周报", false, options)
+                .unwrap()
+                .into_iter()
+                .find(|group| group.kind == kind)
+                .unwrap()
+                .counts
+        };
+        let mut options = SearchOptions::default();
+        let file_types = counts(&options, FacetKind::FileType);
+        assert_eq!(
+            file_types,
+            vec![("docx".to_string(), 2), ("pdf".to_string(), 1)]
+        );
+        let dirs = counts(&options, FacetKind::SourceDir);
+        assert_eq!(dirs, vec![("1".to_string(), 2), ("2".to_string(), 1)]);
+        let sizes = counts(&options, FacetKind::SizeBucket);
+        assert_eq!(
+            sizes,
+            vec![("<100KB".to_string(), 1), ("1MB-10MB".to_string(), 2)]
+        );
+        assert_eq!(counts(&options, FacetKind::ModifiedMonth).len(), 1);
+
+        //按分面筛选, 其他分面的数量随之变化, 本分面不变
+        options.facet_filters = vec![(FacetKind::FileType, "docx".to_string())];
+        assert_eq!(counts(&options, FacetKind::FileType), file_types);
+        let dirs = counts(&options, FacetKind::SourceDir);
+        assert_eq!(dirs, vec![("1".to_string(), 1), ("2".to_string(), 1)]);
+        options.index_dir_ids = vec![2];
+        let r = tantivy_search::search_doc_with_options(&index, "周报", &options, 1, 10).unwrap();
+        assert!(r.iter().all(|doc| doc.file_name == "c.docx"));
+        assert_eq!(r.len(), 2);
+    }
+
+    #[test]
+    pub fn test_explain() {
+        let mut index =
+            tantivy_search::recreate_index("./test_data/explain_indices", &Default::default())
+                .unwrap();
+        for (name, content) in [("a.docx", "租赁华为云资源"), ("b.docx", "周报")] {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: content.to_string(),
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let options = Default::default();
+        let query_str = tantivy_search::and_query_str(&tantivy_jieba::tokenize("华为"));
+        let debug =
+            tantivy_explain::debug_query(&index, "华为", &query_str, false, &options).unwrap();
+        assert!(debug.tokens.contains(&"华为".to_string()));
+        assert!(debug.query_tree.contains("华为"));
+        let explanation = tantivy_explain::explain_file(
+            &index,
+            "华为",
+            &query_str,
+            false,
+            &options,
+            "D:/yiscn/a.docx",
+        )
+        .unwrap()
+        .unwrap();
+        println!("{}", explanation);
+        assert!(explanation.contains("value"));
+        let explanation = tantivy_explain::explain_file(
+            &index,
+            "华为",
+            &query_str,
+            false,
+            &options,
+            "D:/yiscn/b.docx",
+        )
+        .unwrap();
+        assert!(explanation.is_none());
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-02-28 15:02:41
+    /// @Description: 元数据字段查询: author:张三、pages:>10、created:2024、modified:>2024
+    #[test]
+    pub fn test_metadata_filter() {
+        let text = "作者：张三 title:\"年度 报告\" 合同 http://a pages:abc";
+        let (rest, filters) = tantivy_metadata::parse_filters(text);
+        assert_eq!(rest, "合同 http://a pages:abc");
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].field, MetadataField::Author);
+        assert_eq!(filters[1].value, "年度 报告");
+
+        let mut index =
+            tantivy_search::recreate_index("./test_data/metadata_indices", &Default::default())
+                .unwrap();
+        let metadatas = [
+            (
+                "a.docx",
+                "张三",
+                "年度报告",
+                12,
+                "2024-03-05T10:00:00",
+                "2024-03-06T09:00:00",
+            ),
+            (
+                "b.pdf",
+                "李四",
+                "合同",
+                3,
+                "2023-12-31T23:00:00",
+                "2025-01-02T08:00:00",
+            ),
+            ("c.txt", "", "", 0, "", ""),
+        ];
+        for (name, author, title, page_count, created, modified) in metadatas {
+            let doc = IndexDocument {
+                index_dir_id: 1,
+                file_path: format!("D:/yiscn/{}", name),
+                file_name: name.to_string(),
+                file_content: "项目合同".to_string(),
+                metadata: DocMetadata {
+                    author: author.to_string(),
+                    title: title.to_string(),
+                    page_count,
+                    created_time: doc_metadata::parse_time(created),
+                    modified_time: doc_metadata::parse_time(modified),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            tantivy_search::insert_doc(&mut index, &doc).unwrap();
+        }
+        let names = |text: &str| {
+            let (rest, filters) = tantivy_metadata::parse_filters(text);
+            let options = SearchOptions {
+                metadata_filters: filters,
+                ..Default::default()
+            };
+            let query_str = match rest.is_empty() {
+                true => "*".to_string(),
+                false => tantivy_search::and_query_str(&tantivy_jieba::tokenize(&rest)),
+            };
+            let mut names =
+                tantivy_search::search_doc_with_options(&index, &query_str, &options, 1, 10)
+                    .unwrap()
+                    .into_iter()
+                    .map(|doc| doc.file_name)
+                    .collect::<Vec<String>>();
+            names.sort();
+            names
+        };
+        assert_eq!(names("author:张三"), vec!["a.docx"]);
+        assert_eq!(names("合同 作者:李四"), vec!["b.pdf"]);
+        assert_eq!(names("title:\"年度报告\""), vec!["a.docx"]);
+        assert_eq!(names("pages:>10"), vec!["a.docx"]);
+        assert_eq!(names("pages:<=3"), vec!["b.pdf"]);
+        assert_eq!(names("created:2024"), vec!["a.docx"]);
+        assert_eq!(names("created:<2024-01"), vec!["b.pdf"]);
+        assert_eq!(names("created:2024-03-05"), vec!["a.docx"]);
+        assert_eq!(names("modified:>2024"), vec!["b.pdf"]);
+        assert_eq!(names("修改:2024-03"), vec!["a.docx"]);
+        assert_eq!(names("modified:>=2024 created:<2024"), vec!["b.pdf"]);
+        assert!(names("author:王五").is_empty());
+
+        let docs = tantivy_search::search_doc(&index, "*", 1, 10).unwrap();
+        let a = docs.iter().find(|doc| doc.file_name == "a.docx").unwrap();
+        assert_eq!(a.metadata.author, "张三");
+        assert_eq!(a.metadata.page_count, 12);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-07 14:10:25
+    /// @Description: 文本文件按行切分, 片段保留换行并记录起始行号, 搜索结果定位到行
+    #[test]
+    pub fn test_split_lines() {
+        let mut text = String::new();
+        for i in 1..=300 {
+            match i {
+                100 => text.push_str(&"长".repeat(1200)),
+                200 => {}
+                250 => text.push_str("2025-03-07 ERROR 连接数据库超时"),
+                _ => text.push_str(&format!("2025-03-07 INFO 第{}行", i)),
+            }
+            text.push_str("\r\n");
+        }
+        let docs = IndexDocument::split_sections_to_list(
+            "D:/yiscn/app.log".into(),
+            vec![TextSection::lines(1, text)],
+            1,
+            DocMetadata::default(),
+        );
+        assert!(docs.len() > 3);
+        assert_eq!(docs[0].line, 1);
+        assert!(docs[0]
+            .file_content
+            .starts_with("2025-03-07 INFO 第1行\n2025-03-07 INFO 第2行\n"));
+        //片段之间行号连续, 超长的行单独切割
+        let mut next_line = 1;
+        for doc in docs.iter() {
+            assert!(doc.line == next_line || (doc.line == 100 && next_line == 101));
+            assert!(!doc.file_content.contains('\r'));
+            next_line = doc.line + doc.file_content.matches('\n').count() as u64;
+        }
+        assert!(docs.iter().filter(|doc| doc.line == 100).count() >= 2);
+
+        let mut index =
+            tantivy_search::recreate_index("./test_data/lines_indices", &Default::default())
+                .unwrap();
+        tantivy_search::insert_doc_list(&mut index, &docs).unwrap();
+        let r = tantivy_search::search_doc(&index, "超时", 1, 10).unwrap();
+        assert_eq!(r.len(), 1);
+        assert!(r[0].line > 100 && r[0].line <= 250);
+        let hits = text_utils::line_hits(
+            &r[0].file_content,
+            r[0].line,
+            &vec!["超时".to_string()],
+            1,
+            3,
+        );
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, 250);
+        assert_eq!(
+            hits[0].context,
+            vec![
+                (249, "2025-03-07 INFO 第249行".to_string()),
+                (250, "2025-03-07 ERROR 连接数据库超时".to_string()),
+                (251, "2025-03-07 INFO 第251行".to_string()),
+            ]
+        );
+        //片段边界处的行从相邻片段取
+        let boundary = docs[1].line;
+        assert!(boundary < 100);
+        let lines =
+            tantivy_search::search_lines(&index, "D:/yiscn/app.log", boundary - 1, boundary + 1)
+                .unwrap();
+        let expected = (boundary - 1..=boundary + 1)
+            .map(|i| (i, format!("2025-03-07 INFO 第{}行", i)))
+            .collect::<Vec<(u64, String)>>();
+        assert_eq!(lines, expected);
+        //超长的行只取第一段
+        let lines = tantivy_search::search_lines(&index, "D:/yiscn/app.log", 99, 101).unwrap();
+        assert_eq!(
+            lines.iter().map(|(i, _)| *i).collect::<Vec<u64>>(),
+            vec![99, 100, 101]
+        );
+        assert!(lines[1].1.chars().all(|c| c == '长'));
+        let lines = tantivy_search::search_lines(&index, "D:/yiscn/app.log", 200, 200).unwrap();
+        assert_eq!(lines, vec![(200, String::new())]);
+    }
+
     #[test]
     pub fn test_tokenize() {
         let tokens = tantivy_jieba::tokenize("数据库");
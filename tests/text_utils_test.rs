@@ -1,17 +1,51 @@
- //! @Author: DengLibin
- //! @Date: Create in 2024-12-19 14:26:34
- //! @Description: 
+//! @Author: DengLibin
+//! @Date: Create in 2024-12-19 14:26:34
+//! @Description:
 
- mod test {
-    use rust_my_doc_search::util::text_utils::wrap_keywords;
+mod test {
+    use rust_my_doc_search::util::text_utils::{self, wrap_keywords, LineHit};
 
     #[test]
-    pub fn test_wrap_keywords(){
+    pub fn test_wrap_keywords() {
         let text = "数据库是一种数据存储系统";
-      
+
         let keywords = vec!["数据库".to_string(), "".to_string()];
         let new_str = wrap_keywords(text, &keywords, "<tag>", "</tag>");
 
-        println!("{}",  new_str);
+        println!("{}", new_str);
+    }
+
+    /// @Author: DengLibin
+    /// @Date: Create in 2025-03-07 10:40:18
+    /// @Description: 命中的行和上下文
+    #[test]
+    pub fn test_line_hits() {
+        let chunk = "a\nfn connect() {\n    timeout\n}\nb\nc\ntimeout\n";
+        let keywords = vec!["timeout".to_string()];
+        let hits = text_utils::line_hits(chunk, 10, &keywords, 1, 3);
+        assert_eq!(
+            hits,
+            vec![
+                LineHit {
+                    line: 12,
+                    context: vec![
+                        (11, "fn connect() {".to_string()),
+                        (12, "    timeout".to_string()),
+                        (13, "}".to_string()),
+                    ],
+                },
+                LineHit {
+                    line: 16,
+                    context: vec![(15, "c".to_string()), (16, "timeout".to_string())],
+                },
+            ]
+        );
+        //上下文中的命中行不重复列出
+        let hits = text_utils::line_hits("x\ny\nx", 1, &vec!["x".to_string()], 2, 3);
+        assert_eq!(hits.len(), 1);
+        //没有命中时取开头
+        let hits = text_utils::line_hits("a\nb\nc", 5, &vec!["z".to_string()], 1, 3);
+        assert_eq!(hits[0].line, 5);
+        assert_eq!(hits[0].context.len(), 2);
     }
- }
\ No newline at end of file
+}